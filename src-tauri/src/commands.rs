@@ -0,0 +1,944 @@
+use super::*;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tauri::Emitter;
+use tauri::Manager;
+
+pub(crate) struct RuntimeState {
+    pub(crate) tcp_bind: Option<String>,
+    pub(crate) tcp_enabled: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EngineCapabilities {
+    platform: &'static str,
+    version: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocol_version: Option<u32>,
+    trash: bool,
+    duplicates: bool,
+    drive_classification: bool,
+    network_mount_detection: bool,
+    trash_folder_detection: bool,
+    unix_permissions: bool,
+    context_menu: bool,
+    tls: bool,
+    compression: bool,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BatchRenameItem {
+    path: String,
+    new_path: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BatchRenameResult {
+    success_count: u32,
+    errors: Vec<String>,
+}
+
+#[tauri::command]
+pub(crate) fn batch_rename(items: Vec<BatchRenameItem>) -> Result<BatchRenameResult, String> {
+    let mut success_count = 0;
+    let mut errors = Vec::new();
+
+    for item in items {
+        let source = PathBuf::from(&item.path);
+        let dest = PathBuf::from(&item.new_path);
+
+        if !source.exists() {
+            errors.push(format!("Source does not exist: {}", item.path));
+            continue;
+        }
+
+        // Safety check to prevent accidental overwrites
+        if dest.exists() {
+            // Check if it's a case-only rename (e.g. "a.txt" -> "A.TXT")
+            let source_str = source.to_string_lossy().to_string();
+            let dest_str = dest.to_string_lossy().to_string();
+
+            // If paths are different but look the same in lowercase, it's likely a case rename.
+            // On case-insensitive filesystems (Windows/macOS default), dest.exists() is true for case rename.
+            // On case-sensitive (Linux), it is false unless a file named "A.TXT" actually exists.
+
+            // If it is NOT a case-only rename, fail.
+            if source_str != dest_str && source_str.to_lowercase() != dest_str.to_lowercase() {
+                errors.push(format!("Destination already exists: {}", item.new_path));
+                continue;
+            }
+        }
+
+        match fs::rename(&source, &dest) {
+            Ok(_) => success_count += 1,
+            Err(e) => errors.push(format!("Failed to rename {}: {}", item.path, e)),
+        }
+    }
+
+    Ok(BatchRenameResult {
+        success_count,
+        errors,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn get_startup_path(state: tauri::State<StartupPath>) -> Option<String> {
+    state.0.lock().unwrap().clone()
+}
+
+#[tauri::command]
+pub(crate) fn get_launch_context(state: tauri::State<LaunchContextState>) -> LaunchContext {
+    state.0.lock().unwrap().clone()
+}
+
+/// Fields of `options` are laid over the saved preset named `preset`, if any.
+#[tauri::command]
+pub(crate) fn scan_path(
+    window: tauri::Window,
+    path: String,
+    options: Option<JsonValue>,
+    preset: Option<String>,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+    settings: tauri::State<SettingsState>,
+) -> Result<(), String> {
+    let options = {
+        let current = settings
+            .value
+            .lock()
+            .map_err(|_| "Failed to lock settings".to_string())?;
+        resolve_scan_options(&settings.effective(&current), preset.as_deref(), options)?
+    };
+    scan_paths(window, vec![path], options, id, state)
+}
+
+/// The old scan still sends `scan-cancelled`, but its cleanup leaves the new scan alone.
+#[tauri::command]
+pub(crate) fn restart_scan(
+    window: tauri::Window,
+    path: String,
+    options: ScanOptions,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    scan_paths(window, vec![path], options, id, state)
+}
+
+#[tauri::command]
+pub(crate) fn scan_paths(
+    window: tauri::Window,
+    paths: Vec<String>,
+    options: ScanOptions,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let roots = resolve_scan_roots(&paths)?;
+
+    let config = build_scan_config(&options)?;
+    let app_handle = window.app_handle().clone();
+    spawn_window_task(&window, &state, id, move |control, forward, id| {
+        let held_id = id.clone();
+        let emitter: ScanEmitter = Arc::new(move |event| {
+            if let (ScanEvent::Complete(summary), Some(id)) = (&event, held_id.as_deref()) {
+                app_handle
+                    .state::<HeldSummaries>()
+                    .hold(id, summary.clone());
+            }
+            forward(event);
+        });
+        run_scan(roots, config, control, emitter, id)
+    })
+}
+
+#[tauri::command]
+pub(crate) fn find_duplicates(
+    window: tauri::Window,
+    path: String,
+    options: ScanOptions,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    let config = build_scan_config(&options)?;
+    spawn_window_task(&window, &state, id, move |control, emitter, id| {
+        run_duplicate_scan(root, config, control, emitter, id)
+    })
+}
+
+#[tauri::command]
+pub(crate) fn watch_directory(
+    window: tauri::Window,
+    path: String,
+    interval_ms: Option<u64>,
+    state: tauri::State<DirectoryWatchState>,
+) -> Result<String, String> {
+    let target = PathBuf::from(&path);
+    if !target.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+    let interval = Duration::from_millis(
+        interval_ms
+            .unwrap_or(DEFAULT_WATCH_INTERVAL_MS)
+            .max(MIN_WATCH_INTERVAL_MS),
+    );
+    let (id, stop) = register_watch(&state, window.label())?;
+
+    let watch_id = id.clone();
+    thread::spawn(move || {
+        let mut last: Option<DirectorySnapshot> = None;
+        while !stop.load(Ordering::Relaxed) {
+            match measure_directory_shallow(&target) {
+                Ok((size_bytes, file_count, dir_count)) => {
+                    let snapshot = DirectorySnapshot {
+                        id: watch_id.clone(),
+                        path: path.clone(),
+                        size_bytes,
+                        file_count,
+                        dir_count,
+                    };
+                    if last.as_ref() != Some(&snapshot) {
+                        if window
+                            .emit("directory-watch-changed", snapshot.clone())
+                            .is_err()
+                        {
+                            break;
+                        }
+                        last = Some(snapshot);
+                    }
+                }
+                Err(message) => {
+                    let _ = window.emit(
+                        "directory-watch-error",
+                        serde_json::json!({ "id": watch_id, "message": message }),
+                    );
+                    break;
+                }
+            }
+            let deadline = Instant::now() + interval;
+            while Instant::now() < deadline && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+        if let Ok(mut watches) = window
+            .app_handle()
+            .state::<DirectoryWatchState>()
+            .watches
+            .lock()
+        {
+            watches.remove(&watch_id);
+        }
+    });
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub(crate) fn stop_watch_directory(id: String, state: tauri::State<DirectoryWatchState>) -> bool {
+    stop_watch(&state, &id)
+}
+
+#[tauri::command]
+pub(crate) fn watch_path(
+    window: tauri::Window,
+    path: String,
+    filters: Option<ScanFilters>,
+    state: tauri::State<DirectoryWatchState>,
+) -> Result<String, String> {
+    let root =
+        fs::canonicalize(&path).map_err(|error| format!("Failed to resolve {path}: {error}"))?;
+    if !root.is_dir() {
+        return Err("Path is not a directory".to_string());
+    }
+    let filters = build_filter_config(&filters.unwrap_or_default()).map_err(first_option_error)?;
+    let (id, stop) = register_watch(&state, window.label())?;
+    let emitter_window = window.clone();
+    let emitter: ScanEmitter = Arc::new(move |event| emit_to_window(&emitter_window, event));
+    let app = window.app_handle().clone();
+    let watch_id = id.clone();
+    let started = start_path_watch(
+        root,
+        filters,
+        id.clone(),
+        stop,
+        emitter,
+        Box::new(move || {
+            if let Ok(mut watches) = app.state::<DirectoryWatchState>().watches.lock() {
+                watches.remove(&watch_id);
+            }
+        }),
+    );
+    if let Err(error) = started {
+        stop_watch(&state, &id);
+        return Err(error);
+    }
+    Ok(id)
+}
+
+#[tauri::command]
+pub(crate) fn unwatch_path(id: String, state: tauri::State<DirectoryWatchState>) -> bool {
+    stop_watch(&state, &id)
+}
+
+/// Fails with every problem found, one per field, without touching the disk.
+#[tauri::command]
+pub(crate) fn validate_scan_options(options: ScanOptions) -> Result<(), Vec<ScanOptionError>> {
+    let errors = scan_option_errors(&options);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// `root` defaults to the file's parent; `size` overrides the size on disk.
+#[tauri::command]
+pub(crate) fn explain_file(
+    path: String,
+    size: Option<u64>,
+    filters: ScanFilters,
+    root: Option<String>,
+) -> Result<FileExplanation, String> {
+    let file = PathBuf::from(&path);
+    let root = match root {
+        Some(root) => PathBuf::from(root),
+        None => file
+            .parent()
+            .map(|parent| parent.to_path_buf())
+            .ok_or_else(|| "Path has no parent folder".to_string())?,
+    };
+    let filter_config = build_filter_config(&filters).map_err(first_option_error)?;
+    let meta = fs::metadata(&file).ok();
+    let size_bytes = match size {
+        Some(size) => size,
+        None => meta
+            .as_ref()
+            .map(|meta| meta.len())
+            .ok_or_else(|| "Path does not exist".to_string())?,
+    };
+    let modified = meta.and_then(|meta| get_time_millis(meta.modified()));
+    Ok(explain_file_filters(
+        &root,
+        &file,
+        size_bytes,
+        modified,
+        &filter_config,
+    ))
+}
+
+#[tauri::command]
+pub(crate) fn count_entries(
+    window: tauri::Window,
+    path: String,
+    options: ScanOptions,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    let config = build_scan_config(&options)?;
+    spawn_window_task(&window, &state, id, move |control, emitter, id| {
+        run_entry_count(root, config, control, emitter, id)
+    })
+}
+
+#[tauri::command]
+pub(crate) fn find_recent_files(
+    window: tauri::Window,
+    path: String,
+    options: ScanOptions,
+    limit: Option<usize>,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("Path does not exist".to_string());
+    }
+    let limit = limit.unwrap_or(DEFAULT_RECENT_FILES_LIMIT);
+    if limit == 0 || limit > MAX_RECENT_FILES_LIMIT {
+        return Err(format!(
+            "Limit must be between 1 and {}",
+            MAX_RECENT_FILES_LIMIT
+        ));
+    }
+
+    let config = build_scan_config(&options)?;
+    spawn_window_task(&window, &state, id, move |control, emitter, id| {
+        run_recent_files(root, config, limit, control, emitter, id)
+    })
+}
+
+#[tauri::command]
+pub(crate) fn find_same_name_files(
+    window: tauri::Window,
+    path: String,
+    options: ScanOptions,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    let config = build_scan_config(&options)?;
+    spawn_window_task(&window, &state, id, move |control, emitter, id| {
+        run_name_groups(root, config, control, emitter, id)
+    })
+}
+
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn find_archive_candidates(
+    window: tauri::Window,
+    path: String,
+    options: ScanOptions,
+    min_size_bytes: Option<u64>,
+    min_age_days: Option<u64>,
+    limit: Option<usize>,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("Path does not exist".to_string());
+    }
+    let limit = limit.unwrap_or(DEFAULT_ARCHIVE_CANDIDATES_LIMIT);
+    if limit == 0 || limit > MAX_ARCHIVE_CANDIDATES_LIMIT {
+        return Err(format!(
+            "Limit must be between 1 and {}",
+            MAX_ARCHIVE_CANDIDATES_LIMIT
+        ));
+    }
+    let min_size_bytes = min_size_bytes.unwrap_or(DEFAULT_ARCHIVE_MIN_SIZE_BYTES);
+    let min_age_days = min_age_days.unwrap_or(DEFAULT_ARCHIVE_MIN_AGE_DAYS);
+
+    let config = build_scan_config(&options)?;
+    spawn_window_task(&window, &state, id, move |control, emitter, id| {
+        run_archive_candidates(
+            root,
+            config,
+            min_size_bytes,
+            min_age_days,
+            limit,
+            control,
+            emitter,
+            id,
+        )
+    })
+}
+
+#[tauri::command]
+pub(crate) fn estimate_compression(
+    window: tauri::Window,
+    path: String,
+    options: ScanOptions,
+    samples: Option<usize>,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("Path does not exist".to_string());
+    }
+    let samples = samples.unwrap_or(DEFAULT_COMPRESSION_SAMPLES);
+    if samples == 0 || samples > MAX_COMPRESSION_SAMPLES {
+        return Err(format!(
+            "Samples must be between 1 and {}",
+            MAX_COMPRESSION_SAMPLES
+        ));
+    }
+
+    let config = build_scan_config(&options)?;
+    spawn_window_task(&window, &state, id, move |control, emitter, id| {
+        run_compression_estimate(root, config, samples, control, emitter, id)
+    })
+}
+
+#[tauri::command]
+pub(crate) fn scan_to_ndjson(
+    window: tauri::Window,
+    path: String,
+    out_path: String,
+    options: ScanOptions,
+    id: Option<String>,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    let config = build_scan_config(&options)?;
+    // Open without truncating so a bad path fails here, but an existing export is
+    // only emptied once the scan is actually ours to run.
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&out_path)
+        .map_err(|error| error.to_string())?;
+    spawn_window_task(&window, &state, id, move |control, emitter, id| {
+        file.set_len(0).map_err(|error| error.to_string())?;
+        let mut out = std::io::BufWriter::new(file);
+        run_ndjson_export(root, config, control, emitter, id, &mut out)
+    })
+}
+
+/// Runs `task` as this window's scan on the async runtime. An error it returns is
+/// emitted as `scan-error`, and the window's slot is released once it ends.
+fn spawn_window_task<F>(
+    window: &tauri::Window,
+    state: &ScanCancellation,
+    id: Option<String>,
+    task: F,
+) -> Result<(), String>
+where
+    F: FnOnce(ScanControl, ScanEmitter, Option<String>) -> Result<(), String> + Send + 'static,
+{
+    let label = window.label().to_string();
+    let control = ScanControl::default();
+    state.claim(&label, &control, id.clone())?;
+    let window_for_task = window.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let app_handle = window_for_task.app_handle();
+        let emitter = window_emitter(window_for_task.clone());
+        if let Err(error) = task(control.clone(), emitter.clone(), id.clone()) {
+            emitter(ScanEvent::Error(error));
+        }
+        release_window_scan(app_handle, &label, &control, id.as_deref());
+    });
+
+    Ok(())
+}
+
+fn release_window_scan(
+    app: &tauri::AppHandle,
+    label: &str,
+    control: &ScanControl,
+    id: Option<&str>,
+) {
+    app.state::<ScanCancellation>().release(label, control);
+    if let Some(id) = id {
+        app.state::<FinishedScans>().record(id);
+    }
+}
+
+#[tauri::command]
+pub(crate) fn is_scan_active(
+    id: String,
+    scans: tauri::State<ScanCancellation>,
+    finished: tauri::State<FinishedScans>,
+) -> Result<ScanStatus, String> {
+    let running = scans
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock scan state".to_string())?
+        .values()
+        .any(|scan| scan.id.as_deref() == Some(id.as_str()));
+    Ok(finished.status(&id, running))
+}
+
+#[tauri::command]
+pub(crate) fn get_summary(
+    id: String,
+    held: tauri::State<HeldSummaries>,
+) -> Result<ScanSummary, String> {
+    held.summary(&id)
+}
+
+/// `destination` may not exist yet; its nearest existing parent is measured.
+#[tauri::command]
+pub(crate) fn check_transfer_fit(
+    id: String,
+    destination: String,
+    held: tauri::State<HeldSummaries>,
+) -> Result<TransferFit, String> {
+    let target = PathBuf::from(&destination);
+    let existing = target
+        .ancestors()
+        .find(|path| !path.as_os_str().is_empty() && path.exists())
+        .ok_or_else(|| "path-not-found".to_string())?;
+    let snapshot = compute_disk_usage(existing)?;
+    held.with_summary(&id, |summary| transfer_fit(summary, &snapshot))
+}
+
+#[tauri::command]
+pub(crate) fn diff_scans(
+    old: ScanSummary,
+    new: ScanSummary,
+    threshold: Option<u64>,
+) -> Result<ScanDiff, String> {
+    diff_summaries(&old, &new, threshold.unwrap_or(0))
+}
+
+#[tauri::command]
+pub(crate) fn list_scan_files(
+    id: String,
+    sort: Option<FileSortKey>,
+    offset: Option<usize>,
+    limit: usize,
+    held: tauri::State<HeldSummaries>,
+) -> Result<FilePage, String> {
+    if limit == 0 || limit > MAX_FILE_PAGE_SIZE {
+        return Err(format!(
+            "Limit must be between 1 and {}",
+            MAX_FILE_PAGE_SIZE
+        ));
+    }
+    held.file_page(&id, sort.unwrap_or_default(), offset.unwrap_or(0), limit)
+}
+
+#[tauri::command]
+pub(crate) fn cancel_scan(
+    window: tauri::Window,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    state.cancel(window.label())
+}
+
+#[tauri::command]
+pub(crate) fn pause_scan(
+    window: tauri::Window,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    set_scan_paused(&window, &state, true)
+}
+
+#[tauri::command]
+pub(crate) fn resume_scan(
+    window: tauri::Window,
+    state: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    set_scan_paused(&window, &state, false)
+}
+
+fn set_scan_paused(
+    window: &tauri::Window,
+    state: &tauri::State<ScanCancellation>,
+    paused: bool,
+) -> Result<(), String> {
+    let label = window.label().to_string();
+    let cancellations = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock scan state".to_string())?;
+    let scan = cancellations
+        .get(&label)
+        .ok_or_else(|| "No active scan".to_string())?;
+    scan.control.pause.store(paused, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn get_disk_usage(path: String) -> Result<DiskUsageSnapshot, String> {
+    let target = PathBuf::from(&path);
+    compute_disk_usage(&target)
+}
+
+#[tauri::command]
+pub(crate) fn get_capabilities() -> EngineCapabilities {
+    engine_capabilities()
+}
+
+pub(crate) fn engine_capabilities() -> EngineCapabilities {
+    let desktop = cfg!(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux"
+    ));
+    EngineCapabilities {
+        platform: std::env::consts::OS,
+        version: env!("CARGO_PKG_VERSION"),
+        #[cfg(feature = "remote")]
+        protocol_version: Some(PROTOCOL_VERSION),
+        #[cfg(not(feature = "remote"))]
+        protocol_version: None,
+        trash: desktop,
+        duplicates: true,
+        drive_classification: desktop,
+        network_mount_detection: cfg!(any(target_os = "macos", target_os = "linux")),
+        trash_folder_detection: true,
+        unix_permissions: cfg!(unix),
+        context_menu: cfg!(target_os = "windows"),
+        tls: cfg!(feature = "remote"),
+        compression: cfg!(feature = "remote"),
+    }
+}
+
+#[tauri::command]
+pub(crate) fn classify_drive(path: String) -> Result<DriveClassification, String> {
+    let target =
+        fs::canonicalize(&path).map_err(|error| format!("Failed to resolve {path}: {error}"))?;
+    Ok(classify_drive_path(&target))
+}
+
+#[tauri::command]
+pub(crate) fn delete_item(path: String) -> Result<(), String> {
+    let target = PathBuf::from(&path);
+    if !target.exists() {
+        return Err("Path does not exist".to_string());
+    }
+    if target.is_file() {
+        fs::remove_file(target).map_err(|e| e.to_string())?;
+    } else {
+        fs::remove_dir_all(target).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) async fn delete_to_trash(paths: Vec<String>) -> Result<DeleteReport, String> {
+    // Measuring walks every folder being trashed, so keep it off the main thread.
+    tauri::async_runtime::spawn_blocking(move || {
+        measure_delete(&paths, &[], || trash_paths(&paths, &[]))
+    })
+    .await
+    .map_err(|error| error.to_string())
+}
+
+#[tauri::command]
+pub(crate) fn rename_item(path: String, new_path: String) -> Result<(), String> {
+    let target = PathBuf::from(&path);
+    let dest = PathBuf::from(&new_path);
+    if !target.exists() {
+        return Err("Source path does not exist".to_string());
+    }
+    if dest.exists() {
+        return Err("Destination already exists".to_string());
+    }
+    fs::rename(target, dest).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn create_folder(path: String) -> Result<(), String> {
+    let target = PathBuf::from(&path);
+    if target.exists() {
+        return Err("Path already exists".to_string());
+    }
+    fs::create_dir_all(target).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn copy_item(path: String, new_path: String) -> Result<(), String> {
+    let source = PathBuf::from(&path);
+    let dest = PathBuf::from(&new_path);
+    if !source.exists() {
+        return Err("Source does not exist".to_string());
+    }
+    if dest.exists() {
+        return Err("Destination exists".to_string());
+    }
+    if source.is_file() {
+        fs::copy(source, dest).map_err(|e| e.to_string())?;
+    } else {
+        copy_dir_recursive(&source, &dest)?;
+    }
+    Ok(())
+}
+
+/// Errors carry the remote error codes, e.g. `destination-exists` or `cross-device`.
+#[tauri::command]
+pub(crate) fn move_path(window: tauri::Window, from: String, to: String) -> Result<String, String> {
+    let mut on_progress = |progress: &TransferProgress| {
+        let _ = window.emit("move-progress", progress.clone());
+    };
+    move_path_to(&from, &to, &mut on_progress)
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|error| error.message())
+}
+
+/// With `overwrite`, existing files are replaced and folders are merged.
+#[tauri::command]
+pub(crate) fn copy_path(
+    window: tauri::Window,
+    from: String,
+    to: String,
+    overwrite: Option<bool>,
+    state: tauri::State<CopyCancellation>,
+) -> Result<(), String> {
+    let plan = plan_copy(&from, &to, overwrite.unwrap_or(false)).map_err(|e| e.message())?;
+    let label = window.label().to_string();
+    let control = ScanControl::default();
+    {
+        let mut copies = state
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock copy state".to_string())?;
+        if copies.contains_key(&label) {
+            return Err("A copy is already running in this window".to_string());
+        }
+        copies.insert(label.clone(), control.clone());
+    }
+    let window_for_task = window.clone();
+    tauri::async_runtime::spawn(async move {
+        let progress_window = window_for_task.clone();
+        let mut on_progress = |progress: &TransferProgress| {
+            let _ = progress_window.emit("copy-progress", progress.clone());
+        };
+        match run_copy(&plan, &control, &mut on_progress) {
+            Ok(Some(progress)) => {
+                let _ = window_for_task.emit("copy-complete", progress);
+            }
+            Ok(None) => {
+                let _ = window_for_task.emit("copy-cancelled", "Copy cancelled");
+            }
+            Err(error) => {
+                let _ = window_for_task.emit("copy-error", error.message());
+            }
+        }
+        let copies = window_for_task.app_handle().state::<CopyCancellation>();
+        if let Ok(mut map) = copies.0.lock() {
+            map.remove(&label);
+        };
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn cancel_copy(
+    window: tauri::Window,
+    state: tauri::State<CopyCancellation>,
+) -> Result<(), String> {
+    let copies = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock copy state".to_string())?;
+    if let Some(control) = copies.get(window.label()) {
+        control.cancel.store(true, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn restart_app(
+    app: tauri::AppHandle,
+    scans: tauri::State<ScanCancellation>,
+) -> Result<(), String> {
+    if let Ok(cancellations) = scans.0.lock() {
+        for scan in cancellations.values() {
+            scan.control.cancel.store(true, Ordering::SeqCst);
+        }
+    }
+    #[cfg(feature = "remote")]
+    if let Some(server) = app.try_state::<RemoteServerState>() {
+        let handle = server
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock remote server".to_string())?
+            .take();
+        if let Some(handle) = handle {
+            handle.hub.cancel_scan(None);
+            stop_remote_server(handle);
+        }
+    }
+    app.restart()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TcpStatusSnapshot {
+    available: bool,
+    enabled: bool,
+    bind: Option<String>,
+}
+
+#[tauri::command]
+pub(crate) fn get_tcp_status(state: tauri::State<RuntimeState>) -> TcpStatusSnapshot {
+    TcpStatusSnapshot {
+        available: cfg!(feature = "remote"),
+        enabled: state.tcp_enabled,
+        bind: state.tcp_bind.clone(),
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+const FEATURE_DISABLED: &str = "feature-disabled: this build does not include remote support";
+
+#[cfg(not(feature = "remote"))]
+#[tauri::command]
+pub(crate) fn remote_connect() -> Result<(), String> {
+    Err(FEATURE_DISABLED.to_string())
+}
+
+#[cfg(not(feature = "remote"))]
+#[tauri::command]
+pub(crate) fn remote_disconnect() -> Result<(), String> {
+    Err(FEATURE_DISABLED.to_string())
+}
+
+#[cfg(not(feature = "remote"))]
+#[tauri::command]
+pub(crate) fn remote_send() -> Result<(), String> {
+    Err(FEATURE_DISABLED.to_string())
+}
+
+#[cfg(not(feature = "remote"))]
+#[tauri::command]
+pub(crate) fn remote_status() -> Result<(), String> {
+    Err(FEATURE_DISABLED.to_string())
+}
+
+fn scan_result_path(path: String) -> PathBuf {
+    let mut target = PathBuf::from(path);
+    if target.extension().is_none() {
+        target.set_extension(SCAN_RESULT_EXTENSION);
+    }
+    target
+}
+
+/// Appends `.voxara.json` when `path` has no extension and returns the final path.
+#[tauri::command]
+pub(crate) fn save_scan_result(summary: ScanSummary, path: String) -> Result<String, String> {
+    let target = scan_result_path(path);
+    let file = ScanResultFile {
+        format_version: SCAN_RESULT_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        saved_at: get_time_millis(Ok(SystemTime::now())).unwrap_or(0),
+        summary,
+    };
+    let payload = serde_json::to_vec(&file)
+        .map_err(|error| format!("Failed to serialize scan result: {error}"))?;
+    write_file_atomic(&target, &payload)
+        .map_err(|error| format!("Failed to save scan result: {error}"))?;
+    Ok(get_path_string(&target))
+}
+
+/// A `path` without an extension is read as `.voxara.json`, like `save_scan_result` writes it.
+#[tauri::command]
+pub(crate) fn load_scan_result(path: String) -> Result<ScanSummary, String> {
+    let contents = fs::read(scan_result_path(path))
+        .map_err(|error| format!("Failed to read scan result: {error}"))?;
+    let value: JsonValue = serde_json::from_slice(&contents)
+        .map_err(|error| format!("Invalid scan result file: {error}"))?;
+    let version = value.get("formatVersion").and_then(JsonValue::as_u64);
+    if version != Some(SCAN_RESULT_FORMAT_VERSION as u64) {
+        return Err(match version {
+            Some(found) => format!("Unsupported scan result version {found}"),
+            None => "Scan result file has no format version".to_string(),
+        });
+    }
+    let file: ScanResultFile = serde_json::from_value(value)
+        .map_err(|error| format!("Invalid scan result file: {error}"))?;
+    Ok(file.summary)
+}
@@ -0,0 +1,892 @@
+use super::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+#[cfg(target_os = "macos")]
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use jwalk::WalkDir;
+use serde::Serialize;
+
+const TRANSFER_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+const COPY_BUFFER_SIZE: usize = 256 * 1024; // 256KB
+
+pub(crate) struct CopyCancellation(pub(crate) Mutex<HashMap<String, ScanControl>>);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiskUsageSnapshot {
+    pub(crate) path: String,
+    pub(crate) total_bytes: u64,
+    pub(crate) free_bytes: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum DriveKind {
+    Fixed,
+    Removable,
+    Network,
+    Unknown,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DriveClassification {
+    path: String,
+    kind: DriveKind,
+    mount_point: Option<String>,
+    filesystem: Option<String>,
+}
+
+#[cfg(not(target_os = "windows"))]
+struct MountEntry {
+    mount_point: PathBuf,
+    filesystem: String,
+    device: String,
+    options: Vec<String>,
+}
+
+/// Failures of the file operations shared by the GUI and the remote server.
+pub(crate) enum PathError {
+    PathNotFound,
+    ProtectedPath,
+    SourceNotFound,
+    DestinationExists,
+    CrossDevice(String),
+    AccessDenied,
+    InvalidOptions(String),
+    Io(String),
+}
+
+impl PathError {
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            PathError::PathNotFound => "PathNotFound",
+            PathError::ProtectedPath => "ProtectedPath",
+            PathError::SourceNotFound => "SourceNotFound",
+            PathError::DestinationExists => "DestinationExists",
+            PathError::CrossDevice(_) => "CrossDevice",
+            PathError::AccessDenied => "AccessDenied",
+            PathError::InvalidOptions(_) => "InvalidOptions",
+            PathError::Io(_) => "Io",
+        }
+    }
+
+    pub(crate) fn message(&self) -> String {
+        match self {
+            PathError::PathNotFound => "path-not-found".to_string(),
+            PathError::ProtectedPath => "protected-path".to_string(),
+            PathError::SourceNotFound => "source-not-found".to_string(),
+            PathError::DestinationExists => "destination-exists".to_string(),
+            PathError::CrossDevice(detail) => format!("cross-device: {detail}"),
+            PathError::AccessDenied => "access-denied".to_string(),
+            PathError::InvalidOptions(detail) | PathError::Io(detail) => detail.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PathOperationResult {
+    path: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+impl PathOperationResult {
+    fn new(path: &str, outcome: Result<(), PathError>) -> Self {
+        match outcome {
+            Ok(()) => Self {
+                path: path.to_string(),
+                ok: true,
+                code: None,
+                message: None,
+            },
+            Err(error) => Self {
+                path: path.to_string(),
+                ok: false,
+                code: Some(error.code()),
+                message: Some(error.message()),
+            },
+        }
+    }
+}
+
+fn is_filesystem_root(path: &Path) -> bool {
+    path.components().all(|component| {
+        matches!(
+            component,
+            std::path::Component::Prefix(_)
+                | std::path::Component::RootDir
+                | std::path::Component::CurDir
+        )
+    })
+}
+
+fn resolve_deletable_path(path: &str) -> Result<PathBuf, PathError> {
+    let target = Path::new(path);
+    if path.trim().is_empty() || is_filesystem_root(target) {
+        return Err(PathError::ProtectedPath);
+    }
+    let file_name = target.file_name().ok_or(PathError::ProtectedPath)?;
+    let parent = match target.parent() {
+        Some(value) if !value.as_os_str().is_empty() => value,
+        _ => Path::new("."),
+    };
+    let map_io = |error: std::io::Error| match error.kind() {
+        std::io::ErrorKind::NotFound => PathError::PathNotFound,
+        _ => PathError::Io(error.to_string()),
+    };
+    let resolved = fs::canonicalize(parent).map_err(map_io)?.join(file_name);
+    fs::symlink_metadata(&resolved).map_err(map_io)?;
+    Ok(resolved)
+}
+
+pub(crate) fn ensure_within_roots(path: &Path, allowed_roots: &[PathBuf]) -> Result<(), PathError> {
+    if allowed_roots.is_empty() || allowed_roots.iter().any(|root| path.starts_with(root)) {
+        Ok(())
+    } else {
+        Err(PathError::AccessDenied)
+    }
+}
+
+pub(crate) fn trash_paths(paths: &[String], allowed_roots: &[PathBuf]) -> Vec<PathOperationResult> {
+    paths
+        .iter()
+        .map(|path| {
+            let outcome = resolve_deletable_path(path).and_then(|target| {
+                ensure_within_roots(&target, allowed_roots)?;
+                trash::delete(&target).map_err(|error| PathError::Io(error.to_string()))
+            });
+            PathOperationResult::new(path, outcome)
+        })
+        .collect()
+}
+
+#[cfg(feature = "remote")]
+pub(crate) fn delete_paths(
+    paths: &[String],
+    allowed_roots: &[PathBuf],
+) -> Vec<PathOperationResult> {
+    paths
+        .iter()
+        .map(|path| {
+            let outcome = if Path::new(path)
+                .components()
+                .any(|component| component == std::path::Component::ParentDir)
+            {
+                Err(PathError::ProtectedPath)
+            } else {
+                resolve_deletable_path(path).and_then(|target| {
+                    ensure_within_roots(&target, allowed_roots)?;
+                    let is_dir = fs::symlink_metadata(&target)
+                        .map(|meta| meta.is_dir())
+                        .unwrap_or(false);
+                    let result = if is_dir {
+                        fs::remove_dir_all(&target)
+                    } else {
+                        fs::remove_file(&target)
+                    };
+                    result.map_err(|error| PathError::Io(error.to_string()))
+                })
+            };
+            PathOperationResult::new(path, outcome)
+        })
+        .collect()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DeleteReport {
+    results: Vec<PathOperationResult>,
+    logical_bytes: u64,
+    freed_bytes: i64,
+}
+
+pub(crate) fn measure_delete(
+    paths: &[String],
+    allowed_roots: &[PathBuf],
+    operation: impl FnOnce() -> Vec<PathOperationResult>,
+) -> DeleteReport {
+    // Only walk paths the operation itself would accept, so nothing outside the
+    // allowed roots is ever measured.
+    let targets: Vec<(&str, PathBuf)> = paths
+        .iter()
+        .filter_map(|path| {
+            let target = resolve_deletable_path(path).ok()?;
+            ensure_within_roots(&target, allowed_roots).ok()?;
+            Some((path.as_str(), target))
+        })
+        .collect();
+    let sizes: HashMap<&str, u64> = targets
+        .iter()
+        .map(|(path, target)| (*path, measure_move_tree(target).1))
+        .collect();
+    let mut seen: Vec<(u64, u64)> = Vec::new();
+    let mut volumes: Vec<(PathBuf, u64)> = Vec::new();
+    for (_, target) in &targets {
+        let probe = free_space_probe(target);
+        if let Ok(snapshot) = compute_disk_usage(&probe) {
+            let key = (snapshot.total_bytes, snapshot.free_bytes);
+            if !seen.contains(&key) {
+                seen.push(key);
+                volumes.push((probe, snapshot.free_bytes));
+            }
+        }
+    }
+    let results = operation();
+    let logical_bytes = results
+        .iter()
+        .filter(|result| result.ok)
+        .map(|result| sizes.get(result.path.as_str()).copied().unwrap_or(0))
+        .sum();
+    let freed_bytes = volumes
+        .iter()
+        .filter_map(|(probe, before)| {
+            let after = compute_disk_usage(probe).ok()?.free_bytes;
+            Some(after as i64 - *before as i64)
+        })
+        .sum();
+    DeleteReport {
+        results,
+        logical_bytes,
+        freed_bytes,
+    }
+}
+
+/// The parent survives the delete, so it is what gets probed afterwards.
+fn free_space_probe(target: &Path) -> PathBuf {
+    match target.parent() {
+        Some(value) if !value.as_os_str().is_empty() => value.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+pub(crate) fn copy_dir_recursive(source: &Path, dest: &Path) -> Result<(), String> {
+    fs::create_dir_all(dest).map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(source).map_err(|e| e.to_string())? {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let file_type = entry.file_type().map_err(|e| e.to_string())?;
+        let dest_path = dest.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir_recursive(&entry.path(), &dest_path)?;
+        } else {
+            fs::copy(entry.path(), dest_path).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TransferProgress {
+    from: String,
+    to: String,
+    files_copied: u64,
+    total_files: u64,
+    bytes_copied: u64,
+    total_bytes: u64,
+}
+
+pub(crate) fn move_path_to(
+    from: &str,
+    to: &str,
+    on_progress: &mut dyn FnMut(&TransferProgress),
+) -> Result<PathBuf, PathError> {
+    let source = resolve_deletable_path(from).map_err(|error| match error {
+        PathError::PathNotFound => PathError::SourceNotFound,
+        other => other,
+    })?;
+    if to.trim().is_empty() {
+        return Err(PathError::InvalidOptions(
+            "Destination must not be empty".to_string(),
+        ));
+    }
+    let dest = PathBuf::from(to);
+    if fs::symlink_metadata(&dest).is_ok() {
+        return Err(PathError::DestinationExists);
+    }
+    match fs::rename(&source, &dest) {
+        Ok(()) => return Ok(dest),
+        Err(error) if error.kind() == std::io::ErrorKind::CrossesDevices => {}
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Err(PathError::Io(format!(
+                "Destination folder not found: {error}"
+            )))
+        }
+        Err(error) => return Err(PathError::Io(error.to_string())),
+    }
+    let (total_files, total_bytes) = measure_move_tree(&source);
+    let mut progress = TransferProgress {
+        from: source.to_string_lossy().to_string(),
+        to: dest.to_string_lossy().to_string(),
+        files_copied: 0,
+        total_files,
+        bytes_copied: 0,
+        total_bytes,
+    };
+    let mut last_emit = Instant::now();
+    let mut report = |progress: &TransferProgress| {
+        if last_emit.elapsed() >= TRANSFER_PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            on_progress(progress);
+        }
+    };
+    if let Err(error) = copy_move_tree(&source, &dest, &mut progress, &mut report) {
+        // Leave the source untouched and drop whatever part of the copy exists.
+        let _ = remove_path_tree(&dest);
+        return Err(PathError::CrossDevice(error));
+    }
+    remove_path_tree(&source).map_err(|error| {
+        PathError::Io(format!(
+            "Copied to {} but could not remove the source: {error}",
+            dest.display()
+        ))
+    })?;
+    Ok(dest)
+}
+
+fn measure_move_tree(path: &Path) -> (u64, u64) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(value) => value,
+        Err(_) => return (0, 0),
+    };
+    if !metadata.is_dir() {
+        return (1, metadata.len());
+    }
+    let entries = match fs::read_dir(path) {
+        Ok(value) => value,
+        Err(_) => return (0, 0),
+    };
+    entries
+        .flatten()
+        .map(|entry| measure_move_tree(&entry.path()))
+        .fold((0, 0), |(files, bytes), (more_files, more_bytes)| {
+            (files + more_files, bytes + more_bytes)
+        })
+}
+
+fn copy_move_tree(
+    source: &Path,
+    dest: &Path,
+    progress: &mut TransferProgress,
+    report: &mut dyn FnMut(&TransferProgress),
+) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(source).map_err(|e| e.to_string())?;
+    if metadata.is_dir() {
+        fs::create_dir(dest).map_err(|e| e.to_string())?;
+        for entry in fs::read_dir(source).map_err(|e| e.to_string())? {
+            let entry = entry.map_err(|e| e.to_string())?;
+            copy_move_tree(
+                &entry.path(),
+                &dest.join(entry.file_name()),
+                progress,
+                report,
+            )?;
+        }
+        let _ = fs::set_permissions(dest, metadata.permissions());
+        return Ok(());
+    }
+    #[cfg(unix)]
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(source).map_err(|e| e.to_string())?;
+        std::os::unix::fs::symlink(target, dest).map_err(|e| e.to_string())?;
+        progress.files_copied += 1;
+        report(progress);
+        return Ok(());
+    }
+    let bytes = fs::copy(source, dest).map_err(|e| format!("{}: {e}", source.display()))?;
+    progress.files_copied += 1;
+    progress.bytes_copied += bytes;
+    report(progress);
+    Ok(())
+}
+
+fn remove_path_tree(path: &Path) -> std::io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+pub(crate) struct CopyPlan {
+    source: PathBuf,
+    dest: PathBuf,
+    overwrite: bool,
+    dest_existed: bool,
+}
+
+pub(crate) fn plan_copy(from: &str, to: &str, overwrite: bool) -> Result<CopyPlan, PathError> {
+    let source = PathBuf::from(from);
+    if from.trim().is_empty() || fs::symlink_metadata(&source).is_err() {
+        return Err(PathError::SourceNotFound);
+    }
+    if to.trim().is_empty() {
+        return Err(PathError::InvalidOptions(
+            "Destination must not be empty".to_string(),
+        ));
+    }
+    let dest = PathBuf::from(to);
+    let dest_existed = fs::symlink_metadata(&dest).is_ok();
+    if dest_existed && !overwrite {
+        return Err(PathError::DestinationExists);
+    }
+    let resolved_source = fs::canonicalize(&source).map_err(|e| PathError::Io(e.to_string()))?;
+    let resolved_dest =
+        fs::canonicalize(&dest).or_else(|error| match (dest.parent(), dest.file_name()) {
+            (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+                fs::canonicalize(parent).map(|parent| parent.join(name))
+            }
+            (_, Some(name)) => std::env::current_dir().map(|dir| dir.join(name)),
+            _ => Err(error),
+        });
+    match resolved_dest {
+        Ok(resolved) if resolved.starts_with(&resolved_source) => {
+            return Err(PathError::InvalidOptions(
+                "Cannot copy a path onto or into itself".to_string(),
+            ))
+        }
+        Ok(_) => {}
+        Err(_) => {
+            return Err(PathError::Io(format!(
+                "Destination folder not found: {}",
+                dest.display()
+            )))
+        }
+    }
+    Ok(CopyPlan {
+        source,
+        dest,
+        overwrite,
+        dest_existed,
+    })
+}
+
+pub(crate) fn run_copy(
+    plan: &CopyPlan,
+    control: &ScanControl,
+    on_progress: &mut dyn FnMut(&TransferProgress),
+) -> Result<Option<TransferProgress>, PathError> {
+    let metadata = fs::symlink_metadata(&plan.source).map_err(|_| PathError::SourceNotFound)?;
+    let mut entries: Vec<(PathBuf, fs::FileType, u64)> = Vec::new();
+    if metadata.is_dir() {
+        let walk = WalkDir::new(&plan.source)
+            .skip_hidden(false)
+            .parallelism(resolve_parallelism(&ScanPriorityMode::Balanced));
+        for entry in walk {
+            if control.is_cancelled() {
+                return Ok(None);
+            }
+            let entry = entry.map_err(|e| PathError::Io(e.to_string()))?;
+            let path = entry.path();
+            let relative = path
+                .strip_prefix(&plan.source)
+                .unwrap_or(&path)
+                .to_path_buf();
+            let file_type = entry.file_type();
+            let size = if file_type.is_file() {
+                entry.metadata().map(|value| value.len()).unwrap_or(0)
+            } else {
+                0
+            };
+            entries.push((relative, file_type, size));
+        }
+    } else {
+        entries.push((PathBuf::new(), metadata.file_type(), metadata.len()));
+    }
+    let mut progress = TransferProgress {
+        from: plan.source.to_string_lossy().to_string(),
+        to: plan.dest.to_string_lossy().to_string(),
+        files_copied: 0,
+        total_files: entries.iter().filter(|(_, kind, _)| !kind.is_dir()).count() as u64,
+        bytes_copied: 0,
+        total_bytes: entries.iter().map(|(_, _, size)| size).sum(),
+    };
+    let mut last_emit = Instant::now();
+    let mut report = |progress: &TransferProgress| {
+        if last_emit.elapsed() >= TRANSFER_PROGRESS_INTERVAL {
+            last_emit = Instant::now();
+            on_progress(progress);
+        }
+    };
+    let outcome = copy_entries(plan, &entries, control, &mut progress, &mut report);
+    if !matches!(outcome, Ok(true)) && !plan.dest_existed {
+        let _ = remove_path_tree(&plan.dest);
+    }
+    match outcome {
+        Ok(true) => Ok(Some(progress)),
+        Ok(false) => Ok(None),
+        Err(error) => Err(PathError::Io(error)),
+    }
+}
+
+fn copy_entries(
+    plan: &CopyPlan,
+    entries: &[(PathBuf, fs::FileType, u64)],
+    control: &ScanControl,
+    progress: &mut TransferProgress,
+    report: &mut dyn FnMut(&TransferProgress),
+) -> Result<bool, String> {
+    for (relative, file_type, _) in entries {
+        // `join("")` would add a trailing separator, which breaks file paths.
+        let (source, target) = if relative.as_os_str().is_empty() {
+            (plan.source.clone(), plan.dest.clone())
+        } else {
+            (plan.source.join(relative), plan.dest.join(relative))
+        };
+        if file_type.is_dir() {
+            if !target.is_dir() {
+                fs::create_dir(&target).map_err(|e| format!("{}: {e}", target.display()))?;
+            }
+            continue;
+        }
+        #[cfg(unix)]
+        if file_type.is_symlink() {
+            let link = fs::read_link(&source).map_err(|e| e.to_string())?;
+            if plan.overwrite && fs::symlink_metadata(&target).is_ok() {
+                fs::remove_file(&target).map_err(|e| e.to_string())?;
+            }
+            std::os::unix::fs::symlink(link, &target).map_err(|e| e.to_string())?;
+            progress.files_copied += 1;
+            report(progress);
+            continue;
+        }
+        if !copy_file_buffered(&source, &target, control, progress, report)
+            .map_err(|e| format!("{}: {e}", source.display()))?
+        {
+            return Ok(false);
+        }
+        progress.files_copied += 1;
+        report(progress);
+    }
+    Ok(true)
+}
+
+fn copy_file_buffered(
+    source: &Path,
+    target: &Path,
+    control: &ScanControl,
+    progress: &mut TransferProgress,
+    report: &mut dyn FnMut(&TransferProgress),
+) -> std::io::Result<bool> {
+    let input = fs::File::open(source)?;
+    let permissions = input.metadata()?.permissions();
+    let mut reader = BufReader::with_capacity(COPY_BUFFER_SIZE, input);
+    let mut writer = BufWriter::with_capacity(COPY_BUFFER_SIZE, fs::File::create(target)?);
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        if control.is_cancelled() {
+            drop(writer);
+            let _ = fs::remove_file(target);
+            return Ok(false);
+        }
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..read])?;
+        progress.bytes_copied += read as u64;
+        report(progress);
+    }
+    writer.flush()?;
+    let _ = fs::set_permissions(target, permissions);
+    Ok(true)
+}
+
+pub(crate) fn compute_disk_usage(path: &Path) -> Result<DiskUsageSnapshot, String> {
+    if !path.exists() {
+        return Err("path-not-found".to_string());
+    }
+    let total_bytes =
+        fs2::total_space(path).map_err(|error| format!("disk-usage-failed: {error}"))?;
+    let free_bytes =
+        fs2::available_space(path).map_err(|error| format!("disk-usage-failed: {error}"))?;
+    Ok(DiskUsageSnapshot {
+        path: get_path_string(path),
+        total_bytes,
+        free_bytes,
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn classify_drive_path(path: &Path) -> DriveClassification {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Component, Prefix};
+    use windows_sys::Win32::Storage::FileSystem::GetDriveTypeW;
+
+    let (kind, mount_point) = match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::UNC(..) | Prefix::VerbatimUNC(..) => (DriveKind::Network, None),
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                let root = format!("{}:\\", letter as char);
+                let wide: Vec<u16> = std::ffi::OsStr::new(&root)
+                    .encode_wide()
+                    .chain(std::iter::once(0))
+                    .collect();
+                // DRIVE_REMOVABLE, DRIVE_FIXED, DRIVE_REMOTE, DRIVE_CDROM, DRIVE_RAMDISK.
+                let kind = match unsafe { GetDriveTypeW(wide.as_ptr()) } {
+                    2 | 5 => DriveKind::Removable,
+                    3 | 6 => DriveKind::Fixed,
+                    4 => DriveKind::Network,
+                    _ => DriveKind::Unknown,
+                };
+                (kind, Some(root))
+            }
+            _ => (DriveKind::Unknown, None),
+        },
+        _ => (DriveKind::Unknown, None),
+    };
+    DriveClassification {
+        path: get_path_string(path),
+        kind,
+        mount_point,
+        filesystem: None,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn classify_drive_path(path: &Path) -> DriveClassification {
+    let mounts = read_mount_table();
+    match find_mount(path, &mounts) {
+        Some(mount) => DriveClassification {
+            path: get_path_string(path),
+            kind: classify_mount(mount),
+            mount_point: Some(get_path_string(&mount.mount_point)),
+            filesystem: Some(mount.filesystem.clone()),
+        },
+        None => DriveClassification {
+            path: get_path_string(path),
+            kind: DriveKind::Unknown,
+            mount_point: None,
+            filesystem: None,
+        },
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn find_network_mounts(roots: &[PathBuf]) -> HashMap<PathBuf, String> {
+    let mounts = read_mount_table();
+    let mut found = HashMap::new();
+    for root in roots {
+        let canonical = match fs::canonicalize(root) {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        for mount in &mounts {
+            if mount.mount_point == canonical || classify_mount(mount) != DriveKind::Network {
+                continue;
+            }
+            if let Ok(relative) = mount.mount_point.strip_prefix(&canonical) {
+                found.insert(root.join(relative), mount.filesystem.clone());
+            }
+        }
+    }
+    found
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn find_network_mounts(_roots: &[PathBuf]) -> HashMap<PathBuf, String> {
+    HashMap::new()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_mount<'a>(path: &Path, mounts: &'a [MountEntry]) -> Option<&'a MountEntry> {
+    mounts
+        .iter()
+        .filter(|mount| path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.as_os_str().len())
+}
+
+#[cfg(not(target_os = "windows"))]
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "afpfs",
+    "webdav",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "lustre",
+    "fuse.sshfs",
+    "fuse.rclone",
+    "fuse.s3fs",
+    "fuse.davfs2",
+];
+
+#[cfg(not(target_os = "windows"))]
+fn classify_mount(mount: &MountEntry) -> DriveKind {
+    if NETWORK_FILESYSTEMS.contains(&mount.filesystem.as_str()) {
+        return DriveKind::Network;
+    }
+    if cfg!(target_os = "macos") && !mount.options.iter().any(|option| option == "local") {
+        return DriveKind::Network;
+    }
+    if !mount.device.starts_with("/dev/") {
+        return DriveKind::Unknown;
+    }
+    let media_dirs: &[&str] = if cfg!(target_os = "macos") {
+        &["/Volumes"]
+    } else {
+        &["/media", "/run/media", "/mnt/usb"]
+    };
+    if media_dirs
+        .iter()
+        .any(|dir| mount.mount_point.starts_with(dir))
+        || is_removable_block_device(&mount.device)
+    {
+        return DriveKind::Removable;
+    }
+    DriveKind::Fixed
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_removable_block_device(device: &str) -> bool {
+    let name = match fs::canonicalize(device).ok().and_then(|path| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+    }) {
+        Some(value) => value,
+        None => return false,
+    };
+    let sys_path = match fs::canonicalize(Path::new("/sys/class/block").join(name)) {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    [sys_path.join("removable"), sys_path.join("../removable")]
+        .iter()
+        .any(|flag| {
+            fs::read_to_string(flag)
+                .map(|value| value.trim() == "1")
+                .unwrap_or(false)
+        })
+}
+
+#[cfg(target_os = "linux")]
+fn read_mount_table() -> Vec<MountEntry> {
+    // Fields are space-separated with spaces inside paths escaped as `\040`.
+    let unescape = |value: &str| {
+        value
+            .replace("\\040", " ")
+            .replace("\\011", "\t")
+            .replace("\\012", "\n")
+            .replace("\\134", "\\")
+    };
+    fs::read_to_string("/proc/self/mounts")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(' ');
+            let device = unescape(fields.next()?);
+            let mount_point = PathBuf::from(unescape(fields.next()?));
+            let filesystem = fields.next()?.to_string();
+            let options = fields
+                .next()
+                .map(|value| value.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            Some(MountEntry {
+                mount_point,
+                filesystem,
+                device,
+                options,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn read_mount_table() -> Vec<MountEntry> {
+    // `mount` prints lines like `/dev/disk3s1 on /Volumes/USB (msdos, local, nodev)`.
+    let output = match Command::new("/sbin/mount").output() {
+        Ok(value) => String::from_utf8_lossy(&value.stdout).to_string(),
+        Err(_) => return Vec::new(),
+    };
+    output
+        .lines()
+        .filter_map(|line| {
+            let (device, rest) = line.split_once(" on ")?;
+            let (mount_point, details) = rest.rsplit_once(" (")?;
+            let mut options = details
+                .trim_end_matches(')')
+                .split(", ")
+                .map(str::to_string);
+            let filesystem = options.next()?;
+            Some(MountEntry {
+                mount_point: PathBuf::from(mount_point),
+                filesystem,
+                device: device.to_string(),
+                options: options.collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn read_mount_table() -> Vec<MountEntry> {
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delete_report_counts_only_paths_that_were_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let removed = dir.path().join("removed.bin");
+        let kept = dir.path().join("kept.bin");
+        fs::write(&removed, vec![0u8; 100]).unwrap();
+        fs::write(&kept, vec![0u8; 50]).unwrap();
+        let paths = vec![
+            removed.to_string_lossy().to_string(),
+            kept.to_string_lossy().to_string(),
+        ];
+
+        let report = measure_delete(&paths, &[], || {
+            fs::remove_file(&removed).unwrap();
+            vec![
+                PathOperationResult::new(&paths[0], Ok(())),
+                PathOperationResult::new(&paths[1], Err(PathError::AccessDenied)),
+            ]
+        });
+
+        assert_eq!(report.logical_bytes, 100);
+        assert_eq!(report.results.len(), 2);
+    }
+
+    #[test]
+    fn delete_report_skips_paths_outside_the_allowed_roots() {
+        let allowed = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let file = outside.path().join("secret.bin");
+        fs::write(&file, vec![0u8; 64]).unwrap();
+        let paths = vec![file.to_string_lossy().to_string()];
+        let roots = vec![fs::canonicalize(allowed.path()).unwrap()];
+
+        let report = measure_delete(&paths, &roots, || {
+            vec![PathOperationResult::new(&paths[0], Ok(()))]
+        });
+
+        assert_eq!(report.logical_bytes, 0);
+    }
+
+    #[test]
+    fn free_space_is_probed_on_the_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("doomed");
+        assert_eq!(free_space_probe(&target), dir.path());
+        assert_eq!(free_space_probe(Path::new("doomed")), PathBuf::from("."));
+    }
+}
@@ -0,0 +1,633 @@
+use super::*;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use winreg::enums::*;
+#[cfg(target_os = "windows")]
+use winreg::RegKey;
+
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::Manager;
+use tauri_plugin_window_state::{AppHandleExt, StateFlags};
+
+const DEFAULT_WINDOW_WIDTH: f64 = 1200.0;
+const DEFAULT_WINDOW_HEIGHT: f64 = 800.0;
+
+pub(crate) struct StartupPath(pub(crate) Mutex<Option<String>>);
+pub(crate) struct LaunchContextState(pub(crate) Mutex<LaunchContext>);
+
+#[derive(Clone, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LaunchContext {
+    pub(crate) path: Option<String>,
+    paths: Vec<String>,
+    mode: String,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+pub(crate) fn resolve_launch_context(args: &[String]) -> LaunchContext {
+    let mut path = None;
+    let mut paths = Vec::new();
+    let mut mode = "scan".to_string();
+
+    for arg in args.iter().skip(1) {
+        if arg == "--rename" {
+            mode = "rename".to_string();
+        } else if !arg.starts_with('-') && path.is_none() {
+            if Path::new(arg).exists() {
+                path = Some(arg.clone());
+            }
+        } else if !arg.starts_with('-') {
+            if Path::new(arg).exists() {
+                paths.push(arg.clone());
+            }
+        }
+    }
+
+    if path.is_none() && !paths.is_empty() {
+        path = Some(paths[0].clone());
+    } else if let Some(value) = path.clone() {
+        if !paths.contains(&value) {
+            paths.insert(0, value.clone());
+        }
+    }
+
+    LaunchContext { path, paths, mode }
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn hide_console_window() {
+    use windows_sys::Win32::System::Console::GetConsoleWindow;
+    use windows_sys::Win32::UI::WindowsAndMessaging::{ShowWindow, SW_HIDE};
+
+    unsafe {
+        let window = GetConsoleWindow();
+        if window != 0 {
+            ShowWindow(window, SW_HIDE);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn is_context_menu_key_valid(hkcu: &RegKey, key_path: &str, exe_str: &str) -> bool {
+    let key = match hkcu.open_subkey(key_path) {
+        Ok(entry) => entry,
+        Err(_) => return false,
+    };
+    let cmd_key = match key.open_subkey("command") {
+        Ok(entry) => entry,
+        Err(_) => return false,
+    };
+    let cmd_val: String = match cmd_key.get_value("") {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+    let cmd_lower = cmd_val.to_lowercase();
+    let exe_lower = exe_str.to_lowercase();
+    if !cmd_lower.contains(&exe_lower) {
+        return false;
+    }
+    if key_path.contains("Background") {
+        return cmd_lower.contains("%v") || cmd_lower.contains("%1");
+    }
+    cmd_lower.contains("%1") || cmd_lower.contains("%*")
+}
+
+#[tauri::command]
+pub(crate) fn is_context_menu_enabled() -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let exe_path = match std::env::current_exe() {
+            Ok(path) => path,
+            Err(_) => return false,
+        };
+        let exe_str = match exe_path.to_str() {
+            Some(value) => value,
+            None => return false,
+        };
+        let scan_keys = [
+            "Software\\Classes\\Directory\\shell\\Dragabyte",
+            "Software\\Classes\\Drive\\shell\\Dragabyte",
+            "Software\\Classes\\directory\\Background\\shell\\Dragabyte",
+        ];
+        let rename_keys = [
+            "Software\\Classes\\Directory\\shell\\DragabyteRename",
+            "Software\\Classes\\Drive\\shell\\DragabyteRename",
+            "Software\\Classes\\directory\\Background\\shell\\DragabyteRename",
+            "Software\\Classes\\*\\shell\\DragabyteRename",
+        ];
+        scan_keys
+            .iter()
+            .all(|key_path| is_context_menu_key_valid(&hkcu, key_path, exe_str))
+            && rename_keys
+                .iter()
+                .all(|key_path| is_context_menu_key_valid(&hkcu, key_path, exe_str))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let home = match std::env::var("HOME") {
+            Ok(val) => val,
+            Err(_) => return false,
+        };
+        let desktop_file = PathBuf::from(home)
+            .join(".local/share/applications/dragabyte.desktop");
+        desktop_file.exists()
+    }
+}
+
+#[tauri::command]
+pub(crate) fn toggle_context_menu(_enable: bool) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let scan_keys = [
+            "Software\\Classes\\Directory\\shell\\Dragabyte",
+            "Software\\Classes\\Drive\\shell\\Dragabyte",
+            "Software\\Classes\\directory\\Background\\shell\\Dragabyte",
+        ];
+        let rename_keys = [
+            "Software\\Classes\\Directory\\shell\\DragabyteRename",
+            "Software\\Classes\\Drive\\shell\\DragabyteRename",
+            "Software\\Classes\\directory\\Background\\shell\\DragabyteRename",
+            "Software\\Classes\\*\\shell\\DragabyteRename",
+        ];
+
+        if _enable {
+            let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+            let exe_str = exe_path.to_str().ok_or("Invalid path")?;
+            let scan_cmd = format!("\"{}\" \"%1\"", exe_str);
+            let rename_cmd = format!("\"{}\" --rename %*", exe_str);
+
+            for key_path in scan_keys {
+                let (key, _) = hkcu.create_subkey(key_path).map_err(|e| e.to_string())?;
+                key.set_value("", &"Scan with Dragabyte")
+                    .map_err(|e| e.to_string())?;
+                key.set_value("Icon", &exe_str).map_err(|e| e.to_string())?;
+
+                let (cmd_key, _) = key.create_subkey("command").map_err(|e| e.to_string())?;
+
+                let cmd_val = if key_path.contains("Background") {
+                    format!("\"{}\" \"%V\"", exe_str)
+                } else {
+                    scan_cmd.clone()
+                };
+
+                cmd_key.set_value("", &cmd_val).map_err(|e| e.to_string())?;
+
+            }
+
+            for key_path in rename_keys {
+                let (r_key, _) = hkcu
+                    .create_subkey(key_path)
+                    .map_err(|e| e.to_string())?;
+                r_key
+                    .set_value("", &"Rename with Dragabyte")
+                    .map_err(|e| e.to_string())?;
+                r_key.set_value("Icon", &exe_str).map_err(|e| e.to_string())?;
+                let _ = r_key.set_value("MultiSelectModel", &"Player");
+
+                let (r_cmd_key, _) = r_key
+                    .create_subkey("command")
+                    .map_err(|e| e.to_string())?;
+                let r_cmd_val = if key_path.contains("Background") {
+                    format!("\"{}\" --rename \"%V\"", exe_str)
+                } else {
+                    rename_cmd.clone()
+                };
+                r_cmd_key
+                    .set_value("", &r_cmd_val)
+                    .map_err(|e| e.to_string())?;
+            }
+        } else {
+            for key_path in scan_keys {
+                let _ = hkcu.delete_subkey_all(key_path);
+            }
+            for key_path in rename_keys {
+                let _ = hkcu.delete_subkey_all(key_path);
+            }
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let home = std::env::var("HOME").map_err(|_| "HOME not found")?;
+        let apps_dir = PathBuf::from(home).join(".local/share/applications");
+        if !apps_dir.exists() {
+            fs::create_dir_all(&apps_dir).map_err(|e| e.to_string())?;
+        }
+        let desktop_file = apps_dir.join("dragabyte.desktop");
+
+        if _enable {
+            let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
+            let exe_str = exe_path.to_string_lossy();
+            let content = format!(
+                r#"[Desktop Entry]
+Type=Application
+Name=Dragabyte
+Comment=Disk Space Analyzer and Bulk Rename Utility
+Exec="{}" %f
+Icon=utilities-terminal
+Terminal=false
+Categories=Utility;FileTools;
+Actions=Scan;Rename;
+
+[Desktop Action Scan]
+Name=Scan with Dragabyte
+Exec="{}" %f
+
+[Desktop Action Rename]
+Name=Rename with Dragabyte
+Exec="{}" --rename %f
+"#,
+                exe_str, exe_str, exe_str
+            );
+            fs::write(desktop_file, content).map_err(|e| e.to_string())?;
+        } else {
+            if desktop_file.exists() {
+                fs::remove_file(desktop_file).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub(crate) fn reset_context_menu() -> Result<(), String> {
+    toggle_context_menu(false)?;
+    toggle_context_menu(true)
+}
+
+#[tauri::command]
+pub(crate) fn save_temp_and_open(name: String, data: String) -> Result<(), String> {
+    let bytes = BASE64_STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid base64 data: {e}"))?;
+    let temp_dir = std::env::temp_dir();
+    let safe_name = Path::new(&name).file_name().ok_or("Invalid filename")?;
+    let target_path = temp_dir.join(safe_name);
+    fs::write(&target_path, bytes).map_err(|e| format!("Failed to write file: {e}"))?;
+    let path_str = target_path.to_string_lossy().to_string();
+    open_path(path_str)
+}
+
+#[tauri::command]
+pub(crate) fn open_path(path: String) -> Result<(), String> {
+    let target = Path::new(&path);
+    if !target.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let status = Command::new("cmd")
+            .args(["/C", "start", "", &path])
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err("Failed to open path".to_string());
+        }
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let status = Command::new("open")
+            .arg(&path)
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err("Failed to open path".to_string());
+        }
+        return Ok(());
+    }
+
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    {
+        let status = Command::new("xdg-open")
+            .arg(&path)
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err("Failed to open path".to_string());
+        }
+        return Ok(());
+    }
+}
+
+#[tauri::command]
+pub(crate) fn show_in_explorer(path: String) -> Result<(), String> {
+    let target = Path::new(&path);
+    if !target.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if target.is_file() {
+            let select_arg = format!("/select,\"{}\"", path);
+            Command::new("explorer")
+                .arg(select_arg)
+                .spawn()
+                .map_err(|e| e.to_string())?;
+        } else {
+            Command::new("explorer")
+                .arg(&path)
+                .spawn()
+                .map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let status = if target.is_file() {
+            Command::new("open").args(["-R", &path]).status()
+        } else {
+            Command::new("open").arg(&path).status()
+        }
+        .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err("Failed to show path in explorer".to_string());
+        }
+        return Ok(());
+    }
+
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    {
+        let folder = if target.is_file() {
+            target.parent().unwrap_or(target)
+        } else {
+            target
+        };
+        let folder_str = folder.to_string_lossy().to_string();
+        let status = Command::new("xdg-open")
+            .arg(folder_str)
+            .status()
+            .map_err(|e| e.to_string())?;
+        if !status.success() {
+            return Err("Failed to show path in explorer".to_string());
+        }
+        return Ok(());
+    }
+}
+
+#[tauri::command]
+pub(crate) fn reveal_node(summary: ScanSummary, node_path: String) -> Result<(), String> {
+    if !summary_contains_path(&summary.root, &node_path) {
+        return Err("Path is not part of this scan".to_string());
+    }
+    if !Path::new(&node_path).exists() {
+        return Err(format!("Path no longer exists: {node_path}"));
+    }
+    show_in_explorer(node_path)
+}
+
+fn monitor_layout_key(window: &tauri::WebviewWindow) -> Option<String> {
+    let monitors = window.available_monitors().ok()?;
+    if monitors.is_empty() {
+        return None;
+    }
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            format!(
+                "{}@{},{}:{}x{}",
+                monitor.name().map(String::as_str).unwrap_or("unknown"),
+                position.x,
+                position.y,
+                size.width,
+                size.height
+            )
+        })
+        .collect();
+    parts.sort();
+    Some(parts.join("|"))
+}
+
+pub(crate) fn restore_layout_geometry(
+    window: &tauri::WebviewWindow,
+    settings: &AppSettings,
+) -> bool {
+    if !settings.remember_window_layouts.unwrap_or(false) {
+        return false;
+    }
+    let geometry = match monitor_layout_key(window)
+        .and_then(|key| settings.window_layouts.get(&key).copied())
+    {
+        Some(value) => value,
+        None => return false,
+    };
+    let _ = window.set_size(tauri::Size::Physical(tauri::PhysicalSize {
+        width: geometry.width,
+        height: geometry.height,
+    }));
+    let _ = window.set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+        x: geometry.x,
+        y: geometry.y,
+    }));
+    true
+}
+
+pub(crate) fn remember_layout_geometry(window: &tauri::WebviewWindow, state: &SettingsState) {
+    let Ok(mut guard) = state.value.lock() else {
+        return;
+    };
+    let remember = state.effective(&guard).remember_window_layouts;
+    if !remember.unwrap_or(false) || window.is_maximized().unwrap_or(false) {
+        return;
+    }
+    let (Some(key), Ok(position), Ok(size)) = (
+        monitor_layout_key(window),
+        window.outer_position(),
+        window.outer_size(),
+    ) else {
+        return;
+    };
+    guard.window_layouts.insert(
+        key,
+        WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        },
+    );
+    if let Ok(path) = state.path.lock() {
+        if let Err(error) = save_settings(&path, &guard) {
+            eprintln!("[settings] failed to save window layout: {error}");
+        }
+    }
+}
+
+fn get_main_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWindow, String> {
+    app.get_webview_window("main")
+        .ok_or_else(|| "Main window is not available".to_string())
+}
+
+fn apply_window_geometry(
+    app: &tauri::AppHandle,
+    window: &tauri::WebviewWindow,
+    geometry: WindowGeometry,
+) -> Result<(), String> {
+    if window.is_maximized().unwrap_or(false) {
+        let _ = window.unmaximize();
+    }
+    window
+        .set_size(tauri::Size::Physical(tauri::PhysicalSize {
+            width: geometry.width,
+            height: geometry.height,
+        }))
+        .map_err(|error| format!("Failed to resize window: {error}"))?;
+    window
+        .set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+            x: geometry.x,
+            y: geometry.y,
+        }))
+        .map_err(|error| format!("Failed to move window: {error}"))?;
+    ensure_window_bounds(window);
+    app.save_window_state(StateFlags::POSITION | StateFlags::SIZE)
+        .map_err(|error| format!("Failed to save window state: {error}"))?;
+    remember_layout_geometry(window, &app.state::<SettingsState>());
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn get_window_geometry(app: tauri::AppHandle) -> Result<WindowGeometry, String> {
+    let window = get_main_window(&app)?;
+    let position = window.outer_position().map_err(|error| error.to_string())?;
+    let size = window.outer_size().map_err(|error| error.to_string())?;
+    Ok(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn set_window_geometry(
+    app: tauri::AppHandle,
+    geometry: WindowGeometry,
+) -> Result<(), String> {
+    if geometry.width == 0 || geometry.height == 0 {
+        return Err("Window size must be non-zero".to_string());
+    }
+    let window = get_main_window(&app)?;
+    apply_window_geometry(&app, &window, geometry)
+}
+
+#[tauri::command]
+pub(crate) fn reset_window_geometry(app: tauri::AppHandle) -> Result<WindowGeometry, String> {
+    let window = get_main_window(&app)?;
+    let monitor = match window.primary_monitor() {
+        Ok(Some(value)) => value,
+        _ => window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .ok_or_else(|| "No monitor available".to_string())?,
+    };
+    let monitor_position = monitor.position();
+    let monitor_size = monitor.size();
+    let scale = monitor.scale_factor();
+    let width = ((DEFAULT_WINDOW_WIDTH * scale) as u32).min(monitor_size.width);
+    let height = ((DEFAULT_WINDOW_HEIGHT * scale) as u32).min(monitor_size.height);
+    let geometry = WindowGeometry {
+        x: monitor_position.x + (monitor_size.width - width) as i32 / 2,
+        y: monitor_position.y + (monitor_size.height - height) as i32 / 2,
+        width,
+        height,
+    };
+    apply_window_geometry(&app, &window, geometry)?;
+    Ok(geometry)
+}
+
+pub(crate) fn ensure_window_bounds(window: &tauri::WebviewWindow) {
+    let position = match window.outer_position() {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    let size = match window.outer_size() {
+        Ok(value) => value,
+        Err(_) => return,
+    };
+    let mut monitors = match window.available_monitors() {
+        Ok(list) => list,
+        Err(_) => Vec::new(),
+    };
+    if monitors.is_empty() {
+        if let Ok(Some(monitor)) = window.current_monitor() {
+            monitors.push(monitor);
+        } else if let Ok(Some(monitor)) = window.primary_monitor() {
+            monitors.push(monitor);
+        } else {
+            return;
+        }
+    }
+
+    let width = size.width as i32;
+    let height = size.height as i32;
+    let mut fits_monitor = false;
+    for monitor in &monitors {
+        let monitor_position = monitor.position();
+        let monitor_size = monitor.size();
+        let max_x = monitor_position.x + monitor_size.width as i32;
+        let max_y = monitor_position.y + monitor_size.height as i32;
+        if position.x >= monitor_position.x
+            && position.y >= monitor_position.y
+            && position.x + width <= max_x
+            && position.y + height <= max_y
+        {
+            fits_monitor = true;
+            break;
+        }
+    }
+
+    if fits_monitor {
+        return;
+    }
+
+    let monitor = match monitors.into_iter().next() {
+        Some(value) => value,
+        None => return,
+    };
+    let monitor_position = monitor.position();
+    let monitor_size = monitor.size();
+    let mut new_width = size.width;
+    let mut new_height = size.height;
+    if new_width > monitor_size.width {
+        new_width = monitor_size.width;
+    }
+    if new_height > monitor_size.height {
+        new_height = monitor_size.height;
+    }
+    let max_x = monitor_position.x + monitor_size.width as i32 - new_width as i32;
+    let max_y = monitor_position.y + monitor_size.height as i32 - new_height as i32;
+    let new_x = position.x.clamp(monitor_position.x, max_x);
+    let new_y = position.y.clamp(monitor_position.y, max_y);
+
+    let _ = window.set_size(tauri::Size::Physical(tauri::PhysicalSize {
+        width: new_width,
+        height: new_height,
+    }));
+    let _ = window.set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+        x: new_x,
+        y: new_y,
+    }));
+}
@@ -0,0 +1,3579 @@
+use super::*;
+
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use base64::prelude::*;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use jwalk::WalkDir;
+use md5::Md5;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConnection, DigitallySignedStruct, ServerConnection, SignatureScheme};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
+use tauri::Emitter;
+use tauri::Manager;
+use tauri_plugin_updater::UpdaterExt;
+
+const MAX_CONCURRENT_REMOTE_SCANS: usize = 4;
+pub(crate) const PROTOCOL_VERSION: u32 = 1;
+const MIN_PROTOCOL_VERSION: u32 = 1;
+
+enum RemoteError {
+    InvalidJson,
+    Unauthorized,
+    NotAFile,
+    FileTooLarge,
+    OutOfRange,
+    ScanInProgress,
+    TooManyScans,
+    UnsupportedProtocol,
+    ConfirmationRequired,
+    InsufficientScope,
+    ShutdownNotAllowed,
+    ShutdownFailed,
+    PresetNotFound,
+    Path(PathError),
+}
+
+impl From<PathError> for RemoteError {
+    fn from(error: PathError) -> Self {
+        RemoteError::Path(error)
+    }
+}
+
+impl RemoteError {
+    fn code(&self) -> &'static str {
+        match self {
+            RemoteError::InvalidJson => "InvalidJson",
+            RemoteError::Unauthorized => "Unauthorized",
+            RemoteError::NotAFile => "NotAFile",
+            RemoteError::FileTooLarge => "FileTooLarge",
+            RemoteError::OutOfRange => "OutOfRange",
+            RemoteError::ScanInProgress => "ScanInProgress",
+            RemoteError::TooManyScans => "TooManyScans",
+            RemoteError::UnsupportedProtocol => "UnsupportedProtocol",
+            RemoteError::ConfirmationRequired => "ConfirmationRequired",
+            RemoteError::InsufficientScope => "InsufficientScope",
+            RemoteError::ShutdownNotAllowed => "ShutdownNotAllowed",
+            RemoteError::ShutdownFailed => "ShutdownFailed",
+            RemoteError::PresetNotFound => "PresetNotFound",
+            RemoteError::Path(error) => error.code(),
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            RemoteError::InvalidJson => "invalid_json".to_string(),
+            RemoteError::Unauthorized => "unauthorized".to_string(),
+            RemoteError::NotAFile => "not-a-file".to_string(),
+            RemoteError::FileTooLarge => "file-too-large".to_string(),
+            RemoteError::OutOfRange => "out-of-range".to_string(),
+            RemoteError::ScanInProgress => "scan-in-progress".to_string(),
+            RemoteError::TooManyScans => format!(
+                "too-many-scans: at most {MAX_CONCURRENT_REMOTE_SCANS} scans can run at once"
+            ),
+            RemoteError::UnsupportedProtocol => format!(
+                "unsupported-protocol: server speaks {MIN_PROTOCOL_VERSION}..={PROTOCOL_VERSION}"
+            ),
+            RemoteError::ConfirmationRequired => "confirmation-required".to_string(),
+            RemoteError::InsufficientScope => "insufficient-scope".to_string(),
+            RemoteError::ShutdownNotAllowed => "shutdown-not-allowed".to_string(),
+            RemoteError::ShutdownFailed => "shutdown-failed".to_string(),
+            RemoteError::PresetNotFound => "preset-not-found".to_string(),
+            RemoteError::Path(error) => error.message(),
+        }
+    }
+}
+
+const MAX_CONNECTIONS: usize = 50;
+const MAX_LINE_LENGTH: u64 = 10 * 1024 * 1024; // 10MB
+const MAX_READ_CHUNK: u64 = 1024 * 1024; // 1MB
+const MAX_HEX_DUMP: u64 = 64 * 1024; // 64KB
+const DEFAULT_HEX_DUMP: u64 = 256;
+const HEX_DUMP_WIDTH: usize = 16;
+const HASH_CHUNK_SIZE: usize = 256 * 1024; // 256KB
+const REMOTE_ACTIONS: &[&str] = &[
+    "ping",
+    "hello",
+    "list",
+    "disk",
+    "read",
+    "readChunked",
+    "readHex",
+    "stat",
+    "hash",
+    "trash",
+    "prepareDelete",
+    "delete",
+    "move",
+    "copy",
+    "scan",
+    "duplicates",
+    "export",
+    "listPresets",
+    "runPreset",
+    "validateOptions",
+    "cancel",
+    "scanStatus",
+    "subscribe",
+    "pause",
+    "resume",
+    "watch",
+    "unwatch",
+    "heartbeat",
+    "shutdown",
+];
+const DEFAULT_REMOTE_CLI_TIMEOUT_SECS: u64 = 60;
+const DELETE_CONFIRM_TTL: Duration = Duration::from_secs(60);
+const COMPRESSED_LINE_PREFIX: &str = "gz:";
+const MIN_COMPRESSED_LINE_LENGTH: usize = 4 * 1024; // 4KB
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REPLAY_EVENTS: usize = 64;
+const HEARTBEAT_MISSED_LIMIT: u32 = 3;
+
+pub(crate) struct RemoteClientState(pub(crate) Mutex<Option<RemoteClientHandle>>);
+pub(crate) struct RemoteServerState(pub(crate) Arc<Mutex<Option<RemoteServerHandle>>>);
+
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "camelCase")]
+enum RemoteRequest {
+    Ping {
+        id: Option<String>,
+    },
+    Heartbeat {
+        id: Option<String>,
+    },
+    Hello {
+        id: Option<String>,
+        #[serde(default, rename = "clientVersion")]
+        client_version: Option<String>,
+        #[serde(default, rename = "protocolVersion")]
+        protocol_version: Option<u32>,
+        #[serde(default)]
+        compress: bool,
+        #[serde(default)]
+        heartbeat: bool,
+    },
+    List {
+        id: Option<String>,
+        path: Option<String>,
+        #[serde(rename = "includeFiles")]
+        include_files: Option<bool>,
+        #[serde(default, rename = "withSizes")]
+        with_sizes: bool,
+    },
+    Disk {
+        id: Option<String>,
+        path: String,
+    },
+    Read {
+        id: Option<String>,
+        path: String,
+    },
+    ReadChunked {
+        id: Option<String>,
+        path: String,
+        #[serde(default)]
+        offset: u64,
+        length: Option<u64>,
+    },
+    ReadHex {
+        id: Option<String>,
+        path: String,
+        #[serde(default)]
+        offset: u64,
+        length: Option<u64>,
+    },
+    Stat {
+        id: Option<String>,
+        path: String,
+    },
+    Hash {
+        id: Option<String>,
+        path: String,
+        algo: HashAlgorithm,
+    },
+    Trash {
+        id: Option<String>,
+        paths: Vec<String>,
+    },
+    PrepareDelete {
+        id: Option<String>,
+        paths: Vec<String>,
+    },
+    Delete {
+        id: Option<String>,
+        paths: Vec<String>,
+        confirm: Option<String>,
+    },
+    Move {
+        id: Option<String>,
+        from: String,
+        to: String,
+    },
+    Copy {
+        id: Option<String>,
+        from: String,
+        to: String,
+        overwrite: Option<bool>,
+    },
+    Scan {
+        id: Option<String>,
+        path: String,
+        options: Option<ScanOptions>,
+    },
+    Duplicates {
+        id: Option<String>,
+        path: String,
+        options: Option<ScanOptions>,
+    },
+    Export {
+        id: Option<String>,
+        path: String,
+        options: Option<ScanOptions>,
+    },
+    ListPresets {
+        id: Option<String>,
+    },
+    RunPreset {
+        id: Option<String>,
+        name: String,
+        path: String,
+    },
+    ValidateOptions {
+        id: Option<String>,
+        options: ScanOptions,
+    },
+    Watch {
+        id: Option<String>,
+        path: String,
+        filters: Option<ScanFilters>,
+    },
+    Unwatch {
+        id: Option<String>,
+    },
+    Cancel {
+        id: Option<String>,
+    },
+    Subscribe {
+        id: Option<String>,
+    },
+    ScanStatus {
+        id: Option<String>,
+        #[serde(rename = "scanId")]
+        scan_id: String,
+    },
+    Pause {
+        id: Option<String>,
+    },
+    Resume {
+        id: Option<String>,
+    },
+    Shutdown {
+        id: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct RemoteEnvelope {
+    token: Option<String>,
+    #[serde(flatten)]
+    request: RemoteRequest,
+}
+
+const ALL_REMOTE_SCOPES: &[RemoteScope] = &[
+    RemoteScope::Read,
+    RemoteScope::List,
+    RemoteScope::Scan,
+    RemoteScope::Delete,
+    RemoteScope::Shutdown,
+];
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteConnectPayload {
+    host: String,
+    port: u16,
+    token: Option<String>,
+    #[serde(default)]
+    tls: bool,
+    ca_cert: Option<String>,
+    pin_sha256: Option<String>,
+}
+
+#[derive(Clone, Default)]
+struct RemoteTlsOptions {
+    ca_cert: Option<String>,
+    pin_sha256: Option<String>,
+}
+
+#[derive(Clone)]
+enum RemoteStream {
+    Plain(Arc<TcpStream>),
+    Tls(Arc<TlsStream>),
+}
+
+struct TlsStream {
+    connection: Mutex<rustls::Connection>,
+    socket: TcpStream,
+}
+
+impl TlsStream {
+    fn new(connection: impl Into<rustls::Connection>, socket: TcpStream) -> Self {
+        Self {
+            connection: Mutex::new(connection.into()),
+            socket,
+        }
+    }
+
+    fn lock(&self) -> std::io::Result<std::sync::MutexGuard<'_, rustls::Connection>> {
+        self.connection
+            .lock()
+            .map_err(|_| std::io::Error::other("TLS connection lock poisoned"))
+    }
+
+    fn read(&self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut incoming = [0u8; 16 * 1024];
+        loop {
+            match self.lock()?.reader().read(buf) {
+                Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {}
+                result => return result,
+            }
+            let count = (&self.socket).read(&mut incoming)?;
+            let mut connection = self.lock()?;
+            let mut data = &incoming[..count];
+            loop {
+                connection.read_tls(&mut data)?;
+                connection
+                    .process_new_packets()
+                    .map_err(std::io::Error::other)?;
+                if data.is_empty() {
+                    break;
+                }
+            }
+            // Alerts and key updates may need an answer.
+            while connection.wants_write() {
+                connection.write_tls(&mut &self.socket)?;
+            }
+        }
+    }
+
+    fn write(&self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut connection = self.lock()?;
+        let count = connection.writer().write(buf)?;
+        while connection.wants_write() {
+            connection.write_tls(&mut &self.socket)?;
+        }
+        Ok(count)
+    }
+}
+
+impl RemoteStream {
+    fn shutdown(&self) {
+        let socket = match self {
+            RemoteStream::Plain(stream) => stream.as_ref(),
+            RemoteStream::Tls(stream) => &stream.socket,
+        };
+        if let Err(error) = socket.shutdown(std::net::Shutdown::Both) {
+            eprintln!("[remote] socket shutdown failed: {error}");
+        }
+    }
+}
+
+impl Read for RemoteStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            RemoteStream::Plain(stream) => (&**stream).read(buf),
+            RemoteStream::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for RemoteStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            RemoteStream::Plain(stream) => (&**stream).write(buf),
+            RemoteStream::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            RemoteStream::Plain(stream) => (&**stream).flush(),
+            // `write` already hands every record to the socket.
+            RemoteStream::Tls(_) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    fingerprint: Vec<u8>,
+    algorithms: rustls::crypto::WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if Sha256::digest(end_entity.as_ref()).as_slice() == self.fingerprint.as_slice() {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(rustls::Error::General(
+                "Server certificate does not match the pinned fingerprint".to_string(),
+            ))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RemoteSendPayload {
+    #[serde(default)]
+    payload: JsonValue,
+}
+
+#[derive(Default)]
+struct ScanReplay {
+    progress: Option<String>,
+    events: VecDeque<String>,
+}
+
+struct PendingDelete {
+    paths: Vec<String>,
+    expires_at: Instant,
+}
+
+pub(crate) struct RemoteHub {
+    clients: Mutex<Vec<mpsc::Sender<String>>>,
+    authenticated_clients: Mutex<Vec<mpsc::Sender<String>>>,
+    scans: Mutex<HashMap<String, ScanControl>>,
+    replays: Mutex<HashMap<String, ScanReplay>>,
+    finished_scans: FinishedScans,
+    pending_deletes: Mutex<HashMap<String, PendingDelete>>,
+    watches: Mutex<HashMap<String, Arc<AtomicBool>>>,
+    sized_listings: Mutex<Vec<Arc<AtomicBool>>>,
+    tokens: Vec<RemoteTokenConfig>,
+    allowed_roots: Vec<PathBuf>,
+    hash_max_bytes: Option<u64>,
+    settings_path: PathBuf,
+    shutdown: Option<mpsc::Sender<()>>,
+    allow_gui_shutdown: bool,
+    app: Mutex<Option<tauri::AppHandle>>,
+}
+
+impl RemoteHub {
+    fn new(
+        token: Option<String>,
+        scoped_tokens: &[RemoteTokenConfig],
+        allowed_roots: &[String],
+        hash_max_bytes: Option<u64>,
+        settings_path: &Path,
+        shutdown: Option<mpsc::Sender<()>>,
+        allow_gui_shutdown: bool,
+    ) -> Self {
+        // The single configured token predates scopes and keeps full access.
+        let mut tokens: Vec<RemoteTokenConfig> = token
+            .into_iter()
+            .map(|token| RemoteTokenConfig {
+                token,
+                scopes: ALL_REMOTE_SCOPES.to_vec(),
+            })
+            .collect();
+        tokens.extend(scoped_tokens.iter().cloned());
+        Self {
+            clients: Mutex::new(Vec::new()),
+            authenticated_clients: Mutex::new(Vec::new()),
+            scans: Mutex::new(HashMap::new()),
+            replays: Mutex::new(HashMap::new()),
+            finished_scans: FinishedScans::default(),
+            watches: Mutex::new(HashMap::new()),
+            pending_deletes: Mutex::new(HashMap::new()),
+            sized_listings: Mutex::new(Vec::new()),
+            tokens,
+            allowed_roots: allowed_roots
+                .iter()
+                .map(|root| {
+                    // An unresolvable root is kept as written: it matches nothing,
+                    // but the allowlist must never silently become empty.
+                    fs::canonicalize(root).unwrap_or_else(|error| {
+                        eprintln!("[remote] allowed root {root} is not accessible: {error}");
+                        PathBuf::from(root)
+                    })
+                })
+                .collect(),
+            hash_max_bytes,
+            settings_path: settings_path.to_path_buf(),
+            shutdown,
+            allow_gui_shutdown,
+            app: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn attach_app(&self, app: tauri::AppHandle) {
+        if let Ok(mut slot) = self.app.lock() {
+            *slot = Some(app);
+        }
+    }
+
+    fn register_client(&self, sender: mpsc::Sender<String>) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.push(sender);
+        }
+    }
+
+    fn register_authenticated(&self, sender: mpsc::Sender<String>) {
+        if let Ok(mut clients) = self.authenticated_clients.lock() {
+            clients.push(sender);
+        }
+    }
+
+    fn broadcast(&self, message: String) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|sender| sender.send(message.clone()).is_ok());
+        }
+    }
+
+    fn broadcast_authenticated(&self, message: String) {
+        if let Ok(mut clients) = self.authenticated_clients.lock() {
+            clients.retain(|sender| sender.send(message.clone()).is_ok());
+        }
+    }
+
+    fn start_scan(&self, key: &str, control: ScanControl) -> Result<(), RemoteError> {
+        let mut scans = self
+            .scans
+            .lock()
+            .map_err(|_| PathError::Io("Failed to lock scan state".to_string()))?;
+        if scans.contains_key(key) {
+            return Err(RemoteError::ScanInProgress);
+        }
+        if scans.len() >= MAX_CONCURRENT_REMOTE_SCANS {
+            return Err(RemoteError::TooManyScans);
+        }
+        scans.insert(key.to_string(), control);
+        if let Ok(mut replays) = self.replays.lock() {
+            replays.insert(key.to_string(), ScanReplay::default());
+        }
+        Ok(())
+    }
+
+    fn has_scan(&self, key: &str) -> bool {
+        self.scans
+            .lock()
+            .map(|scans| scans.contains_key(key))
+            .unwrap_or(false)
+    }
+
+    fn scan_controls(&self, key: Option<&str>) -> Vec<ScanControl> {
+        match self.scans.lock() {
+            Ok(scans) => match key {
+                Some(key) => scans.get(key).cloned().into_iter().collect(),
+                None => scans.values().cloned().collect(),
+            },
+            Err(_) => Vec::new(),
+        }
+    }
+
+    pub(crate) fn cancel_scan(&self, key: Option<&str>) -> bool {
+        let controls = self.scan_controls(key);
+        for control in &controls {
+            control.cancel.store(true, Ordering::SeqCst);
+        }
+        !controls.is_empty()
+    }
+
+    fn set_scan_paused(&self, key: Option<&str>, paused: bool) -> bool {
+        let controls = self.scan_controls(key);
+        for control in &controls {
+            control.pause.store(paused, Ordering::SeqCst);
+        }
+        !controls.is_empty()
+    }
+
+    fn finish_scan(&self, key: &str) {
+        if let Ok(mut scans) = self.scans.lock() {
+            scans.remove(key);
+        }
+        if let Ok(mut replays) = self.replays.lock() {
+            replays.remove(key);
+        }
+        if !key.is_empty() {
+            self.finished_scans.record(key);
+        }
+    }
+
+    fn broadcast_scan_event(&self, key: &str, line: String, is_progress: bool) {
+        let Ok(mut replays) = self.replays.lock() else {
+            self.broadcast(line);
+            return;
+        };
+        if let Some(replay) = replays.get_mut(key) {
+            if is_progress {
+                replay.progress = Some(line.clone());
+            } else {
+                if replay.events.len() >= MAX_REPLAY_EVENTS {
+                    replay.events.pop_front();
+                }
+                replay.events.push_back(line.clone());
+            }
+        }
+        self.broadcast(line);
+    }
+
+    fn replay_scan(&self, key: &str, sender: &mpsc::Sender<String>) -> Option<usize> {
+        let replays = self.replays.lock().ok()?;
+        let replay = replays.get(key)?;
+        let lines: Vec<&String> = replay.progress.iter().chain(replay.events.iter()).collect();
+        for line in &lines {
+            let _ = sender.send((*line).clone());
+        }
+        Some(lines.len())
+    }
+
+    fn start_watch(&self, key: &str) -> Option<Arc<AtomicBool>> {
+        let mut watches = self.watches.lock().ok()?;
+        if watches.contains_key(key) {
+            return None;
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        watches.insert(key.to_string(), Arc::clone(&stop));
+        Some(stop)
+    }
+
+    fn stop_watch(&self, key: &str) -> bool {
+        let stop = self
+            .watches
+            .lock()
+            .ok()
+            .and_then(|mut watches| watches.remove(key));
+        match stop {
+            Some(flag) => {
+                flag.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn finish_watch(&self, key: &str, stop: &Arc<AtomicBool>) {
+        if let Ok(mut watches) = self.watches.lock() {
+            if watches.get(key).is_some_and(|flag| Arc::ptr_eq(flag, stop)) {
+                watches.remove(key);
+            }
+        }
+    }
+
+    fn start_listing(&self) -> Arc<AtomicBool> {
+        let flag = Arc::new(AtomicBool::new(false));
+        if let Ok(mut listings) = self.sized_listings.lock() {
+            listings.push(Arc::clone(&flag));
+        }
+        flag
+    }
+
+    fn finish_listing(&self, flag: &Arc<AtomicBool>) {
+        if let Ok(mut listings) = self.sized_listings.lock() {
+            listings.retain(|entry| !Arc::ptr_eq(entry, flag));
+        }
+    }
+
+    fn cancel_listings(&self) -> bool {
+        let mut cancelled = false;
+        if let Ok(mut listings) = self.sized_listings.lock() {
+            for flag in listings.drain(..) {
+                flag.store(true, Ordering::SeqCst);
+                cancelled = true;
+            }
+        }
+        cancelled
+    }
+
+    fn authorize_path(&self, path: &str) -> Result<(), RemoteError> {
+        if self.allowed_roots.is_empty() {
+            return Ok(());
+        }
+        let target = Path::new(path);
+        let resolved =
+            fs::canonicalize(target).or_else(|error| match (target.parent(), target.file_name()) {
+                (Some(parent), Some(name)) if !parent.as_os_str().is_empty() => {
+                    fs::canonicalize(parent).map(|parent| parent.join(name))
+                }
+                _ => Err(error),
+            });
+        match resolved {
+            Ok(resolved) => {
+                ensure_within_roots(&resolved, &self.allowed_roots).map_err(RemoteError::from)
+            }
+            Err(_) => Err(PathError::AccessDenied.into()),
+        }
+    }
+
+    fn validate_token(&self, token: Option<&str>) -> Option<&[RemoteScope]> {
+        if self.tokens.is_empty() {
+            return Some(ALL_REMOTE_SCOPES);
+        }
+        let token = token?;
+        self.tokens
+            .iter()
+            .find(|entry| entry.token == token)
+            .map(|entry| entry.scopes.as_slice())
+    }
+
+    fn prepare_delete(&self, paths: Vec<String>) -> Option<String> {
+        let mut pending = self.pending_deletes.lock().ok()?;
+        let now = Instant::now();
+        pending.retain(|_, entry| entry.expires_at > now);
+        let seed = format!(
+            "{:?}|{}|{}|{}",
+            SystemTime::now(),
+            std::process::id(),
+            pending.len(),
+            paths.join("\n")
+        );
+        let nonce = blake3::hash(seed.as_bytes()).to_hex()[..32].to_string();
+        pending.insert(
+            nonce.clone(),
+            PendingDelete {
+                paths,
+                expires_at: now + DELETE_CONFIRM_TTL,
+            },
+        );
+        Some(nonce)
+    }
+
+    fn confirm_delete(&self, nonce: &str, paths: &[String]) -> bool {
+        let Ok(mut pending) = self.pending_deletes.lock() else {
+            return false;
+        };
+        match pending.remove(nonce) {
+            Some(entry) => entry.expires_at > Instant::now() && entry.paths == paths,
+            None => false,
+        }
+    }
+
+    fn request_shutdown(&self) -> bool {
+        match &self.shutdown {
+            Some(sender) => sender.send(()).is_ok(),
+            None => false,
+        }
+    }
+}
+
+fn emit_to_remote(hub: &RemoteHub, event: ScanEvent, request_id: Option<&str>) {
+    let is_progress = event.is_progress();
+    let payload = match event {
+        ScanEvent::Progress(summary) => serde_json::json!({
+          "event": "scan-progress",
+          "id": request_id,
+          "data": summary
+        }),
+        ScanEvent::Complete(summary) => serde_json::json!({
+          "event": "scan-complete",
+          "id": request_id,
+          "data": summary
+        }),
+        ScanEvent::Duplicates(report) => serde_json::json!({
+          "event": "duplicates-complete",
+          "id": request_id,
+          "data": report
+        }),
+        ScanEvent::CountProgress(count) => serde_json::json!({
+          "event": "count-progress",
+          "id": request_id,
+          "data": count
+        }),
+        ScanEvent::CountComplete(count) => serde_json::json!({
+          "event": "count-complete",
+          "id": request_id,
+          "data": count
+        }),
+        ScanEvent::RecentFiles(report) => serde_json::json!({
+          "event": "recent-files-complete",
+          "id": request_id,
+          "data": report
+        }),
+        ScanEvent::NameGroups(report) => serde_json::json!({
+          "event": "name-groups-complete",
+          "id": request_id,
+          "data": report
+        }),
+        ScanEvent::ArchiveCandidates(report) => serde_json::json!({
+          "event": "archive-candidates-complete",
+          "id": request_id,
+          "data": report
+        }),
+        ScanEvent::CompressionEstimate(report) => serde_json::json!({
+          "event": "compression-estimate-complete",
+          "id": request_id,
+          "data": report
+        }),
+        ScanEvent::ExportProgress(progress) => serde_json::json!({
+          "event": "export-progress",
+          "id": request_id,
+          "data": progress
+        }),
+        ScanEvent::ExportComplete(progress) => serde_json::json!({
+          "event": "export-complete",
+          "id": request_id,
+          "data": progress
+        }),
+        ScanEvent::NetworkMount(notice) => serde_json::json!({
+          "event": "scan-network-mount",
+          "id": request_id,
+          "data": notice
+        }),
+        ScanEvent::Delta(delta) => serde_json::json!({
+          "event": "scan-delta",
+          "id": request_id,
+          "data": delta
+        }),
+        ScanEvent::Error(message) => serde_json::json!({
+          "event": "scan-error",
+          "id": request_id,
+          "message": message
+        }),
+        ScanEvent::Cancelled(message, summary) => serde_json::json!({
+          "event": "scan-cancelled",
+          "id": request_id,
+          "message": message,
+          "data": summary
+        }),
+        ScanEvent::Paused => serde_json::json!({
+          "event": "scan-paused",
+          "id": request_id
+        }),
+        ScanEvent::Resumed => serde_json::json!({
+          "event": "scan-resumed",
+          "id": request_id
+        }),
+    };
+    let line = format!("{}\n", payload);
+    hub.broadcast_scan_event(request_id.unwrap_or_default(), line, is_progress);
+}
+
+pub(crate) struct RemoteServerHandle {
+    shutdown: mpsc::Sender<()>,
+    join: thread::JoinHandle<()>,
+    pub(crate) hub: Arc<RemoteHub>,
+    pub(crate) local_addr: SocketAddr,
+}
+
+#[derive(Default)]
+struct RemoteSession {
+    compress: AtomicBool,
+    authenticated: AtomicBool,
+    protocol_version: AtomicU32,
+    incompatible: AtomicBool,
+    heartbeat: AtomicBool,
+    watches: Mutex<Vec<String>>,
+}
+
+enum RemoteClientEvent {
+    Message(JsonValue),
+    TimedOut,
+    Disconnected,
+}
+
+type RemoteClientSink = Arc<dyn Fn(RemoteClientEvent) + Send + Sync>;
+
+pub(crate) struct RemoteClientHandle {
+    sender: mpsc::Sender<String>,
+    shutdown: mpsc::Sender<()>,
+    join: thread::JoinHandle<()>,
+    token: Option<String>,
+    address: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteListEntry {
+    name: String,
+    path: String,
+    is_dir: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<u64>,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum HashAlgorithm {
+    Md5,
+    Sha256,
+    Blake3,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteStat {
+    path: String,
+    size_bytes: u64,
+    is_dir: bool,
+    is_file: bool,
+    is_symlink: bool,
+    modified_ms: Option<u64>,
+    created_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mode: Option<u32>,
+}
+
+pub(crate) fn start_remote_server(
+    config: TcpConfig,
+    headless: bool,
+    settings_path: &Path,
+) -> Result<RemoteServerHandle, String> {
+    eprintln!("[remote] starting tcp server on {}", config.bind_addr);
+    let listener = TcpListener::bind(config.bind_addr)
+        .map_err(|error| format!("Failed to bind TCP server: {error}"))?;
+    let local_addr = listener
+        .local_addr()
+        .map_err(|error| format!("Failed to bind TCP server: {error}"))?;
+    if local_addr != config.bind_addr {
+        eprintln!("[remote] listening on {local_addr}");
+    }
+    listener
+        .set_nonblocking(true)
+        .map_err(|error| format!("Failed to configure TCP listener: {error}"))?;
+    let tls = match (&config.tls_cert, &config.tls_key) {
+        (Some(cert), Some(key)) => Some(load_tls_server_config(cert, key)?),
+        _ => None,
+    };
+    let (shutdown_tx, shutdown_rx) = mpsc::channel();
+    let hub = Arc::new(RemoteHub::new(
+        config.token.clone(),
+        &config.scoped_tokens,
+        &config.allowed_roots,
+        config.hash_max_bytes,
+        settings_path,
+        Some(shutdown_tx.clone()),
+        config.allow_shutdown,
+    ));
+    let server_hub = Arc::clone(&hub);
+    let heartbeat_interval = config.heartbeat_interval;
+    let idle_timeout = heartbeat_interval.map(|interval| interval * HEARTBEAT_MISSED_LIMIT);
+    let mut last_heartbeat = Instant::now();
+    let join = thread::spawn(move || loop {
+        if shutdown_rx.try_recv().is_ok() {
+            break;
+        }
+        if let Some(interval) = heartbeat_interval {
+            if last_heartbeat.elapsed() >= interval {
+                last_heartbeat = Instant::now();
+                // Also prunes clients whose writer has already gone away.
+                hub.broadcast(format!(
+                    "{}\n",
+                    serde_json::json!({
+                      "event": "heartbeat",
+                      "data": { "intervalMs": interval.as_millis() as u64 }
+                    })
+                ));
+            }
+        }
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Ok(clients) = hub.clients.lock() {
+                    if clients.len() >= MAX_CONNECTIONS {
+                        eprintln!("[remote] connection limit reached, rejecting");
+                        continue;
+                    }
+                }
+                eprintln!("[remote] tcp client accepted");
+                let hub_clone = Arc::clone(&hub);
+                let tls_clone = tls.clone();
+                thread::spawn(move || {
+                    handle_client(stream, hub_clone, headless, tls_clone, idle_timeout)
+                });
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(50));
+            }
+            Err(_) => break,
+        }
+    });
+    Ok(RemoteServerHandle {
+        shutdown: shutdown_tx,
+        join,
+        hub: server_hub,
+        local_addr,
+    })
+}
+
+fn load_tls_server_config(
+    cert_path: &str,
+    key_path: &str,
+) -> Result<Arc<rustls::ServerConfig>, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|iter| iter.collect::<Result<Vec<_>, _>>())
+        .map_err(|error| format!("Failed to read TLS certificate {cert_path}: {error}"))?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|error| format!("Failed to read TLS key {key_path}: {error}"))?;
+    let config = rustls::ServerConfig::builder_with_provider(tls_crypto_provider())
+        .with_safe_default_protocol_versions()
+        .map_err(|error| format!("Failed to configure TLS: {error}"))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|error| format!("Invalid TLS certificate or key: {error}"))?;
+    Ok(Arc::new(config))
+}
+
+fn accept_tls(
+    mut stream: TcpStream,
+    config: Arc<rustls::ServerConfig>,
+) -> std::io::Result<RemoteStream> {
+    let mut connection = ServerConnection::new(config).map_err(std::io::Error::other)?;
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(TLS_HANDSHAKE_TIMEOUT))?;
+    while connection.is_handshaking() {
+        connection.complete_io(&mut stream)?;
+    }
+    stream.set_read_timeout(Some(Duration::from_millis(200)))?;
+    Ok(RemoteStream::Tls(Arc::new(TlsStream::new(
+        connection, stream,
+    ))))
+}
+
+fn handle_client(
+    stream: TcpStream,
+    hub: Arc<RemoteHub>,
+    headless: bool,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    idle_timeout: Option<Duration>,
+) {
+    eprintln!("[remote] tcp client connected");
+    let stream = match tls {
+        Some(config) => match accept_tls(stream, config) {
+            Ok(value) => value,
+            Err(error) => {
+                eprintln!("[remote] tls handshake failed: {error}");
+                return;
+            }
+        },
+        None => {
+            if let Err(error) = stream.set_read_timeout(Some(Duration::from_millis(200))) {
+                eprintln!("[remote] set read timeout failed: {error}");
+            }
+            RemoteStream::Plain(Arc::new(stream))
+        }
+    };
+    let (sender, receiver) = mpsc::channel::<String>();
+    hub.register_client(sender.clone());
+    let writer_stream = stream.clone();
+    let session = Arc::new(RemoteSession::default());
+    let writer_session = Arc::clone(&session);
+    thread::spawn(move || write_remote_lines(writer_stream, receiver, writer_session));
+    let mut reader = BufReader::new(stream.clone());
+    let mut last_seen = Instant::now();
+    loop {
+        let line = match read_secure_line(&mut reader, MAX_LINE_LENGTH) {
+            Ok(Some(value)) => {
+                eprintln!("[remote] read line bytes={}", value.len());
+                last_seen = Instant::now();
+                value
+            }
+            Ok(None) => break,
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                if session.heartbeat.load(Ordering::SeqCst)
+                    && idle_timeout.is_some_and(|timeout| last_seen.elapsed() >= timeout)
+                {
+                    eprintln!("[remote] client idle past heartbeat timeout, disconnecting");
+                    // The writer thread holds its own handle, so close the socket
+                    // rather than just dropping the reader.
+                    stream.shutdown();
+                    break;
+                }
+                continue;
+            }
+            Err(error) => {
+                eprintln!("[remote] read line error: {error}");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            eprintln!("[remote] read empty line");
+            continue;
+        }
+        handle_remote_line(&line, Arc::clone(&hub), &sender, &session, headless);
+    }
+    if let Ok(watches) = session.watches.lock() {
+        for key in watches.iter() {
+            hub.stop_watch(key);
+        }
+    };
+}
+
+fn write_remote_lines(
+    mut stream: RemoteStream,
+    receiver: mpsc::Receiver<String>,
+    session: Arc<RemoteSession>,
+) {
+    for line in receiver {
+        let line = if session.compress.load(Ordering::SeqCst)
+            && line.len() >= MIN_COMPRESSED_LINE_LENGTH
+        {
+            encode_compressed_line(&line).unwrap_or(line)
+        } else {
+            line
+        };
+        eprintln!("[remote] sending line bytes={}", line.len());
+        if let Err(error) = stream.write_all(line.as_bytes()) {
+            eprintln!("[remote] write failed: {error}");
+            break;
+        }
+        if let Err(error) = stream.flush() {
+            eprintln!("[remote] flush failed: {error}");
+            break;
+        }
+    }
+}
+
+fn encode_compressed_line(line: &str) -> Option<String> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(line.trim_end().as_bytes()).ok()?;
+    let bytes = encoder.finish().ok()?;
+    Some(format!(
+        "{}{}\n",
+        COMPRESSED_LINE_PREFIX,
+        BASE64_STANDARD.encode(bytes)
+    ))
+}
+
+fn decode_remote_line(line: &str) -> Option<String> {
+    let Some(encoded) = line.strip_prefix(COMPRESSED_LINE_PREFIX) else {
+        return Some(line.to_string());
+    };
+    let bytes = BASE64_STANDARD.decode(encoded.trim()).ok()?;
+    let mut decoded = String::new();
+    GzDecoder::new(bytes.as_slice())
+        .take(MAX_LINE_LENGTH)
+        .read_to_string(&mut decoded)
+        .ok()?;
+    Some(decoded)
+}
+
+fn handle_remote_line(
+    line: &str,
+    hub: Arc<RemoteHub>,
+    sender: &mpsc::Sender<String>,
+    session: &RemoteSession,
+    headless: bool,
+) {
+    // Security: Do not log incoming lines as they may contain auth tokens
+    let envelope: RemoteEnvelope = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(_) => {
+            eprintln!("[remote] invalid json");
+            send_remote_error(sender, None, RemoteError::InvalidJson);
+            return;
+        }
+    };
+    let scopes = match hub.validate_token(envelope.token.as_deref()) {
+        Some(value) => value,
+        None => {
+            eprintln!("[remote] unauthorized token");
+            // Security: Artificial delay to impede brute-force attacks
+            thread::sleep(Duration::from_secs(2));
+            send_remote_error(
+                sender,
+                request_id(&envelope.request),
+                RemoteError::Unauthorized,
+            );
+            return;
+        }
+    };
+    if let Some(scope) = required_scope(&envelope.request) {
+        if !scopes.contains(&scope) {
+            eprintln!("[remote] token lacks the required scope");
+            send_remote_error(
+                sender,
+                request_id(&envelope.request),
+                RemoteError::InsufficientScope,
+            );
+            return;
+        }
+    }
+    if !session.authenticated.swap(true, Ordering::SeqCst) {
+        hub.register_authenticated(sender.clone());
+    }
+    if session.incompatible.load(Ordering::SeqCst)
+        && !matches!(
+            envelope.request,
+            RemoteRequest::Hello { .. } | RemoteRequest::Heartbeat { .. }
+        )
+    {
+        send_remote_error(
+            sender,
+            request_id(&envelope.request),
+            RemoteError::UnsupportedProtocol,
+        );
+        return;
+    }
+    if let Some(Err(error)) = request_paths(&envelope.request)
+        .into_iter()
+        .map(|path| hub.authorize_path(path))
+        .find(Result::is_err)
+    {
+        eprintln!("[remote] path outside allowed roots");
+        send_remote_error(sender, request_id(&envelope.request), error);
+        return;
+    }
+    match envelope.request {
+        RemoteRequest::Ping { id } => {
+            eprintln!("[remote] ping {:?}", id);
+            send_remote_event(sender, serde_json::json!({ "event": "pong", "id": id }));
+        }
+        // Only resets the idle timer in `handle_client`; there is no reply.
+        RemoteRequest::Heartbeat { .. } => {}
+        RemoteRequest::Hello {
+            id,
+            client_version,
+            protocol_version,
+            compress,
+            heartbeat,
+        } => {
+            eprintln!(
+                "[remote] hello {:?} client={:?} protocol={:?}",
+                id, client_version, protocol_version
+            );
+            handle_remote_hello(sender, session, id, protocol_version, compress, heartbeat);
+        }
+        RemoteRequest::List {
+            id,
+            path,
+            include_files,
+            with_sizes,
+        } => {
+            eprintln!("[remote] list {:?} {:?}", id, path);
+            if with_sizes {
+                handle_remote_sized_list(hub, sender, id, path, include_files.unwrap_or(false));
+                return;
+            }
+            handle_remote_list(
+                sender,
+                id,
+                path,
+                include_files.unwrap_or(false),
+                &hub.allowed_roots,
+            );
+        }
+        RemoteRequest::Disk { id, path } => {
+            eprintln!("[remote] disk {:?} {}", id, path);
+            handle_remote_disk(sender, id, path);
+        }
+        RemoteRequest::Read { id, path } => {
+            eprintln!("[remote] read {:?} {}", id, path);
+            handle_remote_read(sender, id, path);
+        }
+        RemoteRequest::ReadChunked {
+            id,
+            path,
+            offset,
+            length,
+        } => {
+            eprintln!("[remote] read chunk {:?} {} @{}", id, path, offset);
+            handle_remote_read_chunk(sender, id, path, offset, length);
+        }
+        RemoteRequest::ReadHex {
+            id,
+            path,
+            offset,
+            length,
+        } => {
+            eprintln!("[remote] read hex {:?} {} @{}", id, path, offset);
+            handle_remote_read_hex(sender, id, path, offset, length);
+        }
+        RemoteRequest::Stat { id, path } => {
+            eprintln!("[remote] stat {:?} {}", id, path);
+            handle_remote_stat(sender, id, path);
+        }
+        RemoteRequest::Hash { id, path, algo } => {
+            eprintln!("[remote] hash {:?} {}", id, path);
+            handle_remote_hash(&hub, sender, id, path, algo);
+        }
+        RemoteRequest::Trash { id, paths } => {
+            eprintln!("[remote] trash {:?} count={}", id, paths.len());
+            send_remote_event(
+                sender,
+                serde_json::json!({
+                  "event": "trash-complete",
+                  "id": id,
+                  "data": measure_delete(&paths, &hub.allowed_roots, || {
+                      trash_paths(&paths, &hub.allowed_roots)
+                  })
+                }),
+            );
+        }
+        RemoteRequest::Move { id, from, to } => {
+            eprintln!("[remote] move {:?}", id);
+            handle_remote_move(sender, id, from, to);
+        }
+        RemoteRequest::Copy {
+            id,
+            from,
+            to,
+            overwrite,
+        } => {
+            eprintln!("[remote] copy {:?}", id);
+            handle_remote_copy(Arc::clone(&hub), sender, id, from, to, overwrite);
+        }
+        RemoteRequest::PrepareDelete { id, paths } => {
+            eprintln!("[remote] prepare delete {:?} count={}", id, paths.len());
+            handle_remote_prepare_delete(&hub, sender, id, paths);
+        }
+        RemoteRequest::Delete { id, paths, confirm } => {
+            eprintln!("[remote] delete {:?} count={}", id, paths.len());
+            if !confirm.is_some_and(|nonce| hub.confirm_delete(&nonce, &paths)) {
+                send_remote_error(sender, id.as_deref(), RemoteError::ConfirmationRequired);
+                return;
+            }
+            send_remote_event(
+                sender,
+                serde_json::json!({
+                  "event": "delete-complete",
+                  "id": id,
+                  "data": measure_delete(&paths, &hub.allowed_roots, || {
+                      delete_paths(&paths, &hub.allowed_roots)
+                  })
+                }),
+            );
+        }
+        RemoteRequest::Scan { id, path, options } => {
+            eprintln!("[remote] scan {:?} {}", id, path);
+            handle_remote_scan(hub, sender, id, path, options);
+        }
+        RemoteRequest::Duplicates { id, path, options } => {
+            eprintln!("[remote] duplicates {:?} {}", id, path);
+            handle_remote_duplicates(hub, sender, id, path, options);
+        }
+        RemoteRequest::Export { id, path, options } => {
+            eprintln!("[remote] export {:?} {}", id, path);
+            handle_remote_export(hub, sender, id, path, options);
+        }
+        RemoteRequest::ListPresets { id } => {
+            eprintln!("[remote] list presets {:?}", id);
+            let presets = sorted_scan_presets(&load_effective_settings(&hub.settings_path));
+            send_remote_event(
+                sender,
+                serde_json::json!({
+                  "event": "presets",
+                  "id": id,
+                  "data": { "presets": presets }
+                }),
+            );
+        }
+        RemoteRequest::RunPreset { id, name, path } => {
+            eprintln!("[remote] run preset {:?} {} {}", id, name, path);
+            match load_effective_settings(&hub.settings_path)
+                .scan_presets
+                .remove(&name)
+            {
+                Some(options) => handle_remote_scan(hub, sender, id, path, Some(options)),
+                None => send_remote_error(sender, id.as_deref(), RemoteError::PresetNotFound),
+            }
+        }
+        RemoteRequest::ValidateOptions { id, options } => {
+            eprintln!("[remote] validate options {:?}", id);
+            let errors = scan_option_errors(&options);
+            send_remote_event(
+                sender,
+                serde_json::json!({
+                  "event": "options-validated",
+                  "id": id,
+                  "data": { "valid": errors.is_empty(), "errors": errors }
+                }),
+            );
+        }
+        RemoteRequest::Watch { id, path, filters } => {
+            eprintln!("[remote] watch {:?} {}", id, path);
+            handle_remote_watch(hub, sender, session, id, path, filters);
+        }
+        RemoteRequest::Unwatch { id } => {
+            eprintln!("[remote] unwatch {:?}", id);
+            let message = if hub.stop_watch(id.as_deref().unwrap_or_default()) {
+                "watch-stopped"
+            } else {
+                "no-active-watch"
+            };
+            send_remote_event(sender, serde_json::json!({ "event": message, "id": id }));
+        }
+        RemoteRequest::Cancel { id } => {
+            eprintln!("[remote] cancel {:?}", id);
+            // An id naming a running scan stops just that scan. Otherwise every
+            // scan and sized listing is stopped, as before scans had ids (not
+            // `||`, so both always run).
+            let cancelled = match id.as_deref().filter(|key| hub.has_scan(key)) {
+                Some(key) => hub.cancel_scan(Some(key)),
+                None => hub.cancel_scan(None) | hub.cancel_listings(),
+            };
+            let message = if cancelled {
+                "cancel-requested"
+            } else {
+                "no-active-scan"
+            };
+            send_remote_event(sender, serde_json::json!({ "event": message, "id": id }));
+        }
+        RemoteRequest::Subscribe { id } => {
+            eprintln!("[remote] subscribe {:?}", id);
+            match hub.replay_scan(id.as_deref().unwrap_or_default(), sender) {
+                Some(replayed) => send_remote_event(
+                    sender,
+                    serde_json::json!({
+                      "event": "subscribed",
+                      "id": id,
+                      "data": { "replayed": replayed }
+                    }),
+                ),
+                None => send_remote_event(
+                    sender,
+                    serde_json::json!({ "event": "no-active-scan", "id": id }),
+                ),
+            }
+        }
+        RemoteRequest::ScanStatus { id, scan_id } => {
+            eprintln!("[remote] scan status {:?} {}", id, scan_id);
+            let status = hub.finished_scans.status(&scan_id, hub.has_scan(&scan_id));
+            send_remote_event(
+                sender,
+                serde_json::json!({
+                  "event": "scan-status",
+                  "id": id,
+                  "data": { "scanId": scan_id, "status": status }
+                }),
+            );
+        }
+        RemoteRequest::Pause { id } => {
+            eprintln!("[remote] pause {:?}", id);
+            let target = id.as_deref().filter(|key| hub.has_scan(key));
+            let message = if hub.set_scan_paused(target, true) {
+                "pause-requested"
+            } else {
+                "no-active-scan"
+            };
+            send_remote_event(sender, serde_json::json!({ "event": message, "id": id }));
+        }
+        RemoteRequest::Resume { id } => {
+            eprintln!("[remote] resume {:?}", id);
+            let target = id.as_deref().filter(|key| hub.has_scan(key));
+            let message = if hub.set_scan_paused(target, false) {
+                "resume-requested"
+            } else {
+                "no-active-scan"
+            };
+            send_remote_event(sender, serde_json::json!({ "event": message, "id": id }));
+        }
+        RemoteRequest::Shutdown { id } => {
+            eprintln!("[remote] shutdown {:?}", id);
+            if !headless {
+                if !hub.allow_gui_shutdown {
+                    send_remote_error(sender, id.as_deref(), RemoteError::ShutdownNotAllowed);
+                    return;
+                }
+                match hub.app.lock().ok().and_then(|app| app.clone()) {
+                    Some(app) => {
+                        send_remote_event(
+                            sender,
+                            serde_json::json!({ "event": "shutdown", "id": id }),
+                        );
+                        shutdown_gui(&hub, app);
+                    }
+                    None => send_remote_error(sender, id.as_deref(), RemoteError::ShutdownFailed),
+                }
+                return;
+            }
+            if hub.request_shutdown() {
+                send_remote_event(sender, serde_json::json!({ "event": "shutdown", "id": id }));
+            } else {
+                send_remote_error(sender, id.as_deref(), RemoteError::ShutdownFailed);
+            }
+        }
+    }
+}
+
+fn shutdown_gui(hub: &RemoteHub, app: tauri::AppHandle) {
+    hub.cancel_scan(None);
+    hub.cancel_listings();
+    if let Ok(scans) = app.state::<ScanCancellation>().0.lock() {
+        for scan in scans.values() {
+            scan.control.cancel.store(true, Ordering::SeqCst);
+        }
+    }
+    hub.request_shutdown();
+    thread::spawn(move || {
+        // Give the `shutdown` reply and the cancelled scans a moment to go out.
+        thread::sleep(Duration::from_millis(250));
+        let settings = app.state::<SettingsState>();
+        for window in app.webview_windows().values() {
+            remember_layout_geometry(window, &settings);
+        }
+        app.exit(0);
+    });
+}
+
+fn handle_remote_scan(
+    hub: Arc<RemoteHub>,
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: String,
+    options: Option<ScanOptions>,
+) {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        send_remote_error(sender, id.as_deref(), PathError::PathNotFound);
+        return;
+    }
+    let config = match build_scan_config(&options.unwrap_or_default()) {
+        Ok(value) => value,
+        Err(error) => {
+            send_remote_error(sender, id.as_deref(), PathError::InvalidOptions(error));
+            return;
+        }
+    };
+    let control = ScanControl::default();
+    let key = id.clone().unwrap_or_default();
+    if let Err(error) = hub.start_scan(&key, control.clone()) {
+        send_remote_error(sender, id.as_deref(), error);
+        return;
+    }
+    send_remote_event(
+        sender,
+        serde_json::json!({ "event": "scan-started", "id": id }),
+    );
+    let hub_for_scan = Arc::clone(&hub);
+    let request_id = id.clone();
+    thread::spawn(move || {
+        let hub_ref = Arc::clone(&hub_for_scan);
+        let request_id_for_emit = request_id.clone();
+        let emitter_hub = Arc::clone(&hub_ref);
+        let emitter: ScanEmitter = Arc::new(move |event| {
+            emit_to_remote(&emitter_hub, event, request_id_for_emit.as_deref());
+        });
+        if let Err(error) = run_scan(vec![root], config, control, emitter, id.clone()) {
+            emit_to_remote(&hub_ref, ScanEvent::Error(error), request_id.as_deref());
+        }
+        hub_ref.finish_scan(&key);
+    });
+}
+
+fn handle_remote_watch(
+    hub: Arc<RemoteHub>,
+    sender: &mpsc::Sender<String>,
+    session: &RemoteSession,
+    id: Option<String>,
+    path: String,
+    filters: Option<ScanFilters>,
+) {
+    let root = match fs::canonicalize(&path) {
+        Ok(value) if value.is_dir() => value,
+        Ok(_) => {
+            send_remote_error(
+                sender,
+                id.as_deref(),
+                PathError::InvalidOptions("Path is not a directory".to_string()),
+            );
+            return;
+        }
+        Err(_) => {
+            send_remote_error(sender, id.as_deref(), PathError::PathNotFound);
+            return;
+        }
+    };
+    let filters = match build_filter_config(&filters.unwrap_or_default()) {
+        Ok(value) => value,
+        Err(errors) => {
+            let error = first_option_error(errors);
+            send_remote_error(sender, id.as_deref(), PathError::InvalidOptions(error));
+            return;
+        }
+    };
+    let key = id.clone().unwrap_or_default();
+    let stop = match hub.start_watch(&key) {
+        Some(value) => value,
+        None => {
+            send_remote_error(
+                sender,
+                id.as_deref(),
+                PathError::InvalidOptions("A watch with this id is already running".to_string()),
+            );
+            return;
+        }
+    };
+    let emitter_hub = Arc::clone(&hub);
+    let request_id = id.clone();
+    let emitter: ScanEmitter = Arc::new(move |event| {
+        emit_to_remote(&emitter_hub, event, request_id.as_deref());
+    });
+    let finish_hub = Arc::clone(&hub);
+    let finish_key = key.clone();
+    let finish_stop = Arc::clone(&stop);
+    let started = start_path_watch(
+        root,
+        filters,
+        key.clone(),
+        stop,
+        emitter,
+        Box::new(move || finish_hub.finish_watch(&finish_key, &finish_stop)),
+    );
+    if let Err(error) = started {
+        hub.stop_watch(&key);
+        send_remote_error(sender, id.as_deref(), PathError::Io(error));
+        return;
+    }
+    if let Ok(mut watches) = session.watches.lock() {
+        watches.push(key);
+    }
+    send_remote_event(
+        sender,
+        serde_json::json!({ "event": "watch-started", "id": id }),
+    );
+}
+
+fn handle_remote_prepare_delete(
+    hub: &RemoteHub,
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    paths: Vec<String>,
+) {
+    if paths.is_empty() {
+        send_remote_error(
+            sender,
+            id.as_deref(),
+            PathError::InvalidOptions("paths must not be empty".to_string()),
+        );
+        return;
+    }
+    match hub.prepare_delete(paths.clone()) {
+        Some(confirm) => send_remote_event(
+            sender,
+            serde_json::json!({
+              "event": "delete-prepared",
+              "id": id,
+              "data": {
+                "confirm": confirm,
+                "paths": paths,
+                "expiresInMs": DELETE_CONFIRM_TTL.as_millis()
+              }
+            }),
+        ),
+        None => send_remote_error(
+            sender,
+            id.as_deref(),
+            PathError::Io("Failed to lock delete state".to_string()),
+        ),
+    }
+}
+
+fn handle_remote_hello(
+    sender: &mpsc::Sender<String>,
+    session: &RemoteSession,
+    id: Option<String>,
+    protocol_version: Option<u32>,
+    compress: bool,
+    heartbeat: bool,
+) {
+    // Clients that predate versioning are treated as speaking the oldest version.
+    let requested = protocol_version.unwrap_or(MIN_PROTOCOL_VERSION);
+    if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&requested) {
+        session.incompatible.store(true, Ordering::SeqCst);
+        send_remote_error(sender, id.as_deref(), RemoteError::UnsupportedProtocol);
+        return;
+    }
+    session.incompatible.store(false, Ordering::SeqCst);
+    session.protocol_version.store(requested, Ordering::SeqCst);
+    send_remote_event(
+        sender,
+        serde_json::json!({
+          "event": "hello",
+          "id": id,
+          "data": {
+            "serverVersion": env!("CARGO_PKG_VERSION"),
+            "protocolVersion": requested,
+            "actions": REMOTE_ACTIONS,
+            "capabilities": engine_capabilities(),
+            "compress": compress,
+            "heartbeat": heartbeat
+          }
+        }),
+    );
+    session.compress.store(compress, Ordering::SeqCst);
+    session.heartbeat.store(heartbeat, Ordering::SeqCst);
+}
+
+fn handle_remote_duplicates(
+    hub: Arc<RemoteHub>,
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: String,
+    options: Option<ScanOptions>,
+) {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        send_remote_error(sender, id.as_deref(), PathError::PathNotFound);
+        return;
+    }
+    let config = match build_scan_config(&options.unwrap_or_default()) {
+        Ok(value) => value,
+        Err(error) => {
+            send_remote_error(sender, id.as_deref(), PathError::InvalidOptions(error));
+            return;
+        }
+    };
+    let control = ScanControl::default();
+    let key = id.clone().unwrap_or_default();
+    if let Err(error) = hub.start_scan(&key, control.clone()) {
+        send_remote_error(sender, id.as_deref(), error);
+        return;
+    }
+    send_remote_event(
+        sender,
+        serde_json::json!({ "event": "duplicates-started", "id": id }),
+    );
+    let hub_for_scan = Arc::clone(&hub);
+    thread::spawn(move || {
+        let request_id_for_emit = id.clone();
+        let emitter_hub = Arc::clone(&hub_for_scan);
+        let emitter: ScanEmitter = Arc::new(move |event| {
+            emit_to_remote(&emitter_hub, event, request_id_for_emit.as_deref());
+        });
+        if let Err(error) = run_duplicate_scan(root, config, control, emitter, id.clone()) {
+            emit_to_remote(&hub_for_scan, ScanEvent::Error(error), id.as_deref());
+        }
+        hub_for_scan.finish_scan(&key);
+    });
+}
+
+fn handle_remote_export(
+    hub: Arc<RemoteHub>,
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: String,
+    options: Option<ScanOptions>,
+) {
+    let root = PathBuf::from(&path);
+    if !root.exists() {
+        send_remote_error(sender, id.as_deref(), PathError::PathNotFound);
+        return;
+    }
+    let config = match build_scan_config(&options.unwrap_or_default()) {
+        Ok(value) => value,
+        Err(error) => {
+            send_remote_error(sender, id.as_deref(), PathError::InvalidOptions(error));
+            return;
+        }
+    };
+    let control = ScanControl::default();
+    let key = id.clone().unwrap_or_default();
+    if let Err(error) = hub.start_scan(&key, control.clone()) {
+        send_remote_error(sender, id.as_deref(), error);
+        return;
+    }
+    send_remote_event(
+        sender,
+        serde_json::json!({ "event": "export-started", "id": id }),
+    );
+    let hub_for_scan = Arc::clone(&hub);
+    let mut out = RemoteExportWriter {
+        sender: sender.clone(),
+        id: id.clone(),
+        pending: Vec::new(),
+    };
+    thread::spawn(move || {
+        let request_id_for_emit = id.clone();
+        let emitter_hub = Arc::clone(&hub_for_scan);
+        let emitter: ScanEmitter = Arc::new(move |event| {
+            emit_to_remote(&emitter_hub, event, request_id_for_emit.as_deref());
+        });
+        if let Err(error) = run_ndjson_export(root, config, control, emitter, id.clone(), &mut out)
+        {
+            emit_to_remote(&hub_for_scan, ScanEvent::Error(error), id.as_deref());
+        }
+        hub_for_scan.finish_scan(&key);
+    });
+}
+
+struct RemoteExportWriter {
+    sender: mpsc::Sender<String>,
+    id: Option<String>,
+    pending: Vec<u8>,
+}
+
+impl Write for RemoteExportWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        while let Some(end) = self.pending.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let data: serde_json::Value = serde_json::from_slice(&line[..end])?;
+            let message = serde_json::json!({
+              "event": "export-line",
+              "id": self.id,
+              "data": data
+            });
+            self.sender
+                .send(format!("{}\n", message))
+                .map_err(|_| std::io::Error::from(std::io::ErrorKind::BrokenPipe))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn handle_remote_move(sender: &mpsc::Sender<String>, id: Option<String>, from: String, to: String) {
+    let sender = sender.clone();
+    thread::spawn(move || {
+        let mut on_progress = |progress: &TransferProgress| {
+            send_remote_event(
+                &sender,
+                serde_json::json!({ "event": "move-progress", "id": id, "data": progress }),
+            );
+        };
+        match move_path_to(&from, &to, &mut on_progress) {
+            Ok(path) => send_remote_event(
+                &sender,
+                serde_json::json!({
+                  "event": "move-complete",
+                  "id": id,
+                  "data": { "path": path.to_string_lossy() }
+                }),
+            ),
+            Err(error) => send_remote_error(&sender, id.as_deref(), error),
+        }
+    });
+}
+
+fn handle_remote_copy(
+    hub: Arc<RemoteHub>,
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    from: String,
+    to: String,
+    overwrite: Option<bool>,
+) {
+    let plan = match plan_copy(&from, &to, overwrite.unwrap_or(false)) {
+        Ok(value) => value,
+        Err(error) => {
+            send_remote_error(sender, id.as_deref(), error);
+            return;
+        }
+    };
+    let control = ScanControl::default();
+    let key = id.clone().unwrap_or_default();
+    if let Err(error) = hub.start_scan(&key, control.clone()) {
+        send_remote_error(sender, id.as_deref(), error);
+        return;
+    }
+    send_remote_event(
+        sender,
+        serde_json::json!({ "event": "copy-started", "id": id }),
+    );
+    let sender = sender.clone();
+    thread::spawn(move || {
+        let mut on_progress = |progress: &TransferProgress| {
+            send_remote_event(
+                &sender,
+                serde_json::json!({ "event": "copy-progress", "id": id, "data": progress }),
+            );
+        };
+        match run_copy(&plan, &control, &mut on_progress) {
+            Ok(Some(progress)) => send_remote_event(
+                &sender,
+                serde_json::json!({ "event": "copy-complete", "id": id, "data": progress }),
+            ),
+            Ok(None) => send_remote_event(
+                &sender,
+                serde_json::json!({
+                  "event": "copy-cancelled",
+                  "id": id,
+                  "message": "Copy cancelled"
+                }),
+            ),
+            Err(error) => send_remote_error(&sender, id.as_deref(), error),
+        }
+        hub.finish_scan(&key);
+    });
+}
+
+fn handle_remote_disk(sender: &mpsc::Sender<String>, id: Option<String>, path: String) {
+    let target = PathBuf::from(&path);
+    match compute_disk_usage(&target) {
+        Ok(snapshot) => {
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "disk-info", "id": id, "data": snapshot }),
+            );
+        }
+        Err(message) => {
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "disk-error", "id": id, "message": message }),
+            );
+        }
+    }
+}
+
+fn handle_remote_stat(sender: &mpsc::Sender<String>, id: Option<String>, path: String) {
+    // symlink_metadata so links are reported as links rather than their targets.
+    let metadata = match fs::symlink_metadata(&path) {
+        Ok(value) => value,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            send_remote_error(sender, id.as_deref(), PathError::PathNotFound);
+            return;
+        }
+        Err(e) => {
+            send_remote_error(sender, id.as_deref(), PathError::Io(e.to_string()));
+            return;
+        }
+    };
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        Some(metadata.permissions().mode())
+    };
+    #[cfg(not(unix))]
+    let mode = None;
+    let stat = RemoteStat {
+        path,
+        size_bytes: metadata.len(),
+        is_dir: metadata.is_dir(),
+        is_file: metadata.is_file(),
+        is_symlink: metadata.file_type().is_symlink(),
+        modified_ms: get_time_millis(metadata.modified()),
+        created_ms: get_time_millis(metadata.created()),
+        mode,
+    };
+    send_remote_event(
+        sender,
+        serde_json::json!({ "event": "stat", "id": id, "data": stat }),
+    );
+}
+
+fn handle_remote_hash(
+    hub: &RemoteHub,
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: String,
+    algo: HashAlgorithm,
+) {
+    let size_bytes = match fs::metadata(&path) {
+        Ok(meta) if meta.is_file() => meta.len(),
+        Ok(_) => {
+            send_remote_error(sender, id.as_deref(), RemoteError::NotAFile);
+            return;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            send_remote_error(sender, id.as_deref(), PathError::PathNotFound);
+            return;
+        }
+        Err(e) => {
+            send_remote_error(sender, id.as_deref(), PathError::Io(e.to_string()));
+            return;
+        }
+    };
+    if hub.hash_max_bytes.is_some_and(|limit| size_bytes > limit) {
+        send_remote_error(sender, id.as_deref(), RemoteError::FileTooLarge);
+        return;
+    }
+    let sender = sender.clone();
+    thread::spawn(move || match hash_file_streaming(Path::new(&path), algo) {
+        Ok(digest) => send_remote_event(
+            &sender,
+            serde_json::json!({
+              "event": "hash-complete",
+              "id": id,
+              "data": { "path": path, "algo": algo, "sizeBytes": size_bytes, "digest": digest }
+            }),
+        ),
+        Err(e) => send_remote_error(&sender, id.as_deref(), PathError::Io(e.to_string())),
+    });
+}
+
+fn hash_file_streaming(path: &Path, algo: HashAlgorithm) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut buffer = vec![0u8; HASH_CHUNK_SIZE];
+    let mut feed = |update: &mut dyn FnMut(&[u8])| -> std::io::Result<()> {
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                return Ok(());
+            }
+            update(&buffer[..read]);
+        }
+    };
+    let to_hex = |bytes: &[u8]| bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    match algo {
+        HashAlgorithm::Md5 => {
+            let mut hasher = Md5::new();
+            feed(&mut |chunk| hasher.update(chunk))?;
+            Ok(to_hex(&hasher.finalize()))
+        }
+        HashAlgorithm::Sha256 => {
+            let mut hasher = Sha256::new();
+            feed(&mut |chunk| hasher.update(chunk))?;
+            Ok(to_hex(&hasher.finalize()))
+        }
+        HashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            feed(&mut |chunk| {
+                hasher.update(chunk);
+            })?;
+            Ok(hasher.finalize().to_hex().to_string())
+        }
+    }
+}
+
+fn handle_remote_read(sender: &mpsc::Sender<String>, id: Option<String>, path: String) {
+    let target = PathBuf::from(&path);
+    if !target.exists() {
+        send_remote_error(sender, id.as_deref(), PathError::PathNotFound);
+        return;
+    }
+    if !target.is_file() {
+        send_remote_error(sender, id.as_deref(), RemoteError::NotAFile);
+        return;
+    }
+    match fs::metadata(&target) {
+        Ok(meta) => {
+            if meta.len() > 5 * 1024 * 1024 {
+                send_remote_error(sender, id.as_deref(), RemoteError::FileTooLarge);
+                return;
+            }
+        }
+        Err(e) => {
+            send_remote_error(sender, id.as_deref(), PathError::Io(e.to_string()));
+            return;
+        }
+    }
+    match fs::read(&target) {
+        Ok(bytes) => {
+            let data = BASE64_STANDARD.encode(&bytes);
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "read-complete", "id": id, "data": { "path": path, "content": data } }),
+            );
+        }
+        Err(e) => {
+            send_remote_error(sender, id.as_deref(), PathError::Io(e.to_string()));
+        }
+    }
+}
+
+fn handle_remote_read_chunk(
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: String,
+    offset: u64,
+    length: Option<u64>,
+) {
+    let requested = length.unwrap_or(MAX_READ_CHUNK).min(MAX_READ_CHUNK);
+    let (bytes, total_bytes) = match read_file_range(Path::new(&path), offset, requested) {
+        Ok(range) => range,
+        Err(error) => {
+            send_remote_error(sender, id.as_deref(), error);
+            return;
+        }
+    };
+    let read = bytes.len() as u64;
+    send_remote_event(
+        sender,
+        serde_json::json!({
+          "event": "read-chunk",
+          "id": id,
+          "data": {
+            "path": path,
+            "offset": offset,
+            "length": read,
+            "totalBytes": total_bytes,
+            "content": BASE64_STANDARD.encode(&bytes),
+            "eof": offset + read >= total_bytes
+          }
+        }),
+    );
+}
+
+fn handle_remote_read_hex(
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: String,
+    offset: u64,
+    length: Option<u64>,
+) {
+    let requested = length.unwrap_or(DEFAULT_HEX_DUMP).min(MAX_HEX_DUMP);
+    let (bytes, total_bytes) = match read_file_range(Path::new(&path), offset, requested) {
+        Ok(range) => range,
+        Err(error) => {
+            send_remote_error(sender, id.as_deref(), error);
+            return;
+        }
+    };
+    let read = bytes.len() as u64;
+    send_remote_event(
+        sender,
+        serde_json::json!({
+          "event": "read-hex",
+          "id": id,
+          "data": {
+            "path": path,
+            "offset": offset,
+            "length": read,
+            "totalBytes": total_bytes,
+            "dump": format_hex_dump(offset, &bytes),
+            "eof": offset + read >= total_bytes
+          }
+        }),
+    );
+}
+
+fn read_file_range(target: &Path, offset: u64, length: u64) -> Result<(Vec<u8>, u64), RemoteError> {
+    if !target.exists() {
+        return Err(PathError::PathNotFound.into());
+    }
+    if !target.is_file() {
+        return Err(RemoteError::NotAFile);
+    }
+    let total_bytes = fs::metadata(target)
+        .map_err(|e| PathError::Io(e.to_string()))?
+        .len();
+    if offset > total_bytes || length == 0 {
+        return Err(RemoteError::OutOfRange);
+    }
+    let window = length.min(total_bytes - offset);
+    let bytes = fs::File::open(target)
+        .and_then(|mut file| {
+            file.seek(SeekFrom::Start(offset))?;
+            let mut buffer = Vec::with_capacity(window as usize);
+            file.take(window).read_to_end(&mut buffer)?;
+            Ok(buffer)
+        })
+        .map_err(|e| PathError::Io(e.to_string()))?;
+    Ok((bytes, total_bytes))
+}
+
+fn format_hex_dump(offset: u64, bytes: &[u8]) -> String {
+    let mut dump = String::new();
+    for (index, line) in bytes.chunks(HEX_DUMP_WIDTH).enumerate() {
+        let line_offset = offset + (index * HEX_DUMP_WIDTH) as u64;
+        dump.push_str(&format!("{line_offset:08x} "));
+        for column in 0..HEX_DUMP_WIDTH {
+            if column == HEX_DUMP_WIDTH / 2 {
+                dump.push(' ');
+            }
+            match line.get(column) {
+                Some(byte) => dump.push_str(&format!(" {byte:02x}")),
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str("  |");
+        for byte in line {
+            match byte {
+                0x20..=0x7e => dump.push(*byte as char),
+                _ => dump.push('.'),
+            }
+        }
+        dump.push_str("|\n");
+    }
+    dump
+}
+
+fn handle_remote_list(
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: Option<String>,
+    include_files: bool,
+    allowed_roots: &[PathBuf],
+) {
+    eprintln!("[remote] handle list {:?} {:?}", id, path);
+    let target = resolve_list_target(path.as_deref(), include_files, allowed_roots);
+    let (entries, list_path) = match target {
+        Ok(value) => value,
+        Err(message) => {
+            eprintln!("[remote] list error {:?}", message);
+            send_remote_event(
+                sender,
+                serde_json::json!({ "event": "list-error", "id": id, "message": message }),
+            );
+            return;
+        }
+    };
+    eprintln!("[remote] list ok {:?} entries={}", list_path, entries.len());
+    let payload = serde_json::json!({
+      "event": "list-complete",
+      "id": id,
+      "data": {
+        "path": list_path,
+        "entries": entries,
+        "os": if cfg!(target_os = "windows") { "windows" } else { "unix" }
+      }
+    });
+    send_remote_event(sender, payload);
+}
+
+fn handle_remote_sized_list(
+    hub: Arc<RemoteHub>,
+    sender: &mpsc::Sender<String>,
+    id: Option<String>,
+    path: Option<String>,
+    include_files: bool,
+) {
+    let (entries, list_path) =
+        match resolve_list_target(path.as_deref(), include_files, &hub.allowed_roots) {
+            Ok(value) => value,
+            Err(message) => {
+                send_remote_event(
+                    sender,
+                    serde_json::json!({ "event": "list-error", "id": id, "message": message }),
+                );
+                return;
+            }
+        };
+    let cancel = hub.start_listing();
+    let sender = sender.clone();
+    thread::spawn(move || {
+        let total = entries.len();
+        let mut sized = Vec::with_capacity(total);
+        for (index, mut entry) in entries.into_iter().enumerate() {
+            if entry.is_dir {
+                entry.size_bytes = measure_tree_size(Path::new(&entry.path), &cancel);
+            }
+            if cancel.load(Ordering::SeqCst) {
+                send_remote_event(
+                    &sender,
+                    serde_json::json!({ "event": "list-cancelled", "id": id }),
+                );
+                hub.finish_listing(&cancel);
+                return;
+            }
+            send_remote_event(
+                &sender,
+                serde_json::json!({
+                  "event": "list-entry",
+                  "id": id,
+                  "data": { "entry": entry, "index": index, "total": total }
+                }),
+            );
+            sized.push(entry);
+        }
+        hub.finish_listing(&cancel);
+        send_remote_event(
+            &sender,
+            serde_json::json!({
+              "event": "list-complete",
+              "id": id,
+              "data": {
+                "path": list_path,
+                "entries": sized,
+                "os": if cfg!(target_os = "windows") { "windows" } else { "unix" }
+              }
+            }),
+        );
+    });
+}
+
+fn measure_tree_size(path: &Path, cancel: &AtomicBool) -> Option<u64> {
+    let mut total: u64 = 0;
+    for entry in WalkDir::new(path).into_iter().flatten() {
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        if entry.file_type().is_file() {
+            total += entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+        }
+    }
+    Some(total)
+}
+
+fn resolve_list_target(
+    path: Option<&str>,
+    include_files: bool,
+    allowed_roots: &[PathBuf],
+) -> Result<(Vec<RemoteListEntry>, Option<String>), String> {
+    let trimmed = path.unwrap_or("").trim();
+    // With an allowlist, the top level shows the allowed roots instead of drives or `/`.
+    if trimmed.is_empty() && !allowed_roots.is_empty() {
+        let entries = allowed_roots
+            .iter()
+            .map(|root| RemoteListEntry {
+                name: get_path_string(root),
+                path: get_path_string(root),
+                is_dir: true,
+                size_bytes: None,
+            })
+            .collect();
+        return Ok((entries, None));
+    }
+    if trimmed.is_empty() {
+        #[cfg(target_os = "windows")]
+        {
+            return Ok((list_windows_drives(), None));
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            let root = PathBuf::from("/");
+            let entries = list_directory_entries(&root, include_files)?;
+            return Ok((entries, Some("/".to_string())));
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        if trimmed == "/" || trimmed == "\\" {
+            return Ok((list_windows_drives(), None));
+        }
+    }
+    let target = PathBuf::from(trimmed);
+    if !target.exists() {
+        return Err("path-not-found".to_string());
+    }
+    let entries = list_directory_entries(&target, include_files)?;
+    Ok((entries, Some(trimmed.to_string())))
+}
+
+fn list_directory_entries(
+    path: &Path,
+    include_files: bool,
+) -> Result<Vec<RemoteListEntry>, String> {
+    let mut entries: Vec<RemoteListEntry> = Vec::new();
+    let read_dir = fs::read_dir(path).map_err(|error| format!("list-failed: {error}"))?;
+    for entry in read_dir {
+        let entry = match entry {
+            Ok(value) => value,
+            Err(error) => {
+                eprintln!("[remote] list entry error: {error}");
+                continue;
+            }
+        };
+        let entry_path = entry.path();
+        let is_dir = entry
+            .file_type()
+            .map(|value| value.is_dir())
+            .unwrap_or(false);
+        if !is_dir && !include_files {
+            continue;
+        }
+        let size_bytes = if is_dir {
+            None
+        } else {
+            Some(entry.metadata().map(|meta| meta.len()).unwrap_or(0))
+        };
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path_str = entry_path.to_string_lossy().to_string();
+        entries.push(RemoteListEntry {
+            name,
+            path: path_str,
+            is_dir,
+            size_bytes,
+        });
+    }
+    entries.sort_by_key(|entry| (!entry.is_dir, entry.name.to_lowercase()));
+    Ok(entries)
+}
+
+#[cfg(target_os = "windows")]
+fn list_windows_drives() -> Vec<RemoteListEntry> {
+    let mut entries = Vec::new();
+    for letter in b'A'..=b'Z' {
+        let drive = format!("{}:\\", letter as char);
+        let path = Path::new(&drive);
+        if !path.exists() {
+            continue;
+        }
+        entries.push(RemoteListEntry {
+            name: drive.clone(),
+            path: drive,
+            is_dir: true,
+            size_bytes: None,
+        });
+    }
+    entries
+}
+
+fn send_remote_event(sender: &mpsc::Sender<String>, value: serde_json::Value) {
+    let _ = sender.send(format!("{}\n", value));
+}
+
+fn send_remote_error(
+    sender: &mpsc::Sender<String>,
+    id: Option<&str>,
+    error: impl Into<RemoteError>,
+) {
+    let error = error.into();
+    send_remote_event(
+        sender,
+        serde_json::json!({
+          "event": "error",
+          "id": id,
+          "code": error.code(),
+          "message": error.message()
+        }),
+    );
+}
+
+fn request_id(request: &RemoteRequest) -> Option<&str> {
+    match request {
+        RemoteRequest::Ping { id }
+        | RemoteRequest::Heartbeat { id }
+        | RemoteRequest::Hello { id, .. }
+        | RemoteRequest::List { id, .. }
+        | RemoteRequest::Disk { id, .. }
+        | RemoteRequest::Read { id, .. }
+        | RemoteRequest::ReadChunked { id, .. }
+        | RemoteRequest::ReadHex { id, .. }
+        | RemoteRequest::Stat { id, .. }
+        | RemoteRequest::Hash { id, .. }
+        | RemoteRequest::Trash { id, .. }
+        | RemoteRequest::PrepareDelete { id, .. }
+        | RemoteRequest::Delete { id, .. }
+        | RemoteRequest::Move { id, .. }
+        | RemoteRequest::Copy { id, .. }
+        | RemoteRequest::Scan { id, .. }
+        | RemoteRequest::Duplicates { id, .. }
+        | RemoteRequest::Export { id, .. }
+        | RemoteRequest::ListPresets { id }
+        | RemoteRequest::RunPreset { id, .. }
+        | RemoteRequest::ValidateOptions { id, .. }
+        | RemoteRequest::Watch { id, .. }
+        | RemoteRequest::Unwatch { id }
+        | RemoteRequest::Cancel { id }
+        | RemoteRequest::Subscribe { id }
+        | RemoteRequest::ScanStatus { id, .. }
+        | RemoteRequest::Pause { id }
+        | RemoteRequest::Resume { id }
+        | RemoteRequest::Shutdown { id } => id.as_deref(),
+    }
+}
+
+fn required_scope(request: &RemoteRequest) -> Option<RemoteScope> {
+    match request {
+        RemoteRequest::Ping { .. }
+        | RemoteRequest::Heartbeat { .. }
+        | RemoteRequest::Hello { .. } => None,
+        RemoteRequest::List { .. } | RemoteRequest::Disk { .. } | RemoteRequest::Stat { .. } => {
+            Some(RemoteScope::List)
+        }
+        RemoteRequest::Read { .. }
+        | RemoteRequest::ReadChunked { .. }
+        | RemoteRequest::ReadHex { .. }
+        | RemoteRequest::Hash { .. } => Some(RemoteScope::Read),
+        RemoteRequest::Scan { .. }
+        | RemoteRequest::Duplicates { .. }
+        | RemoteRequest::Export { .. }
+        | RemoteRequest::ListPresets { .. }
+        | RemoteRequest::RunPreset { .. }
+        | RemoteRequest::ValidateOptions { .. }
+        | RemoteRequest::Watch { .. }
+        | RemoteRequest::Unwatch { .. }
+        | RemoteRequest::Cancel { .. }
+        | RemoteRequest::Subscribe { .. }
+        | RemoteRequest::ScanStatus { .. }
+        | RemoteRequest::Pause { .. }
+        | RemoteRequest::Resume { .. } => Some(RemoteScope::Scan),
+        RemoteRequest::Trash { .. }
+        | RemoteRequest::PrepareDelete { .. }
+        | RemoteRequest::Delete { .. }
+        | RemoteRequest::Move { .. }
+        | RemoteRequest::Copy { .. } => Some(RemoteScope::Delete),
+        RemoteRequest::Shutdown { .. } => Some(RemoteScope::Shutdown),
+    }
+}
+
+fn request_paths(request: &RemoteRequest) -> Vec<&str> {
+    match request {
+        RemoteRequest::List { path, .. } => path
+            .as_deref()
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| vec![value.trim()])
+            .unwrap_or_default(),
+        RemoteRequest::Disk { path, .. }
+        | RemoteRequest::Read { path, .. }
+        | RemoteRequest::ReadChunked { path, .. }
+        | RemoteRequest::ReadHex { path, .. }
+        | RemoteRequest::Stat { path, .. }
+        | RemoteRequest::Hash { path, .. }
+        | RemoteRequest::Scan { path, .. }
+        | RemoteRequest::Duplicates { path, .. }
+        | RemoteRequest::Export { path, .. }
+        | RemoteRequest::RunPreset { path, .. }
+        | RemoteRequest::Watch { path, .. } => vec![path.as_str()],
+        RemoteRequest::PrepareDelete { paths, .. } => paths.iter().map(String::as_str).collect(),
+        RemoteRequest::Move { from, to, .. } | RemoteRequest::Copy { from, to, .. } => {
+            vec![from.as_str(), to.as_str()]
+        }
+        RemoteRequest::Ping { .. }
+        | RemoteRequest::Heartbeat { .. }
+        | RemoteRequest::Hello { .. }
+        | RemoteRequest::Trash { .. }
+        | RemoteRequest::Delete { .. }
+        | RemoteRequest::ListPresets { .. }
+        | RemoteRequest::ValidateOptions { .. }
+        | RemoteRequest::Unwatch { .. }
+        | RemoteRequest::Cancel { .. }
+        | RemoteRequest::Subscribe { .. }
+        | RemoteRequest::ScanStatus { .. }
+        | RemoteRequest::Pause { .. }
+        | RemoteRequest::Resume { .. }
+        | RemoteRequest::Shutdown { .. } => Vec::new(),
+    }
+}
+
+fn emit_remote_status(
+    app: &tauri::AppHandle,
+    status: &str,
+    message: Option<String>,
+    address: Option<String>,
+) {
+    let payload = serde_json::json!({
+      "status": status,
+      "message": message,
+      "address": address
+    });
+    let _ = app.emit("remote-status", payload);
+}
+
+fn build_remote_payload(payload: JsonValue, token: Option<&str>) -> Result<String, String> {
+    eprintln!("[remote] build payload input={}", payload);
+    let mut value = payload;
+    if let Some(secret) = token {
+        match value {
+            JsonValue::Object(ref mut map) => {
+                map.entry("token".to_string())
+                    .or_insert_with(|| JsonValue::String(secret.to_string()));
+            }
+            _ => return Err("Payload must be an object".to_string()),
+        }
+    }
+    Ok(format!("{}\n", value))
+}
+
+pub(crate) fn stop_remote_server(handle: RemoteServerHandle) {
+    let _ = handle.shutdown.send(());
+    let _ = handle.join.join();
+}
+
+fn stop_remote_client(handle: RemoteClientHandle) {
+    let _ = handle.shutdown.send(());
+    drop(handle.sender);
+    let _ = handle.join.join();
+}
+
+fn parse_certificate_pin(pin: &str) -> Result<Vec<u8>, String> {
+    let digits: String = pin
+        .chars()
+        .filter(|c| *c != ':' && !c.is_whitespace())
+        .collect();
+    if digits.len() != 64 || !digits.is_ascii() {
+        return Err("Certificate pin must be a SHA-256 fingerprint (64 hex digits)".to_string());
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&digits[index..index + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| "Certificate pin must be hexadecimal".to_string())
+}
+
+fn tls_crypto_provider() -> Arc<rustls::crypto::CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn build_tls_client_config(options: &RemoteTlsOptions) -> Result<rustls::ClientConfig, String> {
+    let provider = tls_crypto_provider();
+    let builder = rustls::ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()
+        .map_err(|error| format!("Failed to configure TLS: {error}"))?;
+    if let Some(pin) = options.pin_sha256.as_deref() {
+        let verifier = PinnedCertVerifier {
+            fingerprint: parse_certificate_pin(pin)?,
+            algorithms: provider.signature_verification_algorithms,
+        };
+        return Ok(builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth());
+    }
+    let ca_path = options
+        .ca_cert
+        .as_deref()
+        .ok_or_else(|| "TLS needs a CA certificate or a certificate pin".to_string())?;
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(ca_path)
+        .map_err(|error| format!("Failed to read CA certificate {ca_path}: {error}"))?
+    {
+        let cert =
+            cert.map_err(|error| format!("Failed to read CA certificate {ca_path}: {error}"))?;
+        roots
+            .add(cert)
+            .map_err(|error| format!("Invalid CA certificate {ca_path}: {error}"))?;
+    }
+    Ok(builder.with_root_certificates(roots).with_no_client_auth())
+}
+
+fn open_remote_stream(
+    mut stream: TcpStream,
+    host: &str,
+    tls: Option<&RemoteTlsOptions>,
+) -> Result<RemoteStream, String> {
+    let configure = |error: std::io::Error| format!("Failed to configure TCP stream: {error}");
+    let options = match tls {
+        Some(options) => options,
+        None => {
+            stream
+                .set_read_timeout(Some(Duration::from_millis(200)))
+                .map_err(configure)?;
+            return Ok(RemoteStream::Plain(Arc::new(stream)));
+        }
+    };
+    let config = build_tls_client_config(options)?;
+    let server_name = ServerName::try_from(host.trim_matches(|c| c == '[' || c == ']').to_string())
+        .map_err(|_| format!("Invalid TLS server name: {host}"))?;
+    let mut connection = ClientConnection::new(Arc::new(config), server_name)
+        .map_err(|error| format!("Failed to start TLS: {error}"))?;
+    stream
+        .set_read_timeout(Some(TLS_HANDSHAKE_TIMEOUT))
+        .map_err(configure)?;
+    while connection.is_handshaking() {
+        connection
+            .complete_io(&mut stream)
+            .map_err(|error| format!("TLS handshake failed: {error}"))?;
+    }
+    stream
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .map_err(configure)?;
+    Ok(RemoteStream::Tls(Arc::new(TlsStream::new(
+        connection, stream,
+    ))))
+}
+
+fn spawn_remote_client(
+    stream: RemoteStream,
+    token: Option<String>,
+    address: String,
+    sink: RemoteClientSink,
+) -> Result<RemoteClientHandle, String> {
+    let (sender, receiver) = mpsc::channel::<String>();
+    let (shutdown_tx, shutdown_rx) = mpsc::channel::<()>();
+    let writer_stream = stream.clone();
+    thread::spawn(move || write_remote_lines(writer_stream, receiver, Arc::default()));
+    // Ask the server to gzip large events; servers without `hello` reply with an
+    // error event and keep sending plain lines, which the reader handles too.
+    let hello = build_remote_payload(
+        serde_json::json!({
+          "action": "hello",
+          "clientVersion": env!("CARGO_PKG_VERSION"),
+          "protocolVersion": PROTOCOL_VERSION,
+          "compress": true,
+          "heartbeat": true
+        }),
+        token.as_deref(),
+    )?;
+    let _ = sender.send(hello);
+    let address_clone = address.clone();
+    let heartbeat_sender = sender.clone();
+    let heartbeat_reply = build_remote_payload(
+        serde_json::json!({ "action": "heartbeat" }),
+        token.as_deref(),
+    )?;
+    let join = thread::spawn(move || {
+        let mut reader = BufReader::new(stream.clone());
+        let mut last_seen = Instant::now();
+        // Unknown until the first heartbeat, so servers that never send one are
+        // not timed out.
+        let mut idle_timeout: Option<Duration> = None;
+        let mut timed_out = false;
+        loop {
+            if shutdown_rx.try_recv().is_ok() {
+                break;
+            }
+            match read_secure_line(&mut reader, MAX_LINE_LENGTH) {
+                Ok(None) => break,
+                Ok(Some(line)) => {
+                    last_seen = Instant::now();
+                    let Some(line) = decode_remote_line(&line) else {
+                        eprintln!("[remote] dropping undecodable compressed line");
+                        continue;
+                    };
+                    let trimmed = line.trim();
+                    if trimmed.is_empty() {
+                        continue;
+                    }
+                    if let Ok(mut value) = serde_json::from_str::<JsonValue>(trimmed) {
+                        if value.get("event").and_then(JsonValue::as_str) == Some("heartbeat") {
+                            idle_timeout = value
+                                .pointer("/data/intervalMs")
+                                .and_then(JsonValue::as_u64)
+                                .filter(|interval| *interval > 0)
+                                .map(|interval| {
+                                    Duration::from_millis(interval) * HEARTBEAT_MISSED_LIMIT
+                                });
+                            let _ = heartbeat_sender.send(heartbeat_reply.clone());
+                            continue;
+                        }
+                        if let JsonValue::Object(ref mut map) = value {
+                            map.insert(
+                                "_address".to_string(),
+                                JsonValue::String(address_clone.clone()),
+                            );
+                        }
+                        sink(RemoteClientEvent::Message(value));
+                    }
+                }
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    if idle_timeout.is_some_and(|timeout| last_seen.elapsed() >= timeout) {
+                        eprintln!("[remote] no heartbeat from {address_clone}, disconnecting");
+                        stream.shutdown();
+                        timed_out = true;
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+        sink(if timed_out {
+            RemoteClientEvent::TimedOut
+        } else {
+            RemoteClientEvent::Disconnected
+        });
+    });
+    Ok(RemoteClientHandle {
+        sender,
+        shutdown: shutdown_tx,
+        join,
+        token,
+        address,
+    })
+}
+
+#[tauri::command]
+pub(crate) fn remote_connect(
+    app: tauri::AppHandle,
+    state: tauri::State<RemoteClientState>,
+    payload: RemoteConnectPayload,
+) -> Result<(), String> {
+    let address = format!("{}:{}", payload.host.trim(), payload.port);
+    eprintln!("[remote] connect attempt {}", address);
+    emit_remote_status(&app, "connecting", None, Some(address.clone()));
+    let tls =
+        (payload.tls || payload.ca_cert.is_some() || payload.pin_sha256.is_some()).then(|| {
+            RemoteTlsOptions {
+                ca_cert: payload.ca_cert.clone(),
+                pin_sha256: payload.pin_sha256.clone(),
+            }
+        });
+    let stream = TcpStream::connect(&address)
+        .map_err(|error| format!("Failed to connect: {error}"))
+        .and_then(|stream| open_remote_stream(stream, payload.host.trim(), tls.as_ref()))
+        .map_err(|error| {
+            emit_remote_status(&app, "error", Some(error.clone()), Some(address.clone()));
+            format!("{error} ({address})")
+        })?;
+    eprintln!("[remote] connect success {}", address);
+    let mut state_guard = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock remote state".to_string())?;
+    if let Some(existing) = state_guard.take() {
+        stop_remote_client(existing);
+    }
+    let sink_app = app.clone();
+    let sink_address = address.clone();
+    let sink: RemoteClientSink = Arc::new(move |event| match event {
+        RemoteClientEvent::Message(value) => {
+            let _ = sink_app.emit("remote-event", value);
+        }
+        RemoteClientEvent::TimedOut => {
+            emit_remote_status(
+                &sink_app,
+                "timed-out",
+                Some("The server stopped responding".to_string()),
+                Some(sink_address.clone()),
+            );
+        }
+        RemoteClientEvent::Disconnected => {
+            emit_remote_status(&sink_app, "disconnected", None, Some(sink_address.clone()));
+        }
+    });
+    let handle = spawn_remote_client(stream, payload.token, address.clone(), sink)?;
+    *state_guard = Some(handle);
+    emit_remote_status(&app, "connected", None, Some(address));
+    Ok(())
+}
+
+fn is_interim_remote_event(name: &str) -> bool {
+    name == "hello"
+        || name == "list-entry"
+        || name.ends_with("-progress")
+        || name.ends_with("-started")
+        || name.ends_with("-paused")
+        || name.ends_with("-resumed")
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteCliResult {
+    status: &'static str,
+    events: Vec<JsonValue>,
+}
+
+fn parse_remote_cli_requests(args: &[String]) -> Result<Vec<JsonValue>, String> {
+    let lines: Vec<String> = match get_arg_value(args, "--remote-batch") {
+        Some(source) => {
+            let mut contents = String::new();
+            if source == "-" {
+                std::io::stdin()
+                    .read_to_string(&mut contents)
+                    .map_err(|error| format!("Failed to read requests from stdin: {error}"))?;
+            } else {
+                contents = fs::read_to_string(&source)
+                    .map_err(|error| format!("Failed to read {source}: {error}"))?;
+            }
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect()
+        }
+        None => vec![get_arg_value(args, "--remote-request")
+            .unwrap_or_else(|| "{\"action\":\"ping\"}".to_string())],
+    };
+    let mut requests = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        let mut request: JsonValue = match serde_json::from_str(line) {
+            Ok(value @ JsonValue::Object(_)) => value,
+            _ => return Err(format!("Request {} is not a JSON object", index + 1)),
+        };
+        if request.get("id").filter(|value| !value.is_null()).is_none() {
+            request["id"] = JsonValue::String(format!("cli-{}", index + 1));
+        }
+        requests.push(request);
+    }
+    Ok(requests)
+}
+
+fn run_remote_session(
+    address: &str,
+    token: Option<&str>,
+    tls: Option<&RemoteTlsOptions>,
+    requests: &[JsonValue],
+    timeout: Duration,
+    echo: bool,
+) -> Result<Vec<(String, RemoteCliResult)>, String> {
+    let socket_addr = address
+        .to_socket_addrs()
+        .map_err(|error| format!("Failed to resolve {address}: {error}"))?
+        .next()
+        .ok_or_else(|| format!("Failed to resolve {address}"))?;
+    let stream = TcpStream::connect_timeout(&socket_addr, timeout)
+        .map_err(|error| format!("Failed to connect to {address}: {error}"))?;
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+    let stream = open_remote_stream(stream, host, tls)?;
+    let (event_tx, event_rx) = mpsc::channel::<Option<JsonValue>>();
+    let sink: RemoteClientSink = Arc::new(move |event| {
+        let _ = event_tx.send(match event {
+            RemoteClientEvent::Message(mut value) => {
+                if let JsonValue::Object(ref mut map) = value {
+                    map.remove("_address");
+                }
+                Some(value)
+            }
+            RemoteClientEvent::TimedOut | RemoteClientEvent::Disconnected => None,
+        });
+    });
+    let handle = spawn_remote_client(stream, token.map(str::to_string), address.to_string(), sink)?;
+    let mut results = Vec::with_capacity(requests.len());
+    let mut connected = true;
+    for request in requests {
+        let id = request["id"].clone();
+        let key = id
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| id.to_string());
+        let mut result = RemoteCliResult {
+            status: "disconnected",
+            events: Vec::new(),
+        };
+        let sent = connected
+            && build_remote_payload(request.clone(), token)
+                .ok()
+                .is_some_and(|line| handle.sender.send(line).is_ok());
+        if !sent {
+            connected = false;
+            results.push((key, result));
+            continue;
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match event_rx.recv_timeout(remaining) {
+                Ok(Some(value)) => {
+                    if echo {
+                        println!("{value}");
+                    }
+                    if value.get("id") != Some(&id) {
+                        continue;
+                    }
+                    let name = value
+                        .get("event")
+                        .and_then(JsonValue::as_str)
+                        .unwrap_or("")
+                        .to_string();
+                    result.events.push(value);
+                    if !is_interim_remote_event(&name) {
+                        let failed = name == "error" || name.ends_with("-error");
+                        result.status = if failed { "error" } else { "ok" };
+                        break;
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    result.status = "timeout";
+                    break;
+                }
+                Ok(None) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    connected = false;
+                    break;
+                }
+            }
+        }
+        results.push((key, result));
+    }
+    stop_remote_client(handle);
+    Ok(results)
+}
+
+pub(crate) fn run_remote_cli(args: &[String], addresses: &str) -> i32 {
+    let requests = match parse_remote_cli_requests(args) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("{error}");
+            return 2;
+        }
+    };
+    let token = get_arg_value(args, "--remote-token")
+        .or_else(|| env_string("VOXARA_LOCAL_TOKEN"))
+        .or_else(|| env_string("DRAGABYTE_TCP_TOKEN"));
+    let tls_options = RemoteTlsOptions {
+        ca_cert: get_arg_value(args, "--remote-ca"),
+        pin_sha256: get_arg_value(args, "--remote-pin"),
+    };
+    let tls = (has_flag(args, "--remote-tls")
+        || tls_options.ca_cert.is_some()
+        || tls_options.pin_sha256.is_some())
+    .then_some(&tls_options);
+    let timeout = Duration::from_secs(
+        get_arg_value(args, "--remote-timeout")
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_REMOTE_CLI_TIMEOUT_SECS),
+    );
+    let hosts: Vec<&str> = addresses
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .collect();
+    if hosts.len() == 1 && get_arg_value(args, "--remote-batch").is_none() {
+        return match run_remote_session(hosts[0], token.as_deref(), tls, &requests, timeout, true) {
+            Ok(results) if results.iter().all(|(_, result)| result.status == "ok") => 0,
+            Ok(_) => 1,
+            Err(error) => {
+                eprintln!("{error}");
+                1
+            }
+        };
+    }
+    let mut report = serde_json::Map::new();
+    let mut failed = false;
+    for host in hosts {
+        let entry = match run_remote_session(host, token.as_deref(), tls, &requests, timeout, false)
+        {
+            Ok(results) => {
+                let ok = results.iter().all(|(_, result)| result.status == "ok");
+                failed |= !ok;
+                let by_id: serde_json::Map<String, JsonValue> = results
+                    .into_iter()
+                    .map(|(id, result)| (id, serde_json::json!(result)))
+                    .collect();
+                serde_json::json!({ "ok": ok, "results": by_id })
+            }
+            Err(error) => {
+                failed = true;
+                serde_json::json!({ "ok": false, "error": error })
+            }
+        };
+        report.insert(host.to_string(), entry);
+    }
+    match serde_json::to_string_pretty(&JsonValue::Object(report)) {
+        Ok(text) => println!("{text}"),
+        Err(error) => eprintln!("Failed to print results: {error}"),
+    }
+    i32::from(failed)
+}
+
+#[tauri::command]
+pub(crate) fn remote_disconnect(
+    app: tauri::AppHandle,
+    state: tauri::State<RemoteClientState>,
+) -> Result<(), String> {
+    let mut state_guard = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock remote state".to_string())?;
+    if let Some(handle) = state_guard.take() {
+        let address = handle.address.clone();
+        stop_remote_client(handle);
+        emit_remote_status(&app, "disconnected", None, Some(address));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn remote_send(
+    state: tauri::State<RemoteClientState>,
+    payload: RemoteSendPayload,
+) -> Result<(), String> {
+    eprintln!("[remote] send from ui payload={}", payload.payload);
+    let state_guard = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock remote state".to_string())?;
+    let handle = state_guard
+        .as_ref()
+        .ok_or_else(|| "Remote is not connected".to_string())?;
+    let safe_payload = match payload.payload {
+        JsonValue::Object(_) => payload.payload,
+        _ => JsonValue::Object(serde_json::Map::new()),
+    };
+    let line = build_remote_payload(safe_payload, handle.token.as_deref())?;
+    handle
+        .sender
+        .send(line)
+        .map_err(|_| "Failed to send remote payload".to_string())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteStatusSnapshot {
+    connected: bool,
+    address: Option<String>,
+}
+
+#[tauri::command]
+pub(crate) fn remote_status(
+    state: tauri::State<RemoteClientState>,
+) -> Result<RemoteStatusSnapshot, String> {
+    let guard = state
+        .0
+        .lock()
+        .map_err(|_| "Failed to lock remote state".to_string())?;
+    let address = guard.as_ref().map(|handle| handle.address.clone());
+    Ok(RemoteStatusSnapshot {
+        connected: address.is_some(),
+        address,
+    })
+}
+
+fn emit_update_event(hub: Option<&RemoteHub>, event: &str, data: JsonValue) {
+    if let Some(hub) = hub {
+        hub.broadcast_authenticated(format!(
+            "{}\n",
+            serde_json::json!({ "event": event, "data": data })
+        ));
+    }
+}
+
+pub(crate) fn spawn_headless_updater(
+    app: tauri::AppHandle,
+    enabled: bool,
+    hub: Option<Arc<RemoteHub>>,
+) {
+    if !enabled {
+        eprintln!("[updater] headless updates disabled");
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        let updater = match app.updater() {
+            Ok(value) => value,
+            Err(error) => {
+                eprintln!("[updater] init failed: {error}");
+                return;
+            }
+        };
+        match updater.check().await {
+            Ok(Some(update)) => {
+                eprintln!(
+                    "[updater] update {} -> {}",
+                    update.current_version, update.version
+                );
+                emit_update_event(
+                    hub.as_deref(),
+                    "update-available",
+                    serde_json::json!({
+                      "currentVersion": update.current_version,
+                      "version": update.version
+                    }),
+                );
+                let mut downloaded: u64 = 0;
+                let mut last_percent: Option<u64> = None;
+                let on_chunk = |chunk_length: usize, content_length: Option<u64>| {
+                    downloaded += chunk_length as u64;
+                    // Only report whole-percent steps so large downloads don't flood clients.
+                    let percent = content_length
+                        .filter(|total| *total > 0)
+                        .map(|total| downloaded.saturating_mul(100) / total);
+                    if percent.is_some() && percent == last_percent {
+                        return;
+                    }
+                    last_percent = percent;
+                    emit_update_event(
+                        hub.as_deref(),
+                        "update-progress",
+                        serde_json::json!({
+                          "downloadedBytes": downloaded,
+                          "totalBytes": content_length,
+                          "percent": percent
+                        }),
+                    );
+                };
+                match update.download_and_install(on_chunk, || {}).await {
+                    Ok(()) => {
+                        eprintln!("[updater] update installed");
+                        emit_update_event(
+                            hub.as_deref(),
+                            "update-installed",
+                            serde_json::json!({ "version": update.version }),
+                        );
+                    }
+                    Err(error) => {
+                        eprintln!("[updater] install failed: {error}");
+                        emit_update_event(
+                            hub.as_deref(),
+                            "update-failed",
+                            serde_json::json!({ "message": error.to_string() }),
+                        );
+                    }
+                }
+            }
+            Ok(None) => {
+                eprintln!("[updater] no updates available");
+            }
+            Err(error) => {
+                eprintln!("[updater] check failed: {error}");
+            }
+        }
+    });
+}
+
+fn read_secure_line<R: BufRead>(reader: &mut R, max_len: u64) -> std::io::Result<Option<String>> {
+    let mut line = Vec::new();
+    let mut total_read = 0;
+    loop {
+        let available = match reader.fill_buf() {
+            Ok(value) => value,
+            // Bytes already taken would be lost with the error, so wait for the
+            // rest of a line that has started arriving.
+            Err(error)
+                if !line.is_empty()
+                    && matches!(
+                        error.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+            {
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        let length = available.len();
+        if length == 0 {
+            if line.is_empty() {
+                return Ok(None);
+            }
+            break;
+        }
+        let newline_pos = available.iter().position(|&b| b == b'\n');
+
+        let bytes_to_take = if let Some(pos) = newline_pos {
+            pos + 1
+        } else {
+            length
+        };
+
+        if total_read + bytes_to_take as u64 > max_len {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Line too long",
+            ));
+        }
+
+        line.extend_from_slice(&available[..bytes_to_take]);
+        reader.consume(bytes_to_take);
+        total_read += bytes_to_take as u64;
+
+        if newline_pos.is_some() {
+            break;
+        }
+    }
+    String::from_utf8(line)
+        .map(Some)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestServer {
+        handle: Option<RemoteServerHandle>,
+        _dir: tempfile::TempDir,
+    }
+
+    impl TestServer {
+        fn start(config: TcpConfig) -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let handle =
+                start_remote_server(config, true, &dir.path().join("dragabyte.settings.json"))
+                    .unwrap();
+            Self {
+                handle: Some(handle),
+                _dir: dir,
+            }
+        }
+
+        fn addr(&self) -> SocketAddr {
+            self.handle.as_ref().unwrap().local_addr
+        }
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            if let Some(handle) = self.handle.take() {
+                stop_remote_server(handle);
+            }
+        }
+    }
+
+    fn test_config() -> TcpConfig {
+        TcpConfig {
+            bind_addr: "127.0.0.1:0".parse().unwrap(),
+            token: None,
+            scoped_tokens: Vec::new(),
+            allowed_roots: Vec::new(),
+            tls_cert: None,
+            tls_key: None,
+            heartbeat_interval: None,
+            hash_max_bytes: None,
+            allow_shutdown: false,
+        }
+    }
+
+    struct TestClient {
+        stream: RemoteStream,
+        reader: BufReader<RemoteStream>,
+        compressed: usize,
+    }
+
+    impl TestClient {
+        fn connect(addr: SocketAddr, tls: Option<&RemoteTlsOptions>) -> Self {
+            let socket = TcpStream::connect(addr).unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_millis(200)))
+                .unwrap();
+            let stream = open_remote_stream(socket, "localhost", tls).unwrap();
+            Self {
+                reader: BufReader::new(stream.clone()),
+                stream,
+                compressed: 0,
+            }
+        }
+
+        fn send(&mut self, request: JsonValue) {
+            self.stream
+                .write_all(format!("{request}\n").as_bytes())
+                .unwrap();
+        }
+
+        fn next_event(&mut self) -> JsonValue {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                assert!(Instant::now() < deadline, "no event within 10s");
+                match read_secure_line(&mut self.reader, MAX_LINE_LENGTH) {
+                    Ok(Some(line)) => {
+                        if line.starts_with("gz:") {
+                            self.compressed += 1;
+                        }
+                        let line = decode_remote_line(&line).unwrap();
+                        return serde_json::from_str(line.trim()).unwrap();
+                    }
+                    Ok(None) => panic!("server closed the connection"),
+                    Err(error)
+                        if matches!(
+                            error.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) => {}
+                    Err(error) => panic!("read failed: {error}"),
+                }
+            }
+        }
+
+        fn wait_for(&mut self, name: &str) -> JsonValue {
+            loop {
+                let event = self.next_event();
+                if event["event"] == name {
+                    return event;
+                }
+            }
+        }
+
+        fn closed_within(&mut self, timeout: Duration) -> bool {
+            let deadline = Instant::now() + timeout;
+            while Instant::now() < deadline {
+                match read_secure_line(&mut self.reader, MAX_LINE_LENGTH) {
+                    Ok(Some(_)) => {}
+                    Ok(None) => return true,
+                    Err(error)
+                        if matches!(
+                            error.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) => {}
+                    Err(_) => return true,
+                }
+            }
+            false
+        }
+    }
+
+    fn tls_setup(dir: &Path) -> (TcpConfig, RemoteTlsOptions) {
+        let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let cert_path = dir.join("cert.pem");
+        let key_path = dir.join("key.pem");
+        fs::write(&cert_path, certified.cert.pem()).unwrap();
+        fs::write(&key_path, certified.key_pair.serialize_pem()).unwrap();
+        let pin: String = Sha256::digest(certified.cert.der())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let config = TcpConfig {
+            tls_cert: Some(get_path_string(&cert_path)),
+            tls_key: Some(get_path_string(&key_path)),
+            ..test_config()
+        };
+        let options = RemoteTlsOptions {
+            ca_cert: None,
+            pin_sha256: Some(pin),
+        };
+        (config, options)
+    }
+
+    struct StallingReader {
+        chunks: Vec<&'static [u8]>,
+        stalled: bool,
+    }
+
+    impl Read for StallingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.stalled = !self.stalled;
+            if self.stalled {
+                return Err(std::io::ErrorKind::WouldBlock.into());
+            }
+            if self.chunks.is_empty() {
+                return Ok(0);
+            }
+            let chunk = self.chunks.remove(0);
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn a_timeout_mid_line_keeps_what_was_read() {
+        let mut reader = BufReader::new(StallingReader {
+            chunks: vec![b"{\"event\":", b"\"pong\"}\n{}", b"\n"],
+            stalled: false,
+        });
+        let mut lines = Vec::new();
+        loop {
+            match read_secure_line(&mut reader, MAX_LINE_LENGTH) {
+                Ok(Some(line)) => lines.push(line),
+                Ok(None) => break,
+                Err(error) => assert_eq!(error.kind(), std::io::ErrorKind::WouldBlock),
+            }
+        }
+        assert_eq!(lines, ["{\"event\":\"pong\"}\n", "{}\n"]);
+    }
+
+    fn scan_tree(count: usize) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for index in 0..count {
+            let folder = dir.path().join(format!("folder-{}", index % 10));
+            fs::create_dir_all(&folder).unwrap();
+            fs::write(folder.join(format!("file-{index}.txt")), "x").unwrap();
+        }
+        dir
+    }
+
+    fn scan_request(id: &str, path: &Path, options: JsonValue) -> JsonValue {
+        serde_json::json!({
+            "action": "scan",
+            "id": id,
+            "path": get_path_string(path),
+            "options": options
+        })
+    }
+
+    #[test]
+    fn requests_need_a_valid_token() {
+        let server = TestServer::start(TcpConfig {
+            token: Some("secret".to_string()),
+            ..test_config()
+        });
+        let mut client = TestClient::connect(server.addr(), None);
+
+        let start = Instant::now();
+        client.send(serde_json::json!({ "action": "ping", "id": "1", "token": "guess" }));
+        let event = client.next_event();
+        assert_eq!(event["event"], "error");
+        assert_eq!(event["code"], "Unauthorized");
+        assert!(start.elapsed() >= Duration::from_secs(2));
+
+        client.send(serde_json::json!({ "action": "ping", "id": "2" }));
+        assert_eq!(client.next_event()["code"], "Unauthorized");
+
+        client.send(serde_json::json!({ "action": "ping", "id": "3", "token": "secret" }));
+        let event = client.next_event();
+        assert_eq!(event["event"], "pong");
+        assert_eq!(event["id"], "3");
+    }
+
+    #[test]
+    fn scans_round_trip_with_and_without_compression() {
+        let tree = scan_tree(200);
+        let server = TestServer::start(test_config());
+        for compress in [false, true] {
+            let mut client = TestClient::connect(server.addr(), None);
+            client.send(serde_json::json!({ "action": "hello", "id": "h", "compress": compress }));
+            assert_eq!(client.wait_for("hello")["data"]["compress"], compress);
+
+            let id = format!("scan-{compress}");
+            client.send(scan_request(
+                &id,
+                tree.path(),
+                serde_json::json!(ScanOptions::default()),
+            ));
+            assert_eq!(client.wait_for("scan-started")["id"], id);
+            let complete = client.wait_for("scan-complete");
+            assert_eq!(complete["id"], id);
+            assert_eq!(complete["data"]["fileCount"], 200);
+            assert_eq!(complete["data"]["totalBytes"], 200);
+            assert_eq!(client.compressed > 0, compress);
+        }
+    }
+
+    #[test]
+    fn cancel_stops_a_running_scan_with_and_without_compression() {
+        let tree = scan_tree(2000);
+        let server = TestServer::start(test_config());
+        let mut options = serde_json::json!(ScanOptions::default());
+        options["throttleLevel"] = "high".into();
+        for compress in [false, true] {
+            let mut client = TestClient::connect(server.addr(), None);
+            client.send(serde_json::json!({ "action": "hello", "id": "h", "compress": compress }));
+            client.wait_for("hello");
+
+            // Paused right after it starts, so it can't finish before the cancel.
+            let id = format!("scan-{compress}");
+            client.send(scan_request(&id, tree.path(), options.clone()));
+            client.send(serde_json::json!({ "action": "pause", "id": id }));
+            client.wait_for("scan-paused");
+            client.send(serde_json::json!({ "action": "cancel", "id": id }));
+            assert_eq!(client.wait_for("cancel-requested")["id"], id);
+            let cancelled = client.wait_for("scan-cancelled");
+            assert_eq!(cancelled["id"], id);
+            assert!(cancelled["data"]["fileCount"].as_u64().unwrap() < 2000);
+        }
+    }
+
+    #[test]
+    fn tls_replies_are_not_held_up_by_the_read_timeout() {
+        let certs = tempfile::tempdir().unwrap();
+        let (config, options) = tls_setup(certs.path());
+        let server = TestServer::start(config);
+        let mut client = TestClient::connect(server.addr(), Some(&options));
+
+        let start = Instant::now();
+        for index in 0..20 {
+            client.send(serde_json::json!({ "action": "ping", "id": index.to_string() }));
+            let event = client.next_event();
+            assert_eq!(event["event"], "pong");
+            assert_eq!(event["id"], index.to_string());
+        }
+        // Each reply used to wait for the server's 200ms read timeout to release
+        // the connection lock.
+        assert!(
+            start.elapsed() < Duration::from_secs(1),
+            "20 pings took {:?}",
+            start.elapsed()
+        );
+    }
+
+    #[test]
+    fn only_clients_that_ask_for_heartbeats_are_dropped_when_idle() {
+        let server = TestServer::start(TcpConfig {
+            heartbeat_interval: Some(Duration::from_millis(100)),
+            ..test_config()
+        });
+
+        let mut quiet = TestClient::connect(server.addr(), None);
+        quiet.send(serde_json::json!({ "action": "hello", "id": "h" }));
+        assert_eq!(quiet.wait_for("hello")["data"]["heartbeat"], false);
+        thread::sleep(Duration::from_millis(600));
+        quiet.send(serde_json::json!({ "action": "ping", "id": "p" }));
+        assert_eq!(quiet.wait_for("pong")["id"], "p");
+
+        let mut opted_in = TestClient::connect(server.addr(), None);
+        opted_in.send(serde_json::json!({ "action": "hello", "id": "h", "heartbeat": true }));
+        assert_eq!(opted_in.wait_for("hello")["data"]["heartbeat"], true);
+        assert!(opted_in.closed_within(Duration::from_secs(5)));
+    }
+}
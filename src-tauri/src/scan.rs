@@ -0,0 +1,4713 @@
+use super::*;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use infer::MatcherType;
+use jwalk::{Parallelism, WalkDir};
+use notify::{RecursiveMode, Watcher};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+use tauri::Manager;
+use unicode_normalization::UnicodeNormalization;
+
+const DEFAULT_PROGRESS_BATCH_SIZE: u64 = 256;
+const MAX_EXTENSION_STATS: usize = 50;
+const MAX_LARGEST_PER_CATEGORY: usize = 100;
+const DEFAULT_LARGEST_FILES_LIMIT: usize = 100;
+const MAX_ZERO_BYTE_SAMPLE: usize = 100;
+const MAX_LARGEST_FILES_LIMIT: usize = 1000;
+const MAX_LARGEST_DIRS: usize = 100;
+const MIN_EMIT_INTERVAL_MS: u64 = 50;
+const MIN_EMIT_EVERY: u64 = 100;
+const MAX_OVERSIZED_FILES: usize = 1000;
+pub(crate) const DEFAULT_COMPRESSION_SAMPLES: usize = 200;
+pub(crate) const MAX_COMPRESSION_SAMPLES: usize = 2000;
+const COMPRESSION_SAMPLE_BYTES: u64 = 1024 * 1024; // 1MB
+pub(crate) const DEFAULT_ARCHIVE_MIN_SIZE_BYTES: u64 = 100 * 1024 * 1024; // 100MB
+pub(crate) const DEFAULT_ARCHIVE_MIN_AGE_DAYS: u64 = 365;
+pub(crate) const DEFAULT_ARCHIVE_CANDIDATES_LIMIT: usize = 50;
+pub(crate) const MAX_ARCHIVE_CANDIDATES_LIMIT: usize = 1000;
+const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+pub(crate) const DEFAULT_RECENT_FILES_LIMIT: usize = 50;
+pub(crate) const MAX_RECENT_FILES_LIMIT: usize = 1000;
+const MAX_NAME_GROUP_FILES: usize = 100;
+const MAX_NAME_GROUPS: usize = 1000;
+const FILE_CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "images",
+        &[
+            "png", "jpg", "jpeg", "gif", "webp", "bmp", "tiff", "svg", "heic",
+        ],
+    ),
+    (
+        "videos",
+        &[
+            "mp4", "mov", "mkv", "avi", "webm", "wmv", "flv", "mpeg", "mpg", "m4v",
+        ],
+    ),
+    (
+        "audio",
+        &["mp3", "wav", "flac", "aac", "ogg", "m4a", "opus"],
+    ),
+    (
+        "documents",
+        &[
+            "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "md", "rtf",
+        ],
+    ),
+    ("archives", &["zip", "rar", "7z", "tar", "gz", "bz2", "xz"]),
+];
+
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "c", "h", "cpp", "hpp", "cs", "go", "java", "kt", "swift", "py", "rb", "php", "js",
+    "jsx", "ts", "tsx", "css", "scss", "html", "json", "yaml", "yml", "toml", "xml", "sql", "sh",
+    "ps1", "lua",
+];
+const KIND_SNIFF_BYTES: usize = 8192;
+
+pub(crate) const SCAN_RESULT_FORMAT_VERSION: u32 = 1;
+pub(crate) const SCAN_RESULT_EXTENSION: &str = "voxara.json";
+
+pub(crate) const DEFAULT_WATCH_INTERVAL_MS: u64 = 2000;
+pub(crate) const MIN_WATCH_INTERVAL_MS: u64 = 250;
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+const WATCH_MAX_DELAY: Duration = Duration::from_secs(3);
+
+const FINISHED_SCAN_TTL: Duration = Duration::from_secs(10 * 60);
+
+const HELD_SUMMARY_TTL: Duration = Duration::from_secs(30 * 60);
+const HELD_SUMMARY_MAX_FILES: u64 = 5_000_000;
+pub(crate) const MAX_FILE_PAGE_SIZE: usize = 1000;
+
+pub(crate) struct ScanCancellation(pub(crate) Mutex<HashMap<String, WindowScan>>);
+
+pub(crate) struct WindowScan {
+    pub(crate) control: ScanControl,
+    pub(crate) id: Option<String>,
+}
+
+impl ScanCancellation {
+    pub(crate) fn claim(
+        &self,
+        label: &str,
+        control: &ScanControl,
+        id: Option<String>,
+    ) -> Result<(), String> {
+        let mut scans = self
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock scan state".to_string())?;
+        let scan = WindowScan {
+            control: control.clone(),
+            id,
+        };
+        if let Some(existing) = scans.insert(label.to_string(), scan) {
+            existing.control.cancel.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn cancel(&self, label: &str) -> Result<(), String> {
+        let scans = self
+            .0
+            .lock()
+            .map_err(|_| "Failed to lock scan state".to_string())?;
+        if let Some(scan) = scans.get(label) {
+            scan.control.cancel.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn release(&self, label: &str, control: &ScanControl) {
+        if let Ok(mut scans) = self.0.lock() {
+            if scans
+                .get(label)
+                .is_some_and(|current| current.control.same_scan(control))
+            {
+                scans.remove(label);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ScanStatus {
+    Running,
+    Completed,
+    NotFound,
+}
+
+#[derive(Default)]
+pub(crate) struct FinishedScans(Mutex<HashMap<String, Instant>>);
+
+impl FinishedScans {
+    pub(crate) fn record(&self, id: &str) {
+        if let Ok(mut finished) = self.0.lock() {
+            finished.retain(|_, at| at.elapsed() < FINISHED_SCAN_TTL);
+            finished.insert(id.to_string(), Instant::now());
+        }
+    }
+
+    pub(crate) fn status(&self, id: &str, running: bool) -> ScanStatus {
+        if running {
+            return ScanStatus::Running;
+        }
+        let finished = self
+            .0
+            .lock()
+            .map(|finished| {
+                finished
+                    .get(id)
+                    .is_some_and(|at| at.elapsed() < FINISHED_SCAN_TTL)
+            })
+            .unwrap_or(false);
+        if finished {
+            ScanStatus::Completed
+        } else {
+            ScanStatus::NotFound
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum FileSortKey {
+    #[default]
+    SizeDesc,
+    SizeAsc,
+    NameAsc,
+    NameDesc,
+    PathAsc,
+    ModifiedDesc,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FilePage {
+    id: String,
+    total: usize,
+    offset: usize,
+    files: Vec<ScanFile>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FolderChange {
+    path: String,
+    old_bytes: u64,
+    new_bytes: u64,
+    byte_delta: i64,
+    file_delta: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DiffEntry {
+    path: String,
+    size_bytes: u64,
+    is_dir: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScanDiff {
+    byte_delta: i64,
+    file_delta: i64,
+    changes: Vec<FolderChange>,
+    added: Vec<DiffEntry>,
+    removed: Vec<DiffEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TransferFit {
+    destination: String,
+    free_bytes: u64,
+    required_bytes: u64,
+    fits: bool,
+    oversized_files: Vec<ScanFile>,
+    oversized_count: u64,
+}
+
+pub(crate) struct HeldSummaries {
+    capacity: usize,
+    entries: Mutex<Vec<HeldSummary>>,
+}
+
+struct HeldSummary {
+    id: String,
+    summary: ScanSummary,
+    files: Option<(FileSortKey, Vec<ScanFile>)>,
+    used_at: Instant,
+}
+
+impl HeldSummaries {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn hold(&self, id: &str, summary: ScanSummary) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Ok(mut held) = self.entries.lock() {
+            held.retain(|entry| entry.id != id && entry.used_at.elapsed() < HELD_SUMMARY_TTL);
+            held.push(HeldSummary {
+                id: id.to_string(),
+                summary,
+                files: None,
+                used_at: Instant::now(),
+            });
+            // The newest summary stays even if it alone is over the file budget.
+            while held.len() > 1
+                && (held.len() > self.capacity
+                    || held
+                        .iter()
+                        .map(|entry| entry.summary.file_count)
+                        .sum::<u64>()
+                        > HELD_SUMMARY_MAX_FILES)
+            {
+                held.remove(0);
+            }
+        }
+    }
+
+    pub(crate) fn summary(&self, id: &str) -> Result<ScanSummary, String> {
+        self.with_summary(id, ScanSummary::clone)
+    }
+
+    pub(crate) fn with_summary<T>(
+        &self,
+        id: &str,
+        read: impl FnOnce(&ScanSummary) -> T,
+    ) -> Result<T, String> {
+        let mut held = self.lock_fresh()?;
+        let index = find_held(&held, id)?;
+        let mut entry = held.remove(index);
+        entry.used_at = Instant::now();
+        let value = read(&entry.summary);
+        held.push(entry);
+        Ok(value)
+    }
+
+    fn lock_fresh(&self) -> Result<std::sync::MutexGuard<'_, Vec<HeldSummary>>, String> {
+        let mut held = self
+            .entries
+            .lock()
+            .map_err(|_| "Failed to lock held summaries".to_string())?;
+        held.retain(|entry| entry.used_at.elapsed() < HELD_SUMMARY_TTL);
+        Ok(held)
+    }
+
+    pub(crate) fn file_page(
+        &self,
+        id: &str,
+        sort: FileSortKey,
+        offset: usize,
+        limit: usize,
+    ) -> Result<FilePage, String> {
+        let mut held = self.lock_fresh()?;
+        let index = find_held(&held, id)?;
+        // Move it to the back so it is the last to be evicted.
+        let mut entry = held.remove(index);
+        entry.used_at = Instant::now();
+        let files = match entry.files.take() {
+            Some((key, files)) if key == sort => files,
+            Some((_, mut files)) => {
+                sort_files(&mut files, sort);
+                files
+            }
+            None => {
+                let mut files = Vec::new();
+                collect_node_files(&entry.summary.root, &mut files);
+                sort_files(&mut files, sort);
+                files
+            }
+        };
+        let page = FilePage {
+            id: id.to_string(),
+            total: files.len(),
+            offset,
+            files: files.iter().skip(offset).take(limit).cloned().collect(),
+        };
+        entry.files = Some((sort, files));
+        held.push(entry);
+        Ok(page)
+    }
+}
+
+fn find_held(held: &[HeldSummary], id: &str) -> Result<usize, String> {
+    held.iter()
+        .position(|entry| entry.id == id)
+        .ok_or_else(|| format!("No completed scan held for id {id}"))
+}
+
+fn collect_node_files(node: &ScanNode, files: &mut Vec<ScanFile>) {
+    files.extend(node.files.iter().cloned());
+    for child in &node.children {
+        collect_node_files(child, files);
+    }
+}
+
+fn sort_files(files: &mut [ScanFile], key: FileSortKey) {
+    files.sort_by(|a, b| {
+        let order = match key {
+            FileSortKey::SizeDesc => b.size_bytes.cmp(&a.size_bytes),
+            FileSortKey::SizeAsc => a.size_bytes.cmp(&b.size_bytes),
+            FileSortKey::NameAsc => a.name.cmp(&b.name),
+            FileSortKey::NameDesc => b.name.cmp(&a.name),
+            FileSortKey::PathAsc => std::cmp::Ordering::Equal,
+            FileSortKey::ModifiedDesc => b.modified.cmp(&a.modified),
+        };
+        order.then_with(|| a.path.cmp(&b.path))
+    });
+}
+
+pub(crate) fn transfer_fit(summary: &ScanSummary, snapshot: &DiskUsageSnapshot) -> TransferFit {
+    let free_bytes = snapshot.free_bytes;
+    let mut files = Vec::new();
+    collect_node_files(&summary.root, &mut files);
+    let listed: HashSet<&str> = files.iter().map(|file| file.path.as_str()).collect();
+    let extra: Vec<ScanFile> = summary
+        .largest_files
+        .iter()
+        .filter(|file| !listed.contains(file.path.as_str()))
+        .cloned()
+        .collect();
+    files.extend(extra);
+    let mut oversized_files: Vec<ScanFile> = files
+        .into_iter()
+        .filter(|file| file.size_bytes > free_bytes)
+        .collect();
+    sort_files(&mut oversized_files, FileSortKey::SizeDesc);
+    let oversized_count = oversized_files.len() as u64;
+    oversized_files.truncate(MAX_OVERSIZED_FILES);
+    TransferFit {
+        destination: snapshot.path.clone(),
+        free_bytes,
+        required_bytes: summary.total_bytes,
+        fits: summary.total_bytes <= free_bytes,
+        oversized_files,
+        oversized_count,
+    }
+}
+
+fn signed_delta(old: u64, new: u64) -> i64 {
+    new as i64 - old as i64
+}
+
+pub(crate) fn diff_summaries(
+    old: &ScanSummary,
+    new: &ScanSummary,
+    threshold: u64,
+) -> Result<ScanDiff, String> {
+    if old.root.path != new.root.path {
+        return Err(format!(
+            "Scans have different roots: {} and {}",
+            old.root.path, new.root.path
+        ));
+    }
+    let mut diff = ScanDiff {
+        byte_delta: signed_delta(old.total_bytes, new.total_bytes),
+        file_delta: signed_delta(old.file_count, new.file_count),
+        changes: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+    };
+    diff_nodes(&old.root, &new.root, threshold, &mut diff);
+    diff.changes.sort_by(|a, b| {
+        b.byte_delta
+            .unsigned_abs()
+            .cmp(&a.byte_delta.unsigned_abs())
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    for entries in [&mut diff.added, &mut diff.removed] {
+        entries.sort_by(|a, b| {
+            b.size_bytes
+                .cmp(&a.size_bytes)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+    }
+    Ok(diff)
+}
+
+fn diff_nodes(old: &ScanNode, new: &ScanNode, threshold: u64, diff: &mut ScanDiff) {
+    let byte_delta = signed_delta(old.size_bytes, new.size_bytes);
+    let file_delta = signed_delta(old.file_count, new.file_count);
+    if (byte_delta != 0 || file_delta != 0) && byte_delta.unsigned_abs() >= threshold {
+        diff.changes.push(FolderChange {
+            path: new.path.clone(),
+            old_bytes: old.size_bytes,
+            new_bytes: new.size_bytes,
+            byte_delta,
+            file_delta,
+        });
+    }
+
+    let old_children: HashMap<&str, &ScanNode> = old
+        .children
+        .iter()
+        .map(|child| (child.path.as_str(), child))
+        .collect();
+    let new_children: HashSet<&str> = new
+        .children
+        .iter()
+        .map(|child| child.path.as_str())
+        .collect();
+    for child in &new.children {
+        match old_children.get(child.path.as_str()) {
+            Some(previous) => diff_nodes(previous, child, threshold, diff),
+            None => push_diff_entry(
+                &mut diff.added,
+                &child.path,
+                child.size_bytes,
+                true,
+                threshold,
+            ),
+        }
+    }
+    for child in &old.children {
+        if !new_children.contains(child.path.as_str()) {
+            push_diff_entry(
+                &mut diff.removed,
+                &child.path,
+                child.size_bytes,
+                true,
+                threshold,
+            );
+        }
+    }
+
+    let old_files: HashSet<&str> = old.files.iter().map(|file| file.path.as_str()).collect();
+    let new_files: HashSet<&str> = new.files.iter().map(|file| file.path.as_str()).collect();
+    for file in &new.files {
+        if !old_files.contains(file.path.as_str()) {
+            push_diff_entry(
+                &mut diff.added,
+                &file.path,
+                file.size_bytes,
+                false,
+                threshold,
+            );
+        }
+    }
+    for file in &old.files {
+        if !new_files.contains(file.path.as_str()) {
+            push_diff_entry(
+                &mut diff.removed,
+                &file.path,
+                file.size_bytes,
+                false,
+                threshold,
+            );
+        }
+    }
+}
+
+fn push_diff_entry(
+    entries: &mut Vec<DiffEntry>,
+    path: &str,
+    size_bytes: u64,
+    is_dir: bool,
+    threshold: u64,
+) {
+    if size_bytes >= threshold {
+        entries.push(DiffEntry {
+            path: path.to_string(),
+            size_bytes,
+            is_dir,
+        });
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct DirectoryWatchState {
+    next_id: AtomicU64,
+    pub(crate) watches: Mutex<HashMap<String, WatchEntry>>,
+}
+
+pub(crate) struct WatchEntry {
+    stop: Arc<AtomicBool>,
+    window: String,
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct ScanControl {
+    pub(crate) cancel: Arc<AtomicBool>,
+    pub(crate) pause: Arc<AtomicBool>,
+}
+
+#[derive(Clone)]
+pub(crate) enum ScanEvent {
+    Progress(ScanSummary),
+    Complete(ScanSummary),
+    Duplicates(DuplicateReport),
+    CountProgress(EntryCount),
+    CountComplete(EntryCount),
+    RecentFiles(RecentFilesReport),
+    NameGroups(NameGroupsReport),
+    ArchiveCandidates(ArchiveCandidatesReport),
+    CompressionEstimate(CompressionEstimate),
+    ExportProgress(ExportProgress),
+    ExportComplete(ExportProgress),
+    NetworkMount(NetworkMountNotice),
+    Delta(ScanDelta),
+    Error(String),
+    Cancelled(String, Option<ScanSummary>),
+    Paused,
+    Resumed,
+}
+
+pub(crate) type ScanEmitter = Arc<dyn Fn(ScanEvent) + Send + Sync>;
+
+const WINDOW_EVENT_BACKLOG: usize = 16;
+
+impl ScanEvent {
+    pub(crate) fn is_progress(&self) -> bool {
+        matches!(
+            self,
+            ScanEvent::Progress(_) | ScanEvent::CountProgress(_) | ScanEvent::ExportProgress(_)
+        )
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScanNode {
+    path: String,
+    name: String,
+    size_bytes: u64,
+    file_count: u64,
+    dir_count: u64,
+    files: Vec<ScanFile>,
+    children: Vec<ScanNode>,
+    #[serde(default)]
+    truncated: bool,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    child_count: u64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    has_children: bool,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanFile {
+    path: String,
+    name: String,
+    size_bytes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScanSummary {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    pub(crate) root: ScanNode,
+    total_bytes: u64,
+    file_count: u64,
+    dir_count: u64,
+    largest_files: Vec<ScanFile>,
+    #[serde(default)]
+    largest_dirs: Vec<DirStat>,
+    duration_ms: u128,
+    #[serde(default)]
+    by_extension: Vec<ExtensionStat>,
+    #[serde(default)]
+    entries_per_sec: f64,
+    #[serde(default)]
+    eta_ms: Option<u128>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<ScanEntryError>,
+    #[serde(default)]
+    empty_dirs: Vec<String>,
+    #[serde(default)]
+    trash_bytes: u64,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    largest_by_category: HashMap<String, Vec<ScanFile>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skipped_network_mounts: Vec<String>,
+    #[serde(default)]
+    zero_byte_count: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    zero_byte_files: Vec<String>,
+    #[serde(default)]
+    truncated_by_budget: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    filter_hits: Vec<FilterHit>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FilterHit {
+    field: String,
+    rule: String,
+    hits: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DirStat {
+    path: String,
+    size_bytes: u64,
+    file_count: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExtensionStat {
+    ext: String,
+    total_bytes: u64,
+    file_count: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    average_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    median_bytes: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScanEntryError {
+    path: String,
+    message: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DuplicateGroup {
+    hash: String,
+    size_bytes: u64,
+    paths: Vec<String>,
+    reclaimable_bytes: u64,
+}
+
+#[derive(Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DirectorySnapshot {
+    pub(crate) id: String,
+    pub(crate) path: String,
+    pub(crate) size_bytes: u64,
+    pub(crate) file_count: u64,
+    pub(crate) dir_count: u64,
+}
+
+type WatchedFiles = HashMap<PathBuf, (u64, Option<u64>)>;
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScanDelta {
+    id: String,
+    path: String,
+    created: Vec<ScanFile>,
+    removed: Vec<ScanFile>,
+    resized: Vec<ScanFile>,
+    total_bytes: u64,
+    file_count: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecentFilesReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    files: Vec<ScanFile>,
+    scanned_files: u64,
+    truncated_by_budget: bool,
+    duration_ms: u128,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveCandidate {
+    path: String,
+    name: String,
+    size_bytes: u64,
+    modified: u64,
+    age_days: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchiveCandidatesReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    files: Vec<ArchiveCandidate>,
+    matched_files: u64,
+    matched_bytes: u64,
+    scanned_files: u64,
+    min_size_bytes: u64,
+    min_age_days: u64,
+    truncated_by_budget: bool,
+    duration_ms: u128,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NameGroup {
+    name: String,
+    count: u64,
+    total_bytes: u64,
+    files: Vec<ScanFile>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NameGroupsReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    groups: Vec<NameGroup>,
+    omitted_groups: usize,
+    scanned_files: u64,
+    truncated_by_budget: bool,
+    duration_ms: u128,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompressionEstimate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    total_files: u64,
+    total_bytes: u64,
+    sampled_files: u64,
+    sampled_bytes: u64,
+    compressed_sample_bytes: u64,
+    coverage: f64,
+    estimated_ratio: f64,
+    estimated_compressed_bytes: u64,
+    truncated_by_budget: bool,
+    duration_ms: u128,
+}
+
+#[derive(Serialize)]
+struct ExportedFile<'a> {
+    path: &'a str,
+    size: u64,
+    parent: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelledScan {
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<ScanSummary>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ExportProgress {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    lines: u64,
+    total_bytes: u64,
+    truncated_by_budget: bool,
+    duration_ms: u128,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EntryCount {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    file_count: u64,
+    dir_count: u64,
+    truncated_by_budget: bool,
+    duration_ms: u128,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DuplicateReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    root: String,
+    groups: Vec<DuplicateGroup>,
+    reclaimable_bytes: u64,
+    hashed_files: u64,
+    truncated_by_budget: bool,
+    duration_ms: u128,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NetworkMountNotice {
+    mount_point: String,
+    filesystem: String,
+    aborted: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ScanPriorityMode {
+    Performance,
+    Balanced,
+    Low,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum ScanThrottleLevel {
+    Off,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScanFilters {
+    include_extensions: Vec<String>,
+    exclude_extensions: Vec<String>,
+    include_names: Vec<String>,
+    exclude_names: Vec<String>,
+    min_size_bytes: Option<u64>,
+    max_size_bytes: Option<u64>,
+    #[serde(default)]
+    min_size: Option<String>,
+    #[serde(default)]
+    max_size: Option<String>,
+    #[serde(alias = "modifiedAfterMs")]
+    min_modified_timestamp: Option<u64>,
+    #[serde(alias = "modifiedBeforeMs")]
+    max_modified_timestamp: Option<u64>,
+    include_regex: Option<String>,
+    exclude_regex: Option<String>,
+    include_paths: Vec<String>,
+    exclude_paths: Vec<String>,
+    #[serde(default)]
+    exclude_path_prefixes: Vec<String>,
+    #[serde(default)]
+    include_globs: Vec<String>,
+    #[serde(default)]
+    exclude_globs: Vec<String>,
+    #[serde(default)]
+    case_sensitive: bool,
+    #[serde(default)]
+    normalize_unicode: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScanOptions {
+    priority_mode: ScanPriorityMode,
+    throttle_level: ScanThrottleLevel,
+    filters: ScanFilters,
+    #[serde(default)]
+    progress_batch_size: Option<u64>,
+    #[serde(default)]
+    emit_every: Option<u64>,
+    #[serde(default)]
+    emit_interval_ms: Option<u64>,
+    #[serde(default)]
+    max_depth: Option<usize>,
+    #[serde(default)]
+    walk_depth: Option<usize>,
+    #[serde(default)]
+    include_trash: bool,
+    #[serde(default)]
+    largest_per_category: Option<usize>,
+    #[serde(default)]
+    extension_size_stats: bool,
+    #[serde(default)]
+    largest_files_limit: Option<usize>,
+    #[serde(default)]
+    network_mounts: NetworkMountPolicy,
+    #[serde(default)]
+    max_entries: Option<u64>,
+    #[serde(default)]
+    sort_order: SortOrder,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum SortOrder {
+    #[default]
+    SizeDesc,
+    SizeAsc,
+    NameAsc,
+    NameDesc,
+    FileCountDesc,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+enum NetworkMountPolicy {
+    #[default]
+    Follow,
+    Skip,
+    Abort,
+}
+
+impl Default for ScanPriorityMode {
+    fn default() -> Self {
+        ScanPriorityMode::Balanced
+    }
+}
+
+impl Default for ScanThrottleLevel {
+    fn default() -> Self {
+        ScanThrottleLevel::Off
+    }
+}
+
+impl Default for ScanFilters {
+    fn default() -> Self {
+        Self {
+            include_extensions: Vec::new(),
+            exclude_extensions: Vec::new(),
+            include_names: Vec::new(),
+            exclude_names: Vec::new(),
+            min_size_bytes: None,
+            max_size_bytes: None,
+            min_size: None,
+            max_size: None,
+            min_modified_timestamp: None,
+            max_modified_timestamp: None,
+            include_regex: None,
+            exclude_regex: None,
+            include_paths: Vec::new(),
+            exclude_paths: Vec::new(),
+            exclude_path_prefixes: Vec::new(),
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
+            case_sensitive: false,
+            normalize_unicode: false,
+        }
+    }
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            priority_mode: ScanPriorityMode::default(),
+            throttle_level: ScanThrottleLevel::default(),
+            filters: ScanFilters::default(),
+            progress_batch_size: None,
+            emit_every: None,
+            emit_interval_ms: None,
+            max_depth: None,
+            walk_depth: None,
+            include_trash: false,
+            largest_per_category: None,
+            extension_size_stats: false,
+            largest_files_limit: None,
+            network_mounts: NetworkMountPolicy::default(),
+            max_entries: None,
+            sort_order: SortOrder::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct FilterDecision<'a> {
+    included: bool,
+    rule: &'static str,
+    term: Option<&'a str>,
+}
+
+impl<'a> FilterDecision<'a> {
+    fn included(rule: &'static str) -> Self {
+        Self {
+            included: true,
+            rule,
+            term: None,
+        }
+    }
+
+    fn excluded(rule: &'static str) -> Self {
+        Self {
+            included: false,
+            rule,
+            term: None,
+        }
+    }
+
+    fn included_by(rule: &'static str, term: &'a str) -> Self {
+        Self {
+            term: Some(term),
+            ..Self::included(rule)
+        }
+    }
+
+    fn excluded_by(rule: &'static str, term: &'a str) -> Self {
+        Self {
+            term: Some(term),
+            ..Self::excluded(rule)
+        }
+    }
+}
+
+#[derive(Default)]
+struct FilterHits<'a> {
+    counts: HashMap<(&'static str, Option<&'a str>), u64>,
+}
+
+impl<'a> FilterHits<'a> {
+    fn record(&mut self, decision: FilterDecision<'a>) {
+        if matches!(decision.rule, "noIncludes" | "noMatchingInclude") {
+            return;
+        }
+        *self
+            .counts
+            .entry((decision.rule, decision.term))
+            .or_default() += 1;
+    }
+
+    fn report(&self, filters: &'a FilterConfig) -> Vec<FilterHit> {
+        let mut rules: Vec<(&'static str, Option<&str>, String)> = Vec::new();
+        let limits = [
+            ("minSizeBytes", filters.min_size_bytes),
+            ("maxSizeBytes", filters.max_size_bytes),
+            ("minModifiedTimestamp", filters.min_modified_timestamp),
+            ("maxModifiedTimestamp", filters.max_modified_timestamp),
+        ];
+        for (field, value) in limits {
+            if let Some(value) = value {
+                rules.push((field, None, value.to_string()));
+            }
+        }
+        let mut push_terms = |field: &'static str, terms: Vec<&'a str>| {
+            for term in terms {
+                rules.push((field, Some(term), term.to_string()));
+            }
+        };
+        let sorted_set = |set: &'a HashSet<String>| {
+            let mut terms: Vec<&str> = set.iter().map(String::as_str).collect();
+            terms.sort_unstable();
+            terms
+        };
+        let regex_term = |regex: &'a Option<Regex>| regex.iter().map(Regex::as_str).collect();
+        let glob_terms = |globs: &'a Option<GlobFilter>| {
+            globs
+                .iter()
+                .flat_map(|globs| globs.patterns.iter().map(String::as_str))
+                .collect()
+        };
+        let list_terms = |list: &'a [String]| list.iter().map(String::as_str).collect();
+        push_terms("excludeRegex", regex_term(&filters.exclude_regex));
+        push_terms("excludePaths", list_terms(&filters.exclude_paths));
+        push_terms(
+            "excludePathPrefixes",
+            list_terms(&filters.exclude_path_prefixes),
+        );
+        push_terms("excludeNames", list_terms(&filters.exclude_names));
+        push_terms("excludeExtensions", sorted_set(&filters.exclude_extensions));
+        push_terms("excludeGlobs", glob_terms(&filters.exclude_globs));
+        push_terms("includeRegex", regex_term(&filters.include_regex));
+        push_terms("includePaths", list_terms(&filters.include_paths));
+        push_terms("includeNames", list_terms(&filters.include_names));
+        push_terms("includeGlobs", glob_terms(&filters.include_globs));
+        push_terms("includeExtensions", sorted_set(&filters.include_extensions));
+
+        // A term listed twice only ever matches as the first one.
+        let mut seen = HashSet::new();
+        rules
+            .into_iter()
+            .filter(|(field, term, _)| seen.insert((*field, *term)))
+            .map(|(field, term, rule)| FilterHit {
+                field: field.to_string(),
+                rule,
+                hits: self.counts.get(&(field, term)).copied().unwrap_or(0),
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct ScanExtras<'a> {
+    trash_bytes: u64,
+    largest_by_category: HashMap<String, Vec<ScanFile>>,
+    skipped_network_mounts: Vec<String>,
+    zero_byte_count: u64,
+    zero_byte_files: Vec<String>,
+    errors: Vec<ScanEntryError>,
+    truncated_by_budget: bool,
+    filter_hits: FilterHits<'a>,
+}
+
+impl<'a> ScanExtras<'a> {
+    fn apply_progress(&self, summary: &mut ScanSummary) {
+        summary.trash_bytes = self.trash_bytes;
+        summary.largest_by_category = self.largest_by_category.clone();
+        summary.skipped_network_mounts = self.skipped_network_mounts.clone();
+        summary.zero_byte_count = self.zero_byte_count;
+        summary.zero_byte_files = self.zero_byte_files.clone();
+        summary.truncated_by_budget = self.truncated_by_budget;
+    }
+
+    fn finish(self, mut summary: ScanSummary, filters: &'a FilterConfig) -> ScanSummary {
+        summary.trash_bytes = self.trash_bytes;
+        summary.largest_by_category = self.largest_by_category;
+        summary.skipped_network_mounts = self.skipped_network_mounts;
+        summary.zero_byte_count = self.zero_byte_count;
+        summary.zero_byte_files = self.zero_byte_files;
+        summary.truncated_by_budget = self.truncated_by_budget;
+        summary.errors = self.errors;
+        summary.filter_hits = self.filter_hits.report(filters);
+        summary
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileExplanation {
+    path: String,
+    size_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+    included: bool,
+    rule: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    term: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    folder: Option<String>,
+}
+
+pub(crate) struct FilterConfig {
+    include_extensions: HashSet<String>,
+    exclude_extensions: HashSet<String>,
+    include_names: Vec<String>,
+    exclude_names: Vec<String>,
+    min_size_bytes: Option<u64>,
+    max_size_bytes: Option<u64>,
+    min_modified_timestamp: Option<u64>,
+    max_modified_timestamp: Option<u64>,
+    include_regex: Option<Regex>,
+    exclude_regex: Option<Regex>,
+    include_paths: Vec<String>,
+    exclude_paths: Vec<String>,
+    exclude_path_prefixes: Vec<String>,
+    include_globs: Option<GlobFilter>,
+    exclude_globs: Option<GlobFilter>,
+    case_sensitive: bool,
+    normalize_unicode: bool,
+    flags: FilterFlags,
+}
+
+struct GlobFilter {
+    set: GlobSet,
+    patterns: Vec<String>,
+}
+
+struct FilterFlags {
+    has_includes: bool,
+    has_file_excludes: bool,
+    has_dir_excludes: bool,
+    needs_path: bool,
+    needs_name: bool,
+    needs_extension: bool,
+    needs_metadata: bool,
+}
+
+impl FilterConfig {
+    fn fold(&self, value: &str) -> String {
+        fold_text(value, self.case_sensitive, self.normalize_unicode)
+    }
+}
+
+struct ThrottleConfig {
+    every_entries: u64,
+    sleep_ms: u64,
+}
+
+pub(crate) struct ScanConfig {
+    filters: FilterConfig,
+    emit_every: u64,
+    emit_interval: Duration,
+    batch_size: u64,
+    max_depth: Option<usize>,
+    walk_depth: Option<usize>,
+    include_trash: bool,
+    largest_per_category: Option<usize>,
+    extension_size_stats: bool,
+    largest_files_limit: usize,
+    network_mounts: NetworkMountPolicy,
+    max_entries: Option<u64>,
+    sort_order: SortOrder,
+    throttle: Option<ThrottleConfig>,
+    parallelism: Parallelism,
+}
+
+#[derive(Default)]
+struct ScanTree {
+    dirs: Vec<TreeDir>,
+}
+
+struct TreeDir {
+    name: String,
+    children: Vec<usize>,
+    files: Vec<TreeFile>,
+    stats: NodeStats,
+}
+
+struct TreeFile {
+    name: String,
+    size_bytes: u64,
+    modified: Option<u64>,
+}
+
+impl ScanTree {
+    fn add_dir(&mut self, parent: Option<usize>, name: String) -> usize {
+        let id = self.dirs.len();
+        self.dirs.push(TreeDir {
+            name,
+            children: Vec::new(),
+            files: Vec::new(),
+            stats: NodeStats::default(),
+        });
+        if let Some(parent) = parent {
+            let parent = &mut self.dirs[parent];
+            parent.children.push(id);
+            parent.stats.direct_dirs += 1;
+        }
+        id
+    }
+
+    fn note_unlisted_entry(&mut self, owner: Option<usize>) {
+        if let Some(owner) = owner {
+            self.dirs[owner].stats.unlisted_entries += 1;
+        }
+    }
+}
+
+#[derive(Default)]
+struct NodeStats {
+    direct_bytes: u64,
+    direct_files: u64,
+    direct_dirs: u64,
+    pruned_dirs: u64,
+    folded_children: u64,
+    truncated: bool,
+    unlisted_entries: u64,
+}
+
+impl ScanControl {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn same_scan(&self, other: &ScanControl) -> bool {
+        Arc::ptr_eq(&self.cancel, &other.cancel)
+    }
+
+    fn wait_while_paused(&self, emit: &ScanEmitter) {
+        if !self.pause.load(Ordering::Relaxed) {
+            return;
+        }
+        emit(ScanEvent::Paused);
+        while self.pause.load(Ordering::Relaxed) && !self.is_cancelled() {
+            thread::sleep(Duration::from_millis(100));
+        }
+        if !self.is_cancelled() {
+            emit(ScanEvent::Resumed);
+        }
+    }
+}
+
+pub(crate) fn window_emitter(window: tauri::Window) -> ScanEmitter {
+    let (sender, receiver) = mpsc::sync_channel::<ScanEvent>(WINDOW_EVENT_BACKLOG);
+    thread::spawn(move || {
+        while let Ok(first) = receiver.recv() {
+            let mut pending = Some(first);
+            for next in receiver.try_iter() {
+                if let Some(current) = pending.take() {
+                    let replaced = current.is_progress()
+                        && std::mem::discriminant(&current) == std::mem::discriminant(&next);
+                    if !replaced {
+                        emit_to_window(&window, current);
+                    }
+                }
+                pending = Some(next);
+            }
+            if let Some(event) = pending {
+                emit_to_window(&window, event);
+            }
+        }
+    });
+    // Block rather than drop when the queue is full: the worker already coalesces
+    // queued progress, and the newest progress event must always get through.
+    Arc::new(move |event| {
+        let _ = sender.send(event);
+    })
+}
+
+pub(crate) fn emit_to_window(window: &tauri::Window, event: ScanEvent) {
+    match event {
+        ScanEvent::Progress(summary) => {
+            let _ = window.emit("scan-progress", summary);
+        }
+        ScanEvent::Complete(summary) => {
+            let _ = window.emit("scan-complete", summary);
+        }
+        ScanEvent::Duplicates(report) => {
+            let _ = window.emit("duplicates-complete", report);
+        }
+        ScanEvent::CountProgress(count) => {
+            let _ = window.emit("count-progress", count);
+        }
+        ScanEvent::CountComplete(count) => {
+            let _ = window.emit("count-complete", count);
+        }
+        ScanEvent::RecentFiles(report) => {
+            let _ = window.emit("recent-files-complete", report);
+        }
+        ScanEvent::NameGroups(report) => {
+            let _ = window.emit("name-groups-complete", report);
+        }
+        ScanEvent::ArchiveCandidates(report) => {
+            let _ = window.emit("archive-candidates-complete", report);
+        }
+        ScanEvent::CompressionEstimate(report) => {
+            let _ = window.emit("compression-estimate-complete", report);
+        }
+        ScanEvent::ExportProgress(progress) => {
+            let _ = window.emit("export-progress", progress);
+        }
+        ScanEvent::ExportComplete(progress) => {
+            let _ = window.emit("export-complete", progress);
+        }
+        ScanEvent::NetworkMount(notice) => {
+            let _ = window.emit("scan-network-mount", notice);
+        }
+        ScanEvent::Delta(delta) => {
+            let _ = window.emit("scan-delta", delta);
+        }
+        ScanEvent::Error(message) => {
+            let _ = window.emit("scan-error", message);
+        }
+        ScanEvent::Cancelled(message, summary) => {
+            let _ = window.emit("scan-cancelled", CancelledScan { message, summary });
+        }
+        ScanEvent::Paused => {
+            let _ = window.emit("scan-paused", ());
+        }
+        ScanEvent::Resumed => {
+            let _ = window.emit("scan-resumed", ());
+        }
+    }
+}
+
+pub(crate) fn register_watch(
+    state: &DirectoryWatchState,
+    window: &str,
+) -> Result<(String, Arc<AtomicBool>), String> {
+    let id = format!("watch-{}", state.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+    let stop = Arc::new(AtomicBool::new(false));
+    state
+        .watches
+        .lock()
+        .map_err(|_| "Failed to lock watch state".to_string())?
+        .insert(
+            id.clone(),
+            WatchEntry {
+                stop: Arc::clone(&stop),
+                window: window.to_string(),
+            },
+        );
+    Ok((id, stop))
+}
+
+pub(crate) fn stop_watch(state: &DirectoryWatchState, id: &str) -> bool {
+    let entry = state
+        .watches
+        .lock()
+        .ok()
+        .and_then(|mut watches| watches.remove(id));
+    match entry {
+        Some(entry) => {
+            entry.stop.store(true, Ordering::SeqCst);
+            true
+        }
+        None => false,
+    }
+}
+
+pub(crate) fn stop_window_watches(app: &tauri::AppHandle, label: &str) {
+    let state = match app.try_state::<DirectoryWatchState>() {
+        Some(value) => value,
+        None => return,
+    };
+    if let Ok(mut watches) = state.watches.lock() {
+        watches.retain(|_, entry| {
+            if entry.window != label {
+                return true;
+            }
+            entry.stop.store(true, Ordering::SeqCst);
+            false
+        });
+    };
+}
+
+pub(crate) fn start_path_watch(
+    root: PathBuf,
+    filters: FilterConfig,
+    id: String,
+    stop: Arc<AtomicBool>,
+    emit: ScanEmitter,
+    finish: Box<dyn FnOnce() + Send>,
+) -> Result<(), String> {
+    let (event_tx, event_rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(event_tx)
+        .map_err(|error| format!("Failed to start watcher: {error}"))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|error| format!("Failed to watch {}: {error}", root.display()))?;
+    thread::spawn(move || {
+        // Keeps the OS watch alive for as long as this thread runs.
+        let _watcher = watcher;
+        let mut files = WatchedFiles::new();
+        collect_watched_files(&root, &root, &filters, &mut files);
+        let mut delta = new_scan_delta(&id, &root);
+        finish_scan_delta(&mut delta, &files);
+        emit(ScanEvent::Delta(delta));
+
+        let mut pending: HashSet<PathBuf> = HashSet::new();
+        let mut rescan = false;
+        let mut first_event: Option<Instant> = None;
+        let mut last_event = Instant::now();
+        while !stop.load(Ordering::Relaxed) {
+            match event_rx.recv_timeout(Duration::from_millis(100)) {
+                Ok(Ok(event)) => {
+                    rescan |= event.need_rescan();
+                    pending.extend(
+                        event
+                            .paths
+                            .into_iter()
+                            .filter(|path| path.starts_with(&root)),
+                    );
+                    first_event.get_or_insert_with(Instant::now);
+                    last_event = Instant::now();
+                }
+                Ok(Err(error)) => {
+                    // Events may have been lost, so compare the whole tree again.
+                    eprintln!("[watch] {id}: {error}");
+                    rescan = true;
+                    first_event.get_or_insert_with(Instant::now);
+                    last_event = Instant::now();
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    emit(ScanEvent::Error(format!("Watch {id} stopped unexpectedly")));
+                    break;
+                }
+            }
+            let due = first_event.is_some_and(|first| {
+                last_event.elapsed() >= WATCH_DEBOUNCE || first.elapsed() >= WATCH_MAX_DELAY
+            });
+            if !due {
+                continue;
+            }
+            let mut paths: Vec<PathBuf> = if rescan {
+                vec![root.clone()]
+            } else {
+                pending.drain().collect()
+            };
+            pending.clear();
+            rescan = false;
+            first_event = None;
+            // Sorted, a folder comes before everything inside it, so nested
+            // paths already covered by an earlier one can be dropped.
+            paths.sort();
+            let mut covered: Vec<PathBuf> = Vec::new();
+            for path in paths {
+                if !covered.iter().any(|dir| path.starts_with(dir)) {
+                    covered.push(path);
+                }
+            }
+            let mut delta = new_scan_delta(&id, &root);
+            for path in &covered {
+                reconcile_watched_path(&root, path, &filters, &mut files, &mut delta);
+            }
+            if delta.created.is_empty() && delta.removed.is_empty() && delta.resized.is_empty() {
+                continue;
+            }
+            finish_scan_delta(&mut delta, &files);
+            emit(ScanEvent::Delta(delta));
+        }
+        finish();
+    });
+    Ok(())
+}
+
+fn new_scan_delta(id: &str, root: &Path) -> ScanDelta {
+    ScanDelta {
+        id: id.to_string(),
+        path: get_path_string(root),
+        created: Vec::new(),
+        removed: Vec::new(),
+        resized: Vec::new(),
+        total_bytes: 0,
+        file_count: 0,
+    }
+}
+
+fn finish_scan_delta(delta: &mut ScanDelta, files: &WatchedFiles) {
+    delta.total_bytes = files.values().map(|(size, _)| size).sum();
+    delta.file_count = files.len() as u64;
+}
+
+fn watched_file(path: &Path, size_bytes: u64, modified: Option<u64>) -> ScanFile {
+    ScanFile {
+        path: get_path_string(path),
+        name: get_entry_name_string(path),
+        size_bytes,
+        modified,
+        kind: None,
+    }
+}
+
+fn collect_watched_files(
+    root: &Path,
+    dir: &Path,
+    filters: &FilterConfig,
+    files: &mut WatchedFiles,
+) {
+    let mut skipped_dirs: HashSet<PathBuf> = HashSet::new();
+    for entry in WalkDir::new(dir).into_iter().flatten() {
+        let path = entry.path();
+        if path
+            .parent()
+            .is_some_and(|parent| is_within_skipped_dir(parent, &skipped_dirs))
+        {
+            continue;
+        }
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            if should_skip_dir(root, &path, filters) {
+                skipped_dirs.insert(path);
+            }
+        } else if file_type.is_file() {
+            let metadata = entry.metadata();
+            let size = metadata.as_ref().map(|meta| meta.len()).unwrap_or(0);
+            let modified = metadata
+                .as_ref()
+                .ok()
+                .and_then(|meta| get_time_millis(meta.modified()));
+            if should_include_file(root, &path, size, modified, filters) {
+                files.insert(path, (size, modified));
+            }
+        }
+    }
+}
+
+fn is_watched_dir_excluded(root: &Path, dir: &Path, filters: &FilterConfig) -> bool {
+    dir.ancestors()
+        .take_while(|ancestor| *ancestor != root && ancestor.starts_with(root))
+        .any(|ancestor| should_skip_dir(root, ancestor, filters))
+}
+
+fn reconcile_watched_path(
+    root: &Path,
+    path: &Path,
+    filters: &FilterConfig,
+    files: &mut WatchedFiles,
+    delta: &mut ScanDelta,
+) {
+    let mut current = WatchedFiles::new();
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() && !is_watched_dir_excluded(root, path, filters) => {
+            collect_watched_files(root, path, filters, &mut current);
+        }
+        Ok(meta) if meta.is_file() => {
+            let size = meta.len();
+            let modified = get_time_millis(meta.modified());
+            let excluded = path
+                .parent()
+                .is_some_and(|parent| is_watched_dir_excluded(root, parent, filters));
+            if !excluded && should_include_file(root, path, size, modified, filters) {
+                current.insert(path.to_path_buf(), (size, modified));
+            }
+        }
+        _ => {}
+    }
+    let previous: Vec<PathBuf> = files
+        .keys()
+        .filter(|known| known.starts_with(path))
+        .cloned()
+        .collect();
+    for known in previous {
+        let (old_size, old_modified) = files[&known];
+        match current.remove(&known) {
+            Some((size, modified)) => {
+                if size != old_size {
+                    delta.resized.push(watched_file(&known, size, modified));
+                }
+                files.insert(known, (size, modified));
+            }
+            None => {
+                delta
+                    .removed
+                    .push(watched_file(&known, old_size, old_modified));
+                files.remove(&known);
+            }
+        }
+    }
+    for (created, (size, modified)) in current {
+        delta.created.push(watched_file(&created, size, modified));
+        files.insert(created, (size, modified));
+    }
+}
+
+pub(crate) fn measure_directory_shallow(path: &Path) -> Result<(u64, u64, u64), String> {
+    let entries =
+        fs::read_dir(path).map_err(|error| format!("Failed to read directory: {error}"))?;
+    let (mut size_bytes, mut file_count, mut dir_count) = (0, 0, 0);
+    for entry in entries.flatten() {
+        let file_type = match entry.file_type() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        if file_type.is_dir() {
+            dir_count += 1;
+        } else if file_type.is_file() {
+            file_count += 1;
+            size_bytes += entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+        }
+    }
+    Ok((size_bytes, file_count, dir_count))
+}
+
+pub(crate) fn get_time_millis(time: std::io::Result<SystemTime>) -> Option<u64> {
+    time.ok()
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+}
+
+pub(crate) fn run_scan(
+    roots: Vec<PathBuf>,
+    config: ScanConfig,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut tree = ScanTree::default();
+    let mut root_ids: Vec<Option<usize>> = vec![None; roots.len()];
+    let mut largest_files: Vec<ScanFile> = Vec::new();
+    let mut extensions: HashMap<String, ExtensionTotals> = HashMap::new();
+    let mut skipped_dirs: HashSet<PathBuf> = HashSet::new();
+    let mut trash_dirs: HashSet<PathBuf> = HashSet::new();
+    let network_mounts = match config.network_mounts {
+        NetworkMountPolicy::Follow => HashMap::new(),
+        NetworkMountPolicy::Skip | NetworkMountPolicy::Abort => find_network_mounts(&roots),
+    };
+    let mut network_abort: Option<String> = None;
+    let mut last_emit = Instant::now();
+    let mut last_emitted = ProgressCounters::default();
+    let mut processed: u64 = 0;
+    let mut batch_start: u64 = 0;
+    let mut throttled_until: u64 = 0;
+    let mut emit_checked_at: u64 = 0;
+    let mut extras = ScanExtras::default();
+    let expected_bytes = estimate_expected_bytes(&roots);
+
+    'roots: for (root_index, root) in roots.iter().enumerate() {
+        let mut walk = scan_walk(root, &config, &network_mounts).into_iter();
+        // Tree ids of the folders on the path to the current entry, by depth. The
+        // walk is depth first, so an entry's parent is always the last one here.
+        let mut open_dirs: Vec<Option<usize>> = Vec::new();
+        loop {
+            control.wait_while_paused(&emit);
+            if control.is_cancelled() {
+                // Hand back what was found so far, in the same shape as a progress event.
+                let mut partial = build_summary(
+                    &roots,
+                    &root_ids,
+                    &tree,
+                    &largest_files,
+                    &extensions,
+                    start,
+                    scan_id.clone(),
+                    true,
+                    SortOrder::NameAsc,
+                    Some(400),
+                );
+                partial.entries_per_sec = entries_per_second(processed, start.elapsed());
+                emit(ScanEvent::Cancelled(
+                    "Scan cancelled".to_string(),
+                    Some(extras.finish(partial, &config.filters)),
+                ));
+                return Ok(());
+            }
+            // A misbehaving filesystem can panic inside the walker; stop walking but keep
+            // what was collected so the scan still completes.
+            let entry = match panic::catch_unwind(AssertUnwindSafe(|| walk.next())) {
+                Ok(Some(Ok(item))) => item,
+                Ok(Some(Err(_))) => continue,
+                Ok(None) => break,
+                Err(payload) => {
+                    extras.errors.push(ScanEntryError {
+                        path: get_path_string(root),
+                        message: describe_panic(payload.as_ref()),
+                    });
+                    break;
+                }
+            };
+            let entry_path = entry.path();
+            let entry_type = entry.file_type();
+            processed += 1;
+
+            let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+                if entry_type.is_dir() {
+                    open_dirs.truncate(entry.depth);
+                    open_dirs.push(None);
+                }
+                let parent_id = entry
+                    .depth
+                    .checked_sub(1)
+                    .and_then(|depth| open_dirs.get(depth).copied().flatten());
+                if entry_path
+                    .parent()
+                    .is_some_and(|parent| is_within_skipped_dir(parent, &trash_dirs))
+                {
+                    if entry_type.is_file() {
+                        extras.trash_bytes += entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+                    }
+                    return;
+                }
+                if entry_path
+                    .parent()
+                    .map_or(false, |parent| is_within_skipped_dir(parent, &skipped_dirs))
+                {
+                    return;
+                }
+                let folded = config.max_depth.is_some_and(|limit| {
+                    let deepest = if entry_type.is_dir() {
+                        limit
+                    } else {
+                        limit + 1
+                    };
+                    entry.depth > deepest
+                });
+                // Entries below `max_depth` are measured into their ancestor at that depth.
+                let owner = if folded {
+                    config
+                        .max_depth
+                        .and_then(|limit| open_dirs.get(limit).copied().flatten())
+                } else {
+                    parent_id
+                };
+                if entry_type.is_dir() {
+                    if let Some(decision) = dir_exclusion(root, &entry_path, &config.filters) {
+                        extras.filter_hits.record(decision);
+                        tree.note_unlisted_entry(owner);
+                        skipped_dirs.insert(entry_path.to_path_buf());
+                        return;
+                    }
+                    // Scanning a trash folder directly still counts it normally.
+                    if !config.include_trash && entry.depth > 0 && is_trash_dir(&entry_path) {
+                        tree.note_unlisted_entry(owner);
+                        trash_dirs.insert(entry_path.to_path_buf());
+                        return;
+                    }
+                    if let Some(filesystem) = network_mounts.get(entry_path.as_path()) {
+                        let mount_point = get_path_string(&entry_path);
+                        let aborted = config.network_mounts == NetworkMountPolicy::Abort;
+                        emit(ScanEvent::NetworkMount(NetworkMountNotice {
+                            mount_point: mount_point.clone(),
+                            filesystem: filesystem.clone(),
+                            aborted,
+                        }));
+                        if aborted {
+                            network_abort = Some(mount_point);
+                            return;
+                        }
+                        tree.note_unlisted_entry(owner);
+                        skipped_dirs.insert(entry_path.to_path_buf());
+                        extras.skipped_network_mounts.push(mount_point);
+                        return;
+                    }
+                    if folded {
+                        if let Some(anchor) = owner {
+                            let anchor_stats = &mut tree.dirs[anchor].stats;
+                            anchor_stats.pruned_dirs += 1;
+                            anchor_stats.truncated = true;
+                            // Only the anchor's own subfolders are children it lost.
+                            if config
+                                .max_depth
+                                .is_some_and(|limit| entry.depth == limit + 1)
+                            {
+                                anchor_stats.folded_children += 1;
+                            }
+                        }
+                        return;
+                    }
+                    let id = tree.add_dir(parent_id, get_entry_name_string(&entry_path));
+                    if let Some(open) = open_dirs.last_mut() {
+                        *open = Some(id);
+                    }
+                    // Folders at `walk_depth` were never opened, so they can't be called empty.
+                    if config.walk_depth.is_some_and(|limit| entry.depth >= limit) {
+                        tree.note_unlisted_entry(Some(id));
+                    }
+                    if entry.depth == 0 {
+                        root_ids[root_index] = Some(id);
+                    }
+                } else if entry_type.is_file() {
+                    let metadata = entry.metadata();
+                    let size = metadata.as_ref().map(|meta| meta.len()).unwrap_or(0);
+                    let modified = metadata
+                        .as_ref()
+                        .ok()
+                        .and_then(|m| get_time_millis(m.modified()));
+
+                    let decision =
+                        file_decision(root, &entry_path, size, modified, &config.filters);
+                    extras.filter_hits.record(decision);
+                    if !decision.included {
+                        tree.note_unlisted_entry(owner);
+                        return;
+                    }
+                    // Unreadable metadata also reports 0, so only trust a real length.
+                    if metadata.as_ref().is_ok_and(|meta| meta.len() == 0) {
+                        extras.zero_byte_count += 1;
+                        if extras.zero_byte_files.len() < MAX_ZERO_BYTE_SAMPLE {
+                            extras.zero_byte_files.push(get_path_string(&entry_path));
+                        }
+                    }
+                    update_largest_files(
+                        &mut largest_files,
+                        &entry_path,
+                        size,
+                        modified,
+                        config.largest_files_limit,
+                    );
+                    let ext_key = get_extension_key(&entry_path);
+                    if let Some(limit) = config.largest_per_category {
+                        let bucket = extras
+                            .largest_by_category
+                            .entry(get_file_category(&ext_key).to_string())
+                            .or_default();
+                        update_largest_files(bucket, &entry_path, size, modified, limit);
+                    }
+                    let ext_totals = extensions.entry(ext_key).or_default();
+                    ext_totals.total_bytes += size;
+                    ext_totals.file_count += 1;
+                    if config.extension_size_stats {
+                        ext_totals
+                            .median
+                            .get_or_insert_with(MedianEstimator::default)
+                            .add(size as f64);
+                    }
+                    if folded {
+                        if let Some(anchor) = owner {
+                            let anchor_stats = &mut tree.dirs[anchor].stats;
+                            anchor_stats.direct_bytes += size;
+                            anchor_stats.direct_files += 1;
+                        }
+                        return;
+                    }
+                    if let Some(parent) = parent_id {
+                        let dir = &mut tree.dirs[parent];
+                        dir.files.push(TreeFile {
+                            name: get_entry_name_string(&entry_path),
+                            size_bytes: size,
+                            modified,
+                        });
+                        dir.stats.direct_bytes += size;
+                        dir.stats.direct_files += 1;
+                    }
+                } else {
+                    tree.note_unlisted_entry(owner);
+                }
+            }));
+            if let Err(payload) = outcome {
+                extras.errors.push(ScanEntryError {
+                    path: get_path_string(&entry_path),
+                    message: describe_panic(payload.as_ref()),
+                });
+            }
+            if let Some(mount_point) = network_abort {
+                return Err(format!("Scan stopped: {mount_point} is a network mount"));
+            }
+            // Every entry seen so far is fully recorded, so the partial tree still adds up.
+            if config.max_entries.is_some_and(|limit| processed >= limit) {
+                extras.truncated_by_budget = true;
+                break 'roots;
+            }
+
+            // Throttle and progress decisions are made once per batch so they land
+            // between directories rather than at arbitrary points inside one.
+            if !entry_type.is_dir() && processed - batch_start < config.batch_size {
+                continue;
+            }
+            batch_start = processed;
+
+            if let Some(throttle) = &config.throttle {
+                let owed = (processed - throttled_until) / throttle.every_entries;
+                if throttle.sleep_ms > 0 && owed > 0 {
+                    thread::sleep(Duration::from_millis(throttle.sleep_ms * owed));
+                }
+                throttled_until += owed * throttle.every_entries;
+            }
+
+            if should_emit_progress(processed - emit_checked_at, &last_emit, &config) {
+                emit_checked_at = processed;
+                let mut summary = build_summary(
+                    &roots,
+                    &root_ids,
+                    &tree,
+                    &largest_files,
+                    &extensions,
+                    start,
+                    scan_id.clone(),
+                    true,               // compact mode
+                    SortOrder::NameAsc, // stable while sizes are still changing
+                    Some(400),          // cap children to avoid UI overload
+                );
+                let elapsed = start.elapsed();
+                summary.entries_per_sec = entries_per_second(processed, elapsed);
+                extras.apply_progress(&mut summary);
+                summary.eta_ms = estimate_eta_ms(summary.total_bytes, expected_bytes, elapsed);
+
+                // Emit whenever bytes, files or folders moved on, but never a
+                // summary that shows less of any of them than the last one did
+                let counters = ProgressCounters::of(&summary);
+                if counters.advanced_from(&last_emitted) {
+                    last_emitted = counters;
+                    emit(ScanEvent::Progress(summary));
+                    last_emit = Instant::now();
+                }
+            }
+        }
+    }
+
+    let entries_per_sec = entries_per_second(processed, start.elapsed());
+
+    // Always end with a progress event at the true totals, even if the guard above
+    // held back the last one, so clients that only follow scan-progress converge.
+    let mut progress = build_summary(
+        &roots,
+        &root_ids,
+        &tree,
+        &largest_files,
+        &extensions,
+        start,
+        scan_id.clone(),
+        true,
+        SortOrder::NameAsc,
+        Some(400),
+    );
+    progress.entries_per_sec = entries_per_sec;
+    progress.eta_ms = Some(0);
+    extras.apply_progress(&mut progress);
+    emit(ScanEvent::Progress(progress));
+
+    let mut summary = build_summary(
+        &roots,
+        &root_ids,
+        &tree,
+        &largest_files,
+        &extensions,
+        start,
+        scan_id,
+        false, // full mode
+        config.sort_order,
+        None,
+    );
+    summary.entries_per_sec = entries_per_sec;
+    summary.eta_ms = Some(0);
+    let mut summary = extras.finish(summary, &config.filters);
+    for file in &mut summary.largest_files {
+        file.kind = detect_file_kind(Path::new(&file.path));
+    }
+    emit(ScanEvent::Complete(summary));
+    Ok(())
+}
+
+pub(crate) fn resolve_scan_roots(paths: &[String]) -> Result<Vec<PathBuf>, String> {
+    if paths.is_empty() {
+        return Err("No paths provided".to_string());
+    }
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for path in paths {
+        let root = PathBuf::from(path);
+        if !root.exists() {
+            return Err(format!("Path does not exist: {path}"));
+        }
+        roots.push(root);
+    }
+    let candidates = roots.clone();
+    let mut resolved: Vec<PathBuf> = Vec::new();
+    for (index, root) in candidates.iter().enumerate() {
+        let covered = candidates.iter().enumerate().any(|(other_index, other)| {
+            other_index != index
+                && root.starts_with(other)
+                && (root != other || other_index < index)
+        });
+        if !covered {
+            resolved.push(root.clone());
+        }
+    }
+    Ok(resolved)
+}
+
+fn estimate_expected_bytes(roots: &[PathBuf]) -> Option<u64> {
+    let mut seen: HashSet<(u64, u64)> = HashSet::new();
+    let mut used: u64 = 0;
+    for root in roots {
+        let snapshot = compute_disk_usage(root).ok()?;
+        if seen.insert((snapshot.total_bytes, snapshot.free_bytes)) {
+            used += snapshot.total_bytes.saturating_sub(snapshot.free_bytes);
+        }
+    }
+    if used == 0 {
+        return None;
+    }
+    Some(used)
+}
+
+fn entries_per_second(processed: u64, elapsed: Duration) -> f64 {
+    let seconds = elapsed.as_secs_f64();
+    if seconds <= 0.0 {
+        return 0.0;
+    }
+    processed as f64 / seconds
+}
+
+fn estimate_eta_ms(
+    scanned_bytes: u64,
+    expected_bytes: Option<u64>,
+    elapsed: Duration,
+) -> Option<u128> {
+    let expected = expected_bytes?;
+    if scanned_bytes == 0 || scanned_bytes >= expected {
+        return None;
+    }
+    let elapsed_ms = elapsed.as_millis();
+    let remaining = (expected - scanned_bytes) as u128;
+    Some(elapsed_ms * remaining / scanned_bytes as u128)
+}
+
+fn describe_panic(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        return format!("panic: {message}");
+    }
+    if let Some(message) = payload.downcast_ref::<String>() {
+        return format!("panic: {message}");
+    }
+    "panic: unknown cause".to_string()
+}
+
+pub(crate) fn run_entry_count(
+    root: PathBuf,
+    config: ScanConfig,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut last_emit = Instant::now();
+    let mut pending: u64 = 0;
+    let mut count = EntryCount {
+        id: scan_id,
+        file_count: 0,
+        dir_count: 0,
+        truncated_by_budget: false,
+        duration_ms: 0,
+    };
+
+    let read_sizes = config.filters.flags.needs_metadata;
+    let end = walk_filtered(&root, &config, &control, &emit, read_sizes, &mut |entry| {
+        match entry {
+            WalkedEntry::Dir => count.dir_count += 1,
+            WalkedEntry::File { .. } => count.file_count += 1,
+        }
+        pending += 1;
+        if should_emit_progress(pending, &last_emit, &config) {
+            count.duration_ms = start.elapsed().as_millis();
+            emit(ScanEvent::CountProgress(count.clone()));
+            pending = 0;
+            last_emit = Instant::now();
+        }
+        Ok(())
+    })?;
+    if end == WalkEnd::Cancelled {
+        return Ok(());
+    }
+    count.truncated_by_budget = end == WalkEnd::Truncated;
+    count.duration_ms = start.elapsed().as_millis();
+    emit(ScanEvent::CountComplete(count));
+    Ok(())
+}
+
+pub(crate) fn run_recent_files(
+    root: PathBuf,
+    config: ScanConfig,
+    limit: usize,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut recent: Vec<ScanFile> = Vec::new();
+    let mut scanned_files: u64 = 0;
+
+    let end = walk_filtered(&root, &config, &control, &emit, true, &mut |entry| {
+        if let WalkedEntry::File {
+            path,
+            size,
+            modified,
+        } = entry
+        {
+            scanned_files += 1;
+            if let Some(modified) = modified {
+                update_recent_files(&mut recent, &path, size, modified, limit);
+            }
+        }
+        Ok(())
+    })?;
+    if end == WalkEnd::Cancelled {
+        return Ok(());
+    }
+    emit(ScanEvent::RecentFiles(RecentFilesReport {
+        id: scan_id,
+        files: recent,
+        scanned_files,
+        truncated_by_budget: end == WalkEnd::Truncated,
+        duration_ms: start.elapsed().as_millis(),
+    }));
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_archive_candidates(
+    root: PathBuf,
+    config: ScanConfig,
+    min_size_bytes: u64,
+    min_age_days: u64,
+    limit: usize,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let now = get_time_millis(Ok(SystemTime::now())).unwrap_or(0);
+    let cutoff = now.saturating_sub(min_age_days.saturating_mul(DAY_MILLIS));
+    let mut largest: BinaryHeap<Reverse<(u64, u64, PathBuf)>> = BinaryHeap::new();
+    let mut scanned_files: u64 = 0;
+    let mut matched_files: u64 = 0;
+    let mut matched_bytes: u64 = 0;
+
+    let end = walk_filtered(&root, &config, &control, &emit, true, &mut |entry| {
+        let WalkedEntry::File {
+            path,
+            size,
+            modified,
+        } = entry
+        else {
+            return Ok(());
+        };
+        scanned_files += 1;
+        let Some(modified) = modified else {
+            return Ok(());
+        };
+        if size < min_size_bytes || modified > cutoff {
+            return Ok(());
+        }
+        matched_files += 1;
+        matched_bytes += size;
+        if largest.len() < limit {
+            largest.push(Reverse((size, modified, path)));
+        } else if largest
+            .peek()
+            .is_some_and(|Reverse(smallest)| smallest.0 < size)
+        {
+            largest.pop();
+            largest.push(Reverse((size, modified, path)));
+        }
+        Ok(())
+    })?;
+    if end == WalkEnd::Cancelled {
+        return Ok(());
+    }
+
+    let files = largest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((size_bytes, modified, path))| ArchiveCandidate {
+            path: get_path_string(&path),
+            name: get_entry_name_string(&path),
+            size_bytes,
+            modified,
+            age_days: now.saturating_sub(modified) / DAY_MILLIS,
+        })
+        .collect();
+    emit(ScanEvent::ArchiveCandidates(ArchiveCandidatesReport {
+        id: scan_id,
+        files,
+        matched_files,
+        matched_bytes,
+        scanned_files,
+        min_size_bytes,
+        min_age_days,
+        truncated_by_budget: end == WalkEnd::Truncated,
+        duration_ms: start.elapsed().as_millis(),
+    }));
+    Ok(())
+}
+
+pub(crate) fn run_name_groups(
+    root: PathBuf,
+    config: ScanConfig,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut by_name: HashMap<String, NameGroup> = HashMap::new();
+    let mut scanned_files: u64 = 0;
+
+    let end = walk_filtered(&root, &config, &control, &emit, true, &mut |entry| {
+        let WalkedEntry::File {
+            path,
+            size,
+            modified,
+        } = entry
+        else {
+            return Ok(());
+        };
+        scanned_files += 1;
+        let name = get_entry_name_string(&path);
+        let group = by_name.entry(name.clone()).or_insert_with(|| NameGroup {
+            name,
+            count: 0,
+            total_bytes: 0,
+            files: Vec::new(),
+        });
+        group.count += 1;
+        group.total_bytes += size;
+        if group.files.len() < MAX_NAME_GROUP_FILES {
+            group.files.push(ScanFile {
+                path: get_path_string(&path),
+                name: group.name.clone(),
+                size_bytes: size,
+                modified,
+                kind: None,
+            });
+        }
+        Ok(())
+    })?;
+    if end == WalkEnd::Cancelled {
+        return Ok(());
+    }
+
+    let mut groups: Vec<NameGroup> = by_name
+        .into_values()
+        .filter(|group| group.count > 1)
+        .collect();
+    groups.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(b.total_bytes.cmp(&a.total_bytes))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let omitted_groups = groups.len().saturating_sub(MAX_NAME_GROUPS);
+    groups.truncate(MAX_NAME_GROUPS);
+    emit(ScanEvent::NameGroups(NameGroupsReport {
+        id: scan_id,
+        groups,
+        omitted_groups,
+        scanned_files,
+        truncated_by_budget: end == WalkEnd::Truncated,
+        duration_ms: start.elapsed().as_millis(),
+    }));
+    Ok(())
+}
+
+pub(crate) fn run_compression_estimate(
+    root: PathBuf,
+    config: ScanConfig,
+    samples: usize,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut sampler = CompressionSampler::new(samples);
+
+    let end = walk_filtered(&root, &config, &control, &emit, true, &mut |entry| {
+        if let WalkedEntry::File { path, size, .. } = entry {
+            sampler.offer(path, size);
+        }
+        Ok(())
+    })?;
+    if end == WalkEnd::Cancelled {
+        return Ok(());
+    }
+    let CompressionSampler {
+        total_files,
+        total_bytes,
+        reservoir,
+        ..
+    } = sampler;
+
+    let mut sampled_files: u64 = 0;
+    let mut sampled_bytes: u64 = 0;
+    let mut compressed_sample_bytes: u64 = 0;
+    let mut weighted_ratio = 0.0;
+    let mut weight = 0.0;
+    for (path, size) in reservoir {
+        control.wait_while_paused(&emit);
+        if control.is_cancelled() {
+            emit(ScanEvent::Cancelled("Scan cancelled".to_string(), None));
+            return Ok(());
+        }
+        let Some((read, compressed)) = compress_file_sample(&path) else {
+            continue;
+        };
+        if read == 0 {
+            continue;
+        }
+        sampled_files += 1;
+        sampled_bytes += read;
+        compressed_sample_bytes += compressed;
+        weighted_ratio += size as f64 * (compressed as f64 / read as f64);
+        weight += size as f64;
+    }
+
+    let estimated_ratio = if weight > 0.0 {
+        weighted_ratio / weight
+    } else {
+        1.0
+    };
+    emit(ScanEvent::CompressionEstimate(CompressionEstimate {
+        id: scan_id,
+        total_files,
+        total_bytes,
+        sampled_files,
+        sampled_bytes,
+        compressed_sample_bytes,
+        coverage: if total_bytes > 0 {
+            sampled_bytes as f64 / total_bytes as f64
+        } else {
+            0.0
+        },
+        estimated_ratio,
+        estimated_compressed_bytes: (total_bytes as f64 * estimated_ratio).round() as u64,
+        truncated_by_budget: end == WalkEnd::Truncated,
+        duration_ms: start.elapsed().as_millis(),
+    }));
+    Ok(())
+}
+
+/// Reservoir sample over the walk, with a fixed seed so reruns pick the same files.
+/// Empty files count towards the totals but can't be sampled, so they don't count
+/// towards the odds either.
+struct CompressionSampler {
+    samples: usize,
+    total_files: u64,
+    total_bytes: u64,
+    eligible_files: u64,
+    reservoir: Vec<(PathBuf, u64)>,
+    rng: u64,
+}
+
+impl CompressionSampler {
+    fn new(samples: usize) -> Self {
+        Self {
+            samples,
+            total_files: 0,
+            total_bytes: 0,
+            eligible_files: 0,
+            reservoir: Vec::new(),
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    fn offer(&mut self, path: PathBuf, size: u64) {
+        self.total_files += 1;
+        self.total_bytes += size;
+        if size == 0 {
+            return;
+        }
+        self.eligible_files += 1;
+        if self.reservoir.len() < self.samples {
+            self.reservoir.push((path, size));
+            return;
+        }
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let slot = (self.rng % self.eligible_files) as usize;
+        if slot < self.samples {
+            self.reservoir[slot] = (path, size);
+        }
+    }
+}
+
+fn compress_file_sample(path: &Path) -> Option<(u64, u64)> {
+    let file = fs::File::open(path).ok()?;
+    let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::fast());
+    let read = std::io::copy(&mut file.take(COMPRESSION_SAMPLE_BYTES), &mut encoder).ok()?;
+    let compressed = encoder.finish().ok()?;
+    Some((read, compressed.len() as u64))
+}
+
+pub(crate) fn run_ndjson_export(
+    root: PathBuf,
+    config: ScanConfig,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+    out: &mut dyn Write,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut last_emit = Instant::now();
+    let mut pending: u64 = 0;
+    let mut progress = ExportProgress {
+        id: scan_id,
+        lines: 0,
+        total_bytes: 0,
+        truncated_by_budget: false,
+        duration_ms: 0,
+    };
+
+    let end = walk_filtered(&root, &config, &control, &emit, true, &mut |entry| {
+        let WalkedEntry::File {
+            path,
+            size,
+            modified,
+        } = entry
+        else {
+            return Ok(());
+        };
+        let parent = path.parent().map(get_path_string).unwrap_or_default();
+        let line = ExportedFile {
+            path: &get_path_string(&path),
+            size,
+            parent: &parent,
+            modified,
+        };
+        serde_json::to_writer(&mut *out, &line)
+            .map_err(|error| error.to_string())
+            .and_then(|_| out.write_all(b"\n").map_err(|error| error.to_string()))?;
+        progress.lines += 1;
+        progress.total_bytes += size;
+        pending += 1;
+        if should_emit_progress(pending, &last_emit, &config) {
+            progress.duration_ms = start.elapsed().as_millis();
+            emit(ScanEvent::ExportProgress(progress.clone()));
+            pending = 0;
+            last_emit = Instant::now();
+        }
+        Ok(())
+    });
+    if matches!(end, Ok(WalkEnd::Cancelled)) {
+        let _ = out.flush();
+        return Ok(());
+    }
+    let end = end?;
+    out.flush().map_err(|error| error.to_string())?;
+    progress.truncated_by_budget = end == WalkEnd::Truncated;
+    progress.duration_ms = start.elapsed().as_millis();
+    emit(ScanEvent::ExportComplete(progress));
+    Ok(())
+}
+
+pub(crate) fn run_export_cli(args: &[String], path: &str) -> i32 {
+    let options = match get_arg_value(args, "--export-options") {
+        Some(value) => match serde_json::from_str::<ScanOptions>(&value) {
+            Ok(options) => options,
+            Err(error) => {
+                eprintln!("Invalid --export-options: {error}");
+                return 2;
+            }
+        },
+        None => ScanOptions::default(),
+    };
+    let root = PathBuf::from(path);
+    if !root.exists() {
+        eprintln!("Path does not exist: {path}");
+        return 2;
+    }
+    let config = match build_scan_config(&options) {
+        Ok(value) => value,
+        Err(error) => {
+            eprintln!("{error}");
+            return 2;
+        }
+    };
+    // Progress goes to stderr so stdout carries nothing but the NDJSON lines.
+    let emit: ScanEmitter = Arc::new(|event| {
+        if let ScanEvent::ExportComplete(progress) = event {
+            eprintln!(
+                "[export] {} files, {} bytes in {}ms",
+                progress.lines, progress.total_bytes, progress.duration_ms
+            );
+        }
+    });
+    let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+    match run_ndjson_export(root, config, ScanControl::default(), emit, None, &mut out) {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("{error}");
+            1
+        }
+    }
+}
+
+pub(crate) fn run_duplicate_scan(
+    root: PathBuf,
+    config: ScanConfig,
+    control: ScanControl,
+    emit: ScanEmitter,
+    scan_id: Option<String>,
+) -> Result<(), String> {
+    let start = Instant::now();
+    let mut files_by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+
+    let end = walk_filtered(&root, &config, &control, &emit, true, &mut |entry| {
+        // Empty files are trivially identical and reclaim nothing.
+        if let WalkedEntry::File { path, size, .. } = entry {
+            if size > 0 {
+                files_by_size.entry(size).or_default().push(path);
+            }
+        }
+        Ok(())
+    })?;
+    if end == WalkEnd::Cancelled {
+        return Ok(());
+    }
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    let mut hashed_files: u64 = 0;
+    for (size, paths) in files_by_size {
+        if paths.len() < 2 {
+            continue;
+        }
+        let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+        for path in paths {
+            control.wait_while_paused(&emit);
+            if control.is_cancelled() {
+                emit(ScanEvent::Cancelled("Scan cancelled".to_string(), None));
+                return Ok(());
+            }
+            let hash = match hash_file_contents(&path) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            hashed_files += 1;
+            by_hash
+                .entry(hash)
+                .or_default()
+                .push(get_path_string(&path));
+        }
+        for (hash, mut paths) in by_hash {
+            if paths.len() < 2 {
+                continue;
+            }
+            paths.sort();
+            groups.push(DuplicateGroup {
+                hash,
+                size_bytes: size,
+                reclaimable_bytes: size * (paths.len() as u64 - 1),
+                paths,
+            });
+        }
+    }
+    groups.sort_by(|a, b| {
+        b.reclaimable_bytes
+            .cmp(&a.reclaimable_bytes)
+            .then_with(|| a.hash.cmp(&b.hash))
+    });
+
+    emit(ScanEvent::Duplicates(DuplicateReport {
+        id: scan_id,
+        root: get_path_string(&root),
+        reclaimable_bytes: groups.iter().map(|group| group.reclaimable_bytes).sum(),
+        groups,
+        hashed_files,
+        truncated_by_budget: end == WalkEnd::Truncated,
+        duration_ms: start.elapsed().as_millis(),
+    }));
+    Ok(())
+}
+
+/// A listed subfolder, or a file the filters kept, as handed out by [`walk_filtered`].
+enum WalkedEntry {
+    Dir,
+    File {
+        path: PathBuf,
+        size: u64,
+        modified: Option<u64>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum WalkEnd {
+    Finished,
+    Truncated,
+    Cancelled,
+}
+
+/// The walk behind the single-purpose reports: the same filters, trash handling,
+/// `walkDepth`, `maxEntries` budget and throttle as `run_scan`, without the tree.
+/// Cancellation is emitted here, so callers just stop on `WalkEnd::Cancelled`.
+/// Sizes and times are only read when `read_sizes` is set.
+fn walk_filtered(
+    root: &Path,
+    config: &ScanConfig,
+    control: &ScanControl,
+    emit: &ScanEmitter,
+    read_sizes: bool,
+    visit: &mut dyn FnMut(WalkedEntry) -> Result<(), String>,
+) -> Result<WalkEnd, String> {
+    let mut walk = scan_walk(root, config, &HashMap::new()).into_iter();
+    let mut skipped_dirs: HashSet<PathBuf> = HashSet::new();
+    let mut processed: u64 = 0;
+    let mut throttled_until: u64 = 0;
+    loop {
+        control.wait_while_paused(emit);
+        if control.is_cancelled() {
+            emit(ScanEvent::Cancelled("Scan cancelled".to_string(), None));
+            return Ok(WalkEnd::Cancelled);
+        }
+        let entry = match panic::catch_unwind(AssertUnwindSafe(|| walk.next())) {
+            Ok(Some(Ok(item))) => item,
+            Ok(Some(Err(_))) => continue,
+            Ok(None) => return Ok(WalkEnd::Finished),
+            Err(payload) => return Err(describe_panic(payload.as_ref())),
+        };
+        let entry_path = entry.path();
+        let entry_type = entry.file_type();
+        processed += 1;
+        if entry_path
+            .parent()
+            .is_some_and(|parent| is_within_skipped_dir(parent, &skipped_dirs))
+        {
+            if entry_type.is_dir() {
+                skipped_dirs.insert(entry_path);
+            }
+        } else if entry_type.is_dir() {
+            // Walking a trash folder directly still counts it normally.
+            let trash = !config.include_trash && entry.depth > 0 && is_trash_dir(&entry_path);
+            if trash || should_skip_dir(root, &entry_path, &config.filters) {
+                skipped_dirs.insert(entry_path);
+            } else if entry.depth > 0 {
+                visit(WalkedEntry::Dir)?;
+            }
+        } else if entry_type.is_file() {
+            let (size, modified) = if read_sizes {
+                let metadata = entry.metadata();
+                (
+                    metadata.as_ref().map(|meta| meta.len()).unwrap_or(0),
+                    metadata
+                        .as_ref()
+                        .ok()
+                        .and_then(|m| get_time_millis(m.modified())),
+                )
+            } else {
+                (0, None)
+            };
+            if should_include_file(root, &entry_path, size, modified, &config.filters) {
+                visit(WalkedEntry::File {
+                    path: entry_path,
+                    size,
+                    modified,
+                })?;
+            }
+        }
+        if config.max_entries.is_some_and(|limit| processed >= limit) {
+            return Ok(WalkEnd::Truncated);
+        }
+        if let Some(throttle) = &config.throttle {
+            let owed = (processed - throttled_until) / throttle.every_entries;
+            if throttle.sleep_ms > 0 && owed > 0 {
+                thread::sleep(Duration::from_millis(throttle.sleep_ms * owed));
+            }
+            throttled_until += owed * throttle.every_entries;
+        }
+    }
+}
+
+fn is_within_skipped_dir(path: &Path, skipped_dirs: &HashSet<PathBuf>) -> bool {
+    !skipped_dirs.is_empty() && path.ancestors().any(|dir| skipped_dirs.contains(dir))
+}
+
+/// Hidden entries stay skipped as before, except trash folders and the hidden
+/// folders leading to them, which trash detection has to see. Folders in
+/// `unread_dirs` are still yielded but never opened.
+fn scan_walk(root: &Path, config: &ScanConfig, unread_dirs: &HashMap<PathBuf, String>) -> WalkDir {
+    let walk_root = root.to_path_buf();
+    let unread_dirs: HashSet<PathBuf> = unread_dirs.keys().cloned().collect();
+    WalkDir::new(root)
+        .parallelism(config.parallelism.clone())
+        .max_depth(config.walk_depth.unwrap_or(usize::MAX))
+        .skip_hidden(false)
+        .process_read_dir(move |_, _, _, children| {
+            children.retain(|child| {
+                child
+                    .as_ref()
+                    .map_or(true, |entry| is_walked_entry(&walk_root, &entry.path()))
+            });
+            if unread_dirs.is_empty() {
+                return;
+            }
+            for entry in children.iter_mut().flatten() {
+                if unread_dirs.contains(&entry.path()) {
+                    entry.read_children_path = None;
+                }
+            }
+        })
+}
+
+fn is_walked_entry(root: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let hidden = relative
+        .components()
+        .any(|component| component.as_os_str().to_string_lossy().starts_with('.'));
+    if !hidden {
+        return true;
+    }
+    is_trash_route(path)
+        || path
+            .ancestors()
+            .take_while(|ancestor| *ancestor != root)
+            .any(is_trash_dir)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn is_trash_route(path: &Path) -> bool {
+    path.ends_with(".local") || path.ends_with(".local/share")
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+fn is_trash_route(_path: &Path) -> bool {
+    false
+}
+
+fn is_trash_dir(path: &Path) -> bool {
+    let name = match path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => return false,
+    };
+    #[cfg(target_os = "windows")]
+    {
+        ["$recycle.bin", "recycler", "recycled"].contains(&name.to_lowercase().as_str())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        name == ".Trash" || name == ".Trashes"
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        name == ".Trash" || name.starts_with(".Trash-") || path.ends_with(".local/share/Trash")
+    }
+}
+
+fn hash_file_contents(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+pub(crate) fn build_scan_config(options: &ScanOptions) -> Result<ScanConfig, String> {
+    resolve_scan_config(options).map_err(first_option_error)
+}
+
+fn resolve_scan_config(options: &ScanOptions) -> Result<ScanConfig, Vec<ScanOptionError>> {
+    let setting_errors = scan_setting_errors(options);
+    let filters = match build_filter_config(&options.filters) {
+        Ok(filters) if setting_errors.is_empty() => filters,
+        Ok(_) => return Err(setting_errors),
+        Err(mut errors) => {
+            errors.extend(setting_errors);
+            return Err(errors);
+        }
+    };
+    let parallelism = resolve_parallelism(&options.priority_mode);
+    let (default_every, default_interval) = match options.priority_mode {
+        ScanPriorityMode::Performance => (5000, Duration::from_millis(500)),
+        ScanPriorityMode::Balanced => (10000, Duration::from_millis(1000)),
+        ScanPriorityMode::Low => (20000, Duration::from_millis(2000)),
+    };
+    let emit_every = options.emit_every.unwrap_or(default_every);
+    let emit_interval = options
+        .emit_interval_ms
+        .map_or(default_interval, Duration::from_millis);
+    let throttle = match options.throttle_level {
+        ScanThrottleLevel::Off => None,
+        ScanThrottleLevel::Low => Some(ThrottleConfig {
+            every_entries: 1200,
+            sleep_ms: 1,
+        }),
+        ScanThrottleLevel::Medium => Some(ThrottleConfig {
+            every_entries: 600,
+            sleep_ms: 3,
+        }),
+        ScanThrottleLevel::High => Some(ThrottleConfig {
+            every_entries: 250,
+            sleep_ms: 6,
+        }),
+    };
+    let batch_size = options
+        .progress_batch_size
+        .unwrap_or(DEFAULT_PROGRESS_BATCH_SIZE);
+    Ok(ScanConfig {
+        filters,
+        emit_every,
+        emit_interval,
+        batch_size,
+        max_depth: options.max_depth,
+        walk_depth: options.walk_depth,
+        include_trash: options.include_trash,
+        largest_per_category: options.largest_per_category,
+        extension_size_stats: options.extension_size_stats,
+        largest_files_limit: options
+            .largest_files_limit
+            .unwrap_or(DEFAULT_LARGEST_FILES_LIMIT)
+            .clamp(1, MAX_LARGEST_FILES_LIMIT),
+        network_mounts: options.network_mounts,
+        max_entries: options.max_entries,
+        sort_order: options.sort_order,
+        throttle,
+        parallelism,
+    })
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct ScanOptionError {
+    field: String,
+    message: String,
+}
+
+impl ScanOptionError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+pub(crate) fn scan_option_errors(options: &ScanOptions) -> Vec<ScanOptionError> {
+    resolve_scan_config(options).err().unwrap_or_default()
+}
+
+/// For callers that report a single message: the first problem found.
+pub(crate) fn first_option_error(errors: Vec<ScanOptionError>) -> String {
+    errors
+        .into_iter()
+        .next()
+        .map(|error| error.message)
+        .unwrap_or_default()
+}
+
+fn scan_setting_errors(options: &ScanOptions) -> Vec<ScanOptionError> {
+    let mut errors = Vec::new();
+    if options
+        .emit_interval_ms
+        .is_some_and(|interval| interval < MIN_EMIT_INTERVAL_MS)
+    {
+        errors.push(ScanOptionError::new(
+            "emitIntervalMs",
+            format!(
+                "Progress interval must be at least {}ms",
+                MIN_EMIT_INTERVAL_MS
+            ),
+        ));
+    }
+    if options
+        .emit_every
+        .is_some_and(|every| every < MIN_EMIT_EVERY)
+    {
+        errors.push(ScanOptionError::new(
+            "emitEvery",
+            format!(
+                "Entries between progress events must be at least {}",
+                MIN_EMIT_EVERY
+            ),
+        ));
+    }
+    if options.progress_batch_size == Some(0) {
+        errors.push(ScanOptionError::new(
+            "progressBatchSize",
+            "Progress batch size must be greater than zero",
+        ));
+    }
+    if options
+        .largest_per_category
+        .is_some_and(|limit| limit == 0 || limit > MAX_LARGEST_PER_CATEGORY)
+    {
+        errors.push(ScanOptionError::new(
+            "largestPerCategory",
+            format!(
+                "Largest files per category must be between 1 and {}",
+                MAX_LARGEST_PER_CATEGORY
+            ),
+        ));
+    }
+    if options.max_entries == Some(0) {
+        errors.push(ScanOptionError::new(
+            "maxEntries",
+            "Max entries must be greater than zero",
+        ));
+    }
+    errors
+}
+
+fn option_value<T>(
+    errors: &mut Vec<ScanOptionError>,
+    field: &str,
+    result: Result<T, String>,
+) -> Option<T> {
+    result
+        .map_err(|message| errors.push(ScanOptionError::new(field, message)))
+        .ok()
+}
+
+/// Fails with every invalid filter, one error per field.
+pub(crate) fn build_filter_config(
+    filters: &ScanFilters,
+) -> Result<FilterConfig, Vec<ScanOptionError>> {
+    let mut errors = Vec::new();
+    // The raw byte counts win over the string forms when both are given.
+    let min_size_bytes = match (filters.min_size_bytes, &filters.min_size) {
+        (None, Some(value)) => option_value(&mut errors, "filters.minSize", parse_size(value)),
+        (bytes, _) => bytes,
+    };
+    let max_size_bytes = match (filters.max_size_bytes, &filters.max_size) {
+        (None, Some(value)) => option_value(&mut errors, "filters.maxSize", parse_size(value)),
+        (bytes, _) => bytes,
+    };
+    if let (Some(min), Some(max)) = (min_size_bytes, max_size_bytes) {
+        if min > max {
+            let field = if filters.min_size_bytes.is_some() {
+                "filters.minSizeBytes"
+            } else {
+                "filters.minSize"
+            };
+            errors.push(ScanOptionError::new(
+                field,
+                "Min size cannot exceed max size",
+            ));
+        }
+    }
+    if let (Some(min), Some(max)) = (
+        filters.min_modified_timestamp,
+        filters.max_modified_timestamp,
+    ) {
+        if min > max {
+            errors.push(ScanOptionError::new(
+                "filters.minModifiedTimestamp",
+                "Min modified timestamp cannot exceed max modified timestamp",
+            ));
+        }
+    }
+    let case_sensitive = filters.case_sensitive;
+    let unicode = filters.normalize_unicode;
+    let include_regex = filters.include_regex.as_deref().and_then(|pattern| {
+        option_value(
+            &mut errors,
+            "filters.includeRegex",
+            build_regex(pattern, case_sensitive, unicode),
+        )
+    });
+    let exclude_regex = filters.exclude_regex.as_deref().and_then(|pattern| {
+        option_value(
+            &mut errors,
+            "filters.excludeRegex",
+            build_regex(pattern, case_sensitive, unicode),
+        )
+    });
+    let include_globs = option_value(
+        &mut errors,
+        "filters.includeGlobs",
+        build_glob_set(&filters.include_globs, case_sensitive, unicode),
+    )
+    .flatten();
+    let exclude_globs = option_value(
+        &mut errors,
+        "filters.excludeGlobs",
+        build_glob_set(&filters.exclude_globs, case_sensitive, unicode),
+    )
+    .flatten();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let include_extensions =
+        normalize_extensions(&filters.include_extensions, case_sensitive, unicode);
+    let exclude_extensions =
+        normalize_extensions(&filters.exclude_extensions, case_sensitive, unicode);
+    let include_names = normalize_list(&filters.include_names, case_sensitive, unicode);
+    let exclude_names = normalize_list(&filters.exclude_names, case_sensitive, unicode);
+    let include_paths = normalize_list(&filters.include_paths, case_sensitive, unicode);
+    let exclude_paths = normalize_list(&filters.exclude_paths, case_sensitive, unicode);
+    let exclude_path_prefixes =
+        normalize_list(&filters.exclude_path_prefixes, case_sensitive, unicode);
+    let has_include_extensions = !include_extensions.is_empty();
+    let has_exclude_extensions = !exclude_extensions.is_empty();
+    let has_include_names = !include_names.is_empty();
+    let has_exclude_names = !exclude_names.is_empty();
+    let has_include_paths = !include_paths.is_empty();
+    let has_exclude_paths = !exclude_paths.is_empty() || !exclude_path_prefixes.is_empty();
+    let has_include_regex = include_regex.is_some();
+    let has_exclude_regex = exclude_regex.is_some();
+    let has_include_globs = include_globs.is_some();
+    let has_exclude_globs = exclude_globs.is_some();
+    let has_includes = has_include_extensions
+        || has_include_names
+        || has_include_paths
+        || has_include_regex
+        || has_include_globs;
+    let has_dir_excludes =
+        has_exclude_paths || has_exclude_names || has_exclude_regex || has_exclude_globs;
+    let has_file_excludes = has_dir_excludes || has_exclude_extensions;
+    let needs_path =
+        has_exclude_paths || has_include_paths || has_include_regex || has_exclude_regex;
+    let needs_name = has_exclude_names || has_include_names;
+    let needs_extension = has_include_extensions || has_exclude_extensions;
+    let needs_metadata = min_size_bytes.is_some()
+        || max_size_bytes.is_some()
+        || filters.min_modified_timestamp.is_some()
+        || filters.max_modified_timestamp.is_some();
+    Ok(FilterConfig {
+        include_extensions,
+        exclude_extensions,
+        include_names,
+        exclude_names,
+        min_size_bytes,
+        max_size_bytes,
+        min_modified_timestamp: filters.min_modified_timestamp,
+        max_modified_timestamp: filters.max_modified_timestamp,
+        include_regex,
+        exclude_regex,
+        include_paths,
+        exclude_paths,
+        exclude_path_prefixes,
+        include_globs,
+        exclude_globs,
+        case_sensitive,
+        normalize_unicode: unicode,
+        flags: FilterFlags {
+            has_includes,
+            has_file_excludes,
+            has_dir_excludes,
+            needs_path,
+            needs_name,
+            needs_extension,
+            needs_metadata,
+        },
+    })
+}
+
+fn build_regex(pattern: &str, case_sensitive: bool, unicode: bool) -> Result<Regex, String> {
+    RegexBuilder::new(&normalize_text(pattern, unicode))
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+fn build_glob_set(
+    patterns: &[String],
+    case_sensitive: bool,
+    unicode: bool,
+) -> Result<Option<GlobFilter>, String> {
+    let mut builder = GlobSetBuilder::new();
+    let mut added = Vec::new();
+    for pattern in patterns {
+        let trimmed = pattern.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let glob = GlobBuilder::new(&normalize_text(trimmed, unicode))
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|err| format!("Invalid glob pattern '{trimmed}': {err}"))?;
+        builder.add(glob);
+        added.push(trimmed.to_string());
+    }
+    if added.is_empty() {
+        return Ok(None);
+    }
+    let set = builder.build().map_err(|err| err.to_string())?;
+    Ok(Some(GlobFilter {
+        set,
+        patterns: added,
+    }))
+}
+
+fn parse_size(value: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid size: {value}");
+    let trimmed = value.trim();
+    let split = trimmed
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return Err(invalid()),
+    };
+    // Whole numbers skip f64, which can't hold every byte count exactly.
+    if let Ok(whole) = number.parse::<u64>() {
+        return whole.checked_mul(multiplier).ok_or_else(invalid);
+    }
+    let bytes = number.parse::<f64>().map_err(|_| invalid())? * multiplier as f64;
+    if !bytes.is_finite() || bytes > u64::MAX as f64 {
+        return Err(invalid());
+    }
+    Ok(bytes.round() as u64)
+}
+
+fn normalize_extensions(values: &[String], case_sensitive: bool, unicode: bool) -> HashSet<String> {
+    let mut set = HashSet::new();
+    for value in values {
+        let cleaned = fold_text(
+            value.trim().trim_start_matches('.'),
+            case_sensitive,
+            unicode,
+        );
+        if !cleaned.is_empty() {
+            set.insert(cleaned);
+        }
+    }
+    set
+}
+
+fn normalize_list(values: &[String], case_sensitive: bool, unicode: bool) -> Vec<String> {
+    let mut list = Vec::new();
+    for value in values {
+        let cleaned = fold_text(value.trim(), case_sensitive, unicode);
+        if !cleaned.is_empty() {
+            list.push(cleaned);
+        }
+    }
+    list
+}
+
+#[derive(Clone, Copy, Default)]
+struct ProgressCounters {
+    bytes: u64,
+    files: u64,
+    dirs: u64,
+}
+
+impl ProgressCounters {
+    fn of(summary: &ScanSummary) -> Self {
+        Self {
+            bytes: summary.total_bytes,
+            files: summary.file_count,
+            dirs: summary.dir_count,
+        }
+    }
+
+    fn advanced_from(&self, last: &ProgressCounters) -> bool {
+        let grew = self.bytes > last.bytes || self.files > last.files || self.dirs > last.dirs;
+        let shrank = self.bytes < last.bytes || self.files < last.files || self.dirs < last.dirs;
+        grew && !shrank
+    }
+}
+
+fn should_emit_progress(pending: u64, last_emit: &Instant, config: &ScanConfig) -> bool {
+    if pending >= config.emit_every {
+        return true;
+    }
+    last_emit.elapsed() >= config.emit_interval
+}
+
+pub(crate) fn get_path_string(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn get_entry_name_string(path: &Path) -> String {
+    path.file_name()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_else(|| get_path_string(path))
+}
+
+fn should_skip_dir(root: &Path, path: &Path, filters: &FilterConfig) -> bool {
+    dir_exclusion(root, path, filters).is_some()
+}
+
+fn dir_exclusion<'a>(
+    root: &Path,
+    path: &Path,
+    filters: &'a FilterConfig,
+) -> Option<FilterDecision<'a>> {
+    if path == root {
+        return None;
+    }
+    if !filters.flags.has_dir_excludes {
+        return None;
+    }
+    let path_str = if filters.flags.needs_path {
+        Some(filters.fold(&path.to_string_lossy()))
+    } else {
+        None
+    };
+    let name_str = if filters.flags.needs_name {
+        Some(filters.fold(&get_entry_name_string(path)))
+    } else {
+        None
+    };
+    if let Some(path_value) = path_str.as_deref() {
+        if let Some(term) = matching_regex(path_value, &filters.exclude_regex) {
+            return Some(FilterDecision::excluded_by("excludeRegex", term));
+        }
+        if let Some(term) = contained_term(path_value, &filters.exclude_paths) {
+            return Some(FilterDecision::excluded_by("excludePaths", term));
+        }
+        if let Some(term) = path_prefix_term(path_value, &filters.exclude_path_prefixes) {
+            return Some(FilterDecision::excluded_by("excludePathPrefixes", term));
+        }
+    }
+    if let Some(name_value) = name_str.as_deref() {
+        if let Some(term) = contained_term(name_value, &filters.exclude_names) {
+            return Some(FilterDecision::excluded_by("excludeNames", term));
+        }
+    }
+    matching_glob(
+        root,
+        path,
+        &filters.exclude_globs,
+        filters.normalize_unicode,
+    )
+    .map(|term| FilterDecision::excluded_by("excludeGlobs", term))
+}
+
+fn should_include_file(
+    root: &Path,
+    path: &Path,
+    size_bytes: u64,
+    modified: Option<u64>,
+    filters: &FilterConfig,
+) -> bool {
+    file_decision(root, path, size_bytes, modified, filters).included
+}
+
+fn file_decision<'a>(
+    root: &Path,
+    path: &Path,
+    size_bytes: u64,
+    modified: Option<u64>,
+    filters: &'a FilterConfig,
+) -> FilterDecision<'a> {
+    if let Some(min_size) = filters.min_size_bytes {
+        if size_bytes < min_size {
+            return FilterDecision::excluded("minSizeBytes");
+        }
+    }
+    if let Some(max_size) = filters.max_size_bytes {
+        if size_bytes > max_size {
+            return FilterDecision::excluded("maxSizeBytes");
+        }
+    }
+    if let Some(min_ts) = filters.min_modified_timestamp {
+        if modified.map_or(false, |ts| ts < min_ts) {
+            return FilterDecision::excluded("minModifiedTimestamp");
+        }
+    }
+    if let Some(max_ts) = filters.max_modified_timestamp {
+        if modified.map_or(false, |ts| ts > max_ts) {
+            return FilterDecision::excluded("maxModifiedTimestamp");
+        }
+    }
+    let path_str = if filters.flags.needs_path {
+        Some(filters.fold(&path.to_string_lossy()))
+    } else {
+        None
+    };
+    let name_str = if filters.flags.needs_name {
+        Some(filters.fold(&get_entry_name_string(path)))
+    } else {
+        None
+    };
+    let ext = if filters.flags.needs_extension {
+        path.extension()
+            .and_then(|value| value.to_str())
+            .map(|value| filters.fold(value))
+    } else {
+        None
+    };
+    if filters.flags.has_file_excludes {
+        if let Some(path_value) = path_str.as_deref() {
+            if let Some(term) = matching_regex(path_value, &filters.exclude_regex) {
+                return FilterDecision::excluded_by("excludeRegex", term);
+            }
+            if let Some(term) = contained_term(path_value, &filters.exclude_paths) {
+                return FilterDecision::excluded_by("excludePaths", term);
+            }
+            if let Some(term) = path_prefix_term(path_value, &filters.exclude_path_prefixes) {
+                return FilterDecision::excluded_by("excludePathPrefixes", term);
+            }
+        }
+        if let Some(name_value) = name_str.as_deref() {
+            if let Some(term) = contained_term(name_value, &filters.exclude_names) {
+                return FilterDecision::excluded_by("excludeNames", term);
+            }
+        }
+        if let Some(ext_value) = ext.as_deref() {
+            if let Some(term) = matching_extension(ext_value, &filters.exclude_extensions) {
+                return FilterDecision::excluded_by("excludeExtensions", term);
+            }
+        }
+        if let Some(term) = matching_glob(
+            root,
+            path,
+            &filters.exclude_globs,
+            filters.normalize_unicode,
+        ) {
+            return FilterDecision::excluded_by("excludeGlobs", term);
+        }
+    }
+
+    if !filters.flags.has_includes {
+        return FilterDecision::included("noIncludes");
+    }
+
+    if let Some(path_value) = path_str.as_deref() {
+        if let Some(term) = matching_regex(path_value, &filters.include_regex) {
+            return FilterDecision::included_by("includeRegex", term);
+        }
+        if let Some(term) = contained_term(path_value, &filters.include_paths) {
+            return FilterDecision::included_by("includePaths", term);
+        }
+    }
+    if let Some(name_value) = name_str.as_deref() {
+        if let Some(term) = contained_term(name_value, &filters.include_names) {
+            return FilterDecision::included_by("includeNames", term);
+        }
+    }
+    if let Some(term) = matching_glob(
+        root,
+        path,
+        &filters.include_globs,
+        filters.normalize_unicode,
+    ) {
+        return FilterDecision::included_by("includeGlobs", term);
+    }
+    if let Some(ext_value) = ext.as_deref() {
+        if let Some(term) = matching_extension(ext_value, &filters.include_extensions) {
+            return FilterDecision::included_by("includeExtensions", term);
+        }
+    }
+
+    FilterDecision::excluded("noMatchingInclude")
+}
+
+pub(crate) fn explain_file_filters(
+    root: &Path,
+    path: &Path,
+    size_bytes: u64,
+    modified: Option<u64>,
+    filters: &FilterConfig,
+) -> FileExplanation {
+    let mut folders: Vec<&Path> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|ancestor| *ancestor != root && ancestor.starts_with(root))
+        .collect();
+    folders.reverse();
+    let excluded_folder = folders.into_iter().find_map(|folder| {
+        dir_exclusion(root, folder, filters).map(|decision| (get_path_string(folder), decision))
+    });
+    let (decision, folder) = match excluded_folder {
+        Some((folder, decision)) => (decision, Some(folder)),
+        None => (
+            file_decision(root, path, size_bytes, modified, filters),
+            None,
+        ),
+    };
+    FileExplanation {
+        path: get_path_string(path),
+        size_bytes,
+        modified,
+        included: decision.included,
+        rule: decision.rule,
+        term: decision.term.map(str::to_string),
+        folder,
+    }
+}
+
+pub(crate) fn resolve_parallelism(priority_mode: &ScanPriorityMode) -> Parallelism {
+    let available = thread::available_parallelism()
+        .map(|value| value.get())
+        .unwrap_or(1);
+    let threads = match priority_mode {
+        ScanPriorityMode::Performance => available,
+        ScanPriorityMode::Balanced => (available + 1) / 2,
+        ScanPriorityMode::Low => 1,
+    };
+    if threads <= 1 {
+        return Parallelism::Serial;
+    }
+    Parallelism::RayonNewPool(threads)
+}
+
+fn matching_regex<'a>(value: &str, regex: &'a Option<Regex>) -> Option<&'a str> {
+    regex
+        .as_ref()
+        .filter(|pattern| pattern.is_match(value))
+        .map(Regex::as_str)
+}
+
+fn matching_glob<'a>(
+    root: &Path,
+    path: &Path,
+    globs: &'a Option<GlobFilter>,
+    unicode: bool,
+) -> Option<&'a str> {
+    let globs = globs.as_ref()?;
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let matched = if unicode {
+        globs
+            .set
+            .matches(normalize_text(&relative.to_string_lossy(), true).as_str())
+    } else {
+        globs.set.matches(relative)
+    };
+    matched.first().map(|index| globs.patterns[*index].as_str())
+}
+
+fn contained_term<'a>(path: &str, values: &'a [String]) -> Option<&'a str> {
+    values
+        .iter()
+        .find(|value| !value.is_empty() && path.contains(value.as_str()))
+        .map(String::as_str)
+}
+
+fn path_prefix_term<'a>(path: &str, prefixes: &'a [String]) -> Option<&'a str> {
+    let path = Path::new(path);
+    prefixes
+        .iter()
+        .find(|prefix| path.starts_with(prefix))
+        .map(String::as_str)
+}
+
+fn matching_extension<'a>(ext: &str, extensions: &'a HashSet<String>) -> Option<&'a str> {
+    extensions.get(ext).map(String::as_str)
+}
+
+fn fold_text(value: &str, case_sensitive: bool, unicode: bool) -> String {
+    let value = normalize_text(value, unicode);
+    if case_sensitive {
+        value
+    } else {
+        value.to_lowercase()
+    }
+}
+
+fn normalize_text(value: &str, unicode: bool) -> String {
+    if unicode {
+        value.nfc().collect()
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ScanResultFile {
+    pub(crate) format_version: u32,
+    pub(crate) app_version: String,
+    pub(crate) saved_at: u64,
+    pub(crate) summary: ScanSummary,
+}
+
+pub(crate) fn summary_contains_path(node: &ScanNode, path: &str) -> bool {
+    if node.path == path || node.files.iter().any(|file| file.path == path) {
+        return true;
+    }
+    node.children
+        .iter()
+        .any(|child| summary_contains_path(child, path))
+}
+
+fn build_summary(
+    roots: &[PathBuf],
+    root_ids: &[Option<usize>],
+    tree: &ScanTree,
+    largest_files: &[ScanFile],
+    extensions: &HashMap<String, ExtensionTotals>,
+    start: Instant,
+    scan_id: Option<String>,
+    compact: bool,
+    order: SortOrder,
+    max_children: Option<usize>,
+) -> ScanSummary {
+    let (max_depth, max_files) = if compact {
+        (Some(1), Some(0))
+    } else {
+        (None, None)
+    };
+    let mut root_nodes: Vec<ScanNode> = roots
+        .iter()
+        .zip(root_ids)
+        .map(|(root, id)| {
+            build_node(
+                tree,
+                *id,
+                root,
+                0,
+                max_depth,
+                max_files,
+                order,
+                max_children,
+            )
+        })
+        .collect();
+    let root_node = if root_nodes.len() == 1 {
+        root_nodes.remove(0)
+    } else {
+        build_combined_root(root_nodes, order)
+    };
+    // Only the final summary carries empty and largest directories; progress
+    // updates skip the walks.
+    let mut empty_dirs = Vec::new();
+    let mut largest_dirs = Vec::new();
+    if !compact {
+        for (root, id) in roots.iter().zip(root_ids) {
+            collect_empty_dirs(tree, *id, root, true, &mut empty_dirs);
+            collect_largest_dirs(tree, *id, root, true, &mut largest_dirs);
+        }
+        empty_dirs.sort();
+    }
+    ScanSummary {
+        id: scan_id,
+        total_bytes: root_node.size_bytes,
+        file_count: root_node.file_count,
+        dir_count: root_node.dir_count,
+        root: root_node,
+        largest_files: largest_files.to_vec(),
+        largest_dirs,
+        by_extension: build_extension_stats(extensions, MAX_EXTENSION_STATS),
+        duration_ms: start.elapsed().as_millis(),
+        entries_per_sec: 0.0,
+        eta_ms: None,
+        errors: Vec::new(),
+        empty_dirs,
+        trash_bytes: 0,
+        largest_by_category: HashMap::new(),
+        skipped_network_mounts: Vec::new(),
+        zero_byte_count: 0,
+        zero_byte_files: Vec::new(),
+        truncated_by_budget: false,
+        filter_hits: Vec::new(),
+    }
+}
+
+fn collect_empty_dirs(
+    tree: &ScanTree,
+    id: Option<usize>,
+    path: &Path,
+    is_root: bool,
+    empty_dirs: &mut Vec<String>,
+) -> bool {
+    let dir = id.map(|id| &tree.dirs[id]);
+    let mut is_empty =
+        !dir.is_some_and(|dir| dir.stats.direct_files > 0 || dir.stats.unlisted_entries > 0);
+    if let Some(dir) = dir {
+        for &child in &dir.children {
+            let child_path = path.join(&tree.dirs[child].name);
+            if !collect_empty_dirs(tree, Some(child), &child_path, false, empty_dirs) {
+                is_empty = false;
+            }
+        }
+    }
+    if is_empty && !is_root {
+        empty_dirs.push(get_path_string(path));
+    }
+    is_empty
+}
+
+fn collect_largest_dirs(
+    tree: &ScanTree,
+    id: Option<usize>,
+    path: &Path,
+    is_root: bool,
+    largest_dirs: &mut Vec<DirStat>,
+) -> (u64, u64) {
+    let Some(dir) = id.map(|id| &tree.dirs[id]) else {
+        return (0, 0);
+    };
+    let mut size_bytes = dir.stats.direct_bytes;
+    let mut file_count = dir.stats.direct_files;
+    for &child in &dir.children {
+        let child_path = path.join(&tree.dirs[child].name);
+        let (bytes, files) =
+            collect_largest_dirs(tree, Some(child), &child_path, false, largest_dirs);
+        size_bytes += bytes;
+        file_count += files;
+    }
+    if !is_root {
+        update_largest_dirs(largest_dirs, path, size_bytes, file_count);
+    }
+    (size_bytes, file_count)
+}
+
+fn update_largest_dirs(
+    largest_dirs: &mut Vec<DirStat>,
+    path: &Path,
+    size_bytes: u64,
+    file_count: u64,
+) {
+    if size_bytes == 0 {
+        return;
+    }
+    if largest_dirs.len() >= MAX_LARGEST_DIRS
+        && largest_dirs
+            .last()
+            .is_some_and(|smallest| size_bytes <= smallest.size_bytes)
+    {
+        return;
+    }
+    largest_dirs.push(DirStat {
+        path: get_path_string(path),
+        size_bytes,
+        file_count,
+    });
+    largest_dirs.sort_by_key(|dir| std::cmp::Reverse(dir.size_bytes));
+    largest_dirs.truncate(MAX_LARGEST_DIRS);
+}
+
+#[derive(Default)]
+struct ExtensionTotals {
+    total_bytes: u64,
+    file_count: u64,
+    median: Option<MedianEstimator>,
+}
+
+#[derive(Default)]
+struct MedianEstimator {
+    count: usize,
+    heights: [f64; 5],
+    positions: [f64; 5],
+}
+
+impl MedianEstimator {
+    const TARGETS: [f64; 5] = [0.0, 0.25, 0.5, 0.75, 1.0];
+
+    fn add(&mut self, value: f64) {
+        if self.count < 5 {
+            self.heights[self.count] = value;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(f64::total_cmp);
+                self.positions = [1.0, 2.0, 3.0, 4.0, 5.0];
+            }
+            return;
+        }
+        self.count += 1;
+        let cell = if value < self.heights[0] {
+            self.heights[0] = value;
+            0
+        } else if value >= self.heights[4] {
+            self.heights[4] = value;
+            3
+        } else {
+            (1..5).find(|&i| value < self.heights[i]).unwrap_or(4) - 1
+        };
+        for position in &mut self.positions[cell + 1..] {
+            *position += 1.0;
+        }
+        let last = (self.count - 1) as f64;
+        for i in 1..4 {
+            let drift = 1.0 + last * Self::TARGETS[i] - self.positions[i];
+            let room_above = self.positions[i + 1] - self.positions[i];
+            let room_below = self.positions[i - 1] - self.positions[i];
+            if (drift >= 1.0 && room_above > 1.0) || (drift <= -1.0 && room_below < -1.0) {
+                let step = drift.signum();
+                let parabolic = self.parabolic(i, step);
+                self.heights[i] =
+                    if self.heights[i - 1] < parabolic && parabolic < self.heights[i + 1] {
+                        parabolic
+                    } else {
+                        self.linear(i, step)
+                    };
+                self.positions[i] += step;
+            }
+        }
+    }
+
+    fn parabolic(&self, i: usize, step: f64) -> f64 {
+        let (h, p) = (&self.heights, &self.positions);
+        h[i] + step / (p[i + 1] - p[i - 1])
+            * ((p[i] - p[i - 1] + step) * (h[i + 1] - h[i]) / (p[i + 1] - p[i])
+                + (p[i + 1] - p[i] - step) * (h[i] - h[i - 1]) / (p[i] - p[i - 1]))
+    }
+
+    fn linear(&self, i: usize, step: f64) -> f64 {
+        let other = if step > 0.0 { i + 1 } else { i - 1 };
+        self.heights[i]
+            + step * (self.heights[other] - self.heights[i])
+                / (self.positions[other] - self.positions[i])
+    }
+
+    fn median(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            1..=4 => {
+                let mut seen = self.heights[..self.count].to_vec();
+                seen.sort_by(f64::total_cmp);
+                let mid = self.count / 2;
+                Some(if self.count.is_multiple_of(2) {
+                    (seen[mid - 1] + seen[mid]) / 2.0
+                } else {
+                    seen[mid]
+                })
+            }
+            _ => Some(self.heights[2]),
+        }
+    }
+}
+
+fn build_extension_stats(
+    extensions: &HashMap<String, ExtensionTotals>,
+    limit: usize,
+) -> Vec<ExtensionStat> {
+    let mut list: Vec<ExtensionStat> = extensions
+        .iter()
+        .map(|(ext, totals)| ExtensionStat {
+            ext: ext.clone(),
+            total_bytes: totals.total_bytes,
+            file_count: totals.file_count,
+            average_bytes: totals
+                .median
+                .as_ref()
+                .map(|_| totals.total_bytes / totals.file_count.max(1)),
+            median_bytes: totals
+                .median
+                .as_ref()
+                .and_then(MedianEstimator::median)
+                .map(|median| median.round() as u64),
+        })
+        .collect();
+    list.sort_by(|a, b| {
+        b.total_bytes
+            .cmp(&a.total_bytes)
+            .then_with(|| a.ext.cmp(&b.ext))
+    });
+    list.truncate(limit);
+    list
+}
+
+fn get_extension_key(path: &Path) -> String {
+    path.extension()
+        .map(|value| value.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+fn get_file_category(ext: &str) -> &'static str {
+    FILE_CATEGORIES
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&ext))
+        .map(|(category, _)| *category)
+        .unwrap_or("other")
+}
+
+fn detect_file_kind(path: &Path) -> Option<String> {
+    let mut sample = vec![0u8; KIND_SNIFF_BYTES];
+    let read = fs::File::open(path)
+        .and_then(|mut file| file.read(&mut sample))
+        .ok()?;
+    sample.truncate(read);
+    let kind = match infer::get(&sample).map(|found| found.matcher_type()) {
+        Some(MatcherType::Image) => "image",
+        Some(MatcherType::Video) => "video",
+        Some(MatcherType::Audio) => "audio",
+        Some(MatcherType::Archive) => "archive",
+        Some(MatcherType::Doc | MatcherType::Book) => "document",
+        Some(MatcherType::App | MatcherType::Font | MatcherType::Custom) => "binary",
+        Some(MatcherType::Text) | None => {
+            let ext = get_extension_key(path);
+            match get_file_category(&ext) {
+                "images" => "image",
+                "videos" => "video",
+                "audio" => "audio",
+                "documents" => "document",
+                "archives" => "archive",
+                _ if CODE_EXTENSIONS.contains(&ext.as_str()) => "code",
+                _ if sample.contains(&0) => "binary",
+                _ => "document",
+            }
+        }
+    };
+    Some(kind.to_string())
+}
+
+fn sort_nodes(nodes: &mut [ScanNode], order: SortOrder) {
+    nodes.sort_by(|a, b| {
+        let primary = match order {
+            SortOrder::SizeDesc => b.size_bytes.cmp(&a.size_bytes),
+            SortOrder::SizeAsc => a.size_bytes.cmp(&b.size_bytes),
+            SortOrder::NameAsc => std::cmp::Ordering::Equal,
+            SortOrder::NameDesc => b.name.cmp(&a.name),
+            SortOrder::FileCountDesc => b.file_count.cmp(&a.file_count),
+        };
+        primary.then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+fn build_combined_root(mut nodes: Vec<ScanNode>, order: SortOrder) -> ScanNode {
+    sort_nodes(&mut nodes, order);
+    ScanNode {
+        path: String::new(),
+        name: format!("{} locations", nodes.len()),
+        size_bytes: nodes.iter().map(|node| node.size_bytes).sum(),
+        file_count: nodes.iter().map(|node| node.file_count).sum(),
+        dir_count: nodes.iter().map(|node| 1 + node.dir_count).sum(),
+        files: Vec::new(),
+        child_count: nodes.len() as u64,
+        has_children: !nodes.is_empty(),
+        children: nodes,
+        truncated: false,
+    }
+}
+
+fn update_largest_files(
+    largest_files: &mut Vec<ScanFile>,
+    path: &Path,
+    size_bytes: u64,
+    modified: Option<u64>,
+    limit: usize,
+) {
+    if size_bytes == 0 {
+        return;
+    }
+    let name = get_entry_name_string(path);
+    if largest_files.len() < limit {
+        largest_files.push(ScanFile {
+            path: get_path_string(path),
+            name,
+            size_bytes,
+            modified,
+            kind: None,
+        });
+        largest_files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+        return;
+    }
+    let smallest = largest_files
+        .last()
+        .map(|file| file.size_bytes)
+        .unwrap_or(0);
+    if size_bytes <= smallest {
+        return;
+    }
+    largest_files.push(ScanFile {
+        path: get_path_string(path),
+        name,
+        size_bytes,
+        modified,
+        kind: None,
+    });
+    largest_files.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+    largest_files.truncate(limit);
+}
+
+fn update_recent_files(
+    recent_files: &mut Vec<ScanFile>,
+    path: &Path,
+    size_bytes: u64,
+    modified: u64,
+    limit: usize,
+) {
+    if recent_files.len() >= limit
+        && recent_files
+            .last()
+            .is_some_and(|file| file.modified.unwrap_or(0) >= modified)
+    {
+        return;
+    }
+    let position = recent_files.partition_point(|file| file.modified.unwrap_or(0) >= modified);
+    recent_files.insert(
+        position,
+        ScanFile {
+            path: get_path_string(path),
+            name: get_entry_name_string(path),
+            size_bytes,
+            modified: Some(modified),
+            kind: None,
+        },
+    );
+    recent_files.truncate(limit);
+}
+
+fn build_node(
+    tree: &ScanTree,
+    id: Option<usize>,
+    path: &Path,
+    depth: usize,
+    max_depth: Option<usize>,
+    max_files: Option<usize>,
+    order: SortOrder,
+    max_children: Option<usize>,
+) -> ScanNode {
+    let mut size_bytes = 0;
+    let mut file_count = 0;
+    let mut dir_count = 0;
+    let mut truncated = false;
+    let mut nodes: Vec<ScanNode> = Vec::new();
+    let dir = id.map(|id| &tree.dirs[id]);
+
+    if let Some(dir) = dir {
+        size_bytes += dir.stats.direct_bytes;
+        file_count += dir.stats.direct_files;
+        dir_count += dir.stats.pruned_dirs;
+        truncated = dir.stats.truncated;
+
+        for &child in &dir.children {
+            let child_node = build_node(
+                tree,
+                Some(child),
+                &path.join(&tree.dirs[child].name),
+                depth + 1,
+                max_depth,
+                max_files,
+                order,
+                max_children,
+            );
+            size_bytes += child_node.size_bytes;
+            file_count += child_node.file_count;
+            dir_count += 1 + child_node.dir_count;
+
+            if max_depth.map_or(true, |max| depth < max) {
+                nodes.push(child_node);
+            }
+        }
+    }
+
+    sort_nodes(&mut nodes, order);
+    if let Some(limit) = max_children {
+        if nodes.len() > limit {
+            nodes.truncate(limit);
+        }
+    }
+
+    let mut kept: Vec<&TreeFile> = dir
+        .map(|dir| dir.files.iter().collect())
+        .unwrap_or_default();
+    if let Some(limit) = max_files {
+        if kept.len() > limit {
+            kept.sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes));
+            kept.truncate(limit);
+        }
+    }
+    let files = kept
+        .into_iter()
+        .map(|file| ScanFile {
+            path: get_path_string(&path.join(&file.name)),
+            name: file.name.clone(),
+            size_bytes: file.size_bytes,
+            modified: file.modified,
+            kind: None,
+        })
+        .collect();
+
+    let child_count = dir.map_or(0, |dir| {
+        (dir.children.len() + dir.files.len()) as u64 + dir.stats.folded_children
+    });
+    ScanNode {
+        path: get_path_string(path),
+        name: get_entry_name_string(path),
+        size_bytes,
+        file_count,
+        dir_count,
+        files,
+        children: nodes,
+        truncated,
+        child_count,
+        has_children: child_count > 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn filters(filters: ScanFilters) -> FilterConfig {
+        build_filter_config(&filters).unwrap()
+    }
+
+    fn includes(filters: &FilterConfig, path: &str, size_bytes: u64) -> bool {
+        should_include_file(
+            Path::new("/data"),
+            Path::new(path),
+            size_bytes,
+            Some(1_000),
+            filters,
+        )
+    }
+
+    #[test]
+    fn window_scans_are_replaced_and_released_by_their_own_scan() {
+        let scans = ScanCancellation(Mutex::new(HashMap::new()));
+        let running_id = |label: &str| {
+            scans
+                .0
+                .lock()
+                .unwrap()
+                .get(label)
+                .map(|scan| scan.id.clone())
+        };
+        let first = ScanControl::default();
+        scans.claim("main", &first, Some("a".to_string())).unwrap();
+        assert_eq!(running_id("main"), Some(Some("a".to_string())));
+
+        let second = ScanControl::default();
+        scans.claim("main", &second, None).unwrap();
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert_eq!(running_id("main"), Some(None));
+
+        let other = ScanControl::default();
+        scans.claim("other", &other, Some("b".to_string())).unwrap();
+        assert!(!second.is_cancelled());
+
+        // The replaced scan finishing late must not drop its successor.
+        scans.release("main", &first);
+        assert_eq!(running_id("main"), Some(None));
+        scans.cancel("main").unwrap();
+        assert!(second.is_cancelled());
+        assert!(!other.is_cancelled());
+        scans.release("main", &second);
+        assert_eq!(running_id("main"), None);
+        assert_eq!(running_id("other"), Some(Some("b".to_string())));
+        scans.release("other", &other);
+        assert!(scans.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_cancelled_scan_reports_the_same_extras_as_a_completed_one() {
+        let dir = tempfile::tempdir().unwrap();
+        for index in 0..300 {
+            let ext = if index % 2 == 0 { "log" } else { "txt" };
+            fs::write(dir.path().join(format!("{index}.{ext}")), "x").unwrap();
+        }
+        let options = ScanOptions {
+            priority_mode: ScanPriorityMode::Low,
+            emit_every: Some(MIN_EMIT_EVERY),
+            filters: ScanFilters {
+                exclude_extensions: strings(&["log"]),
+                ..ScanFilters::default()
+            },
+            ..ScanOptions::default()
+        };
+        let control = ScanControl::default();
+        let cancelled = Arc::new(Mutex::new(None));
+        let emit: ScanEmitter = {
+            let control = control.clone();
+            let cancelled = Arc::clone(&cancelled);
+            Arc::new(move |event| match event {
+                ScanEvent::Progress(_) => control.cancel.store(true, Ordering::SeqCst),
+                ScanEvent::Cancelled(_, summary) => *cancelled.lock().unwrap() = summary,
+                _ => {}
+            })
+        };
+        run_scan(
+            vec![dir.path().to_path_buf()],
+            build_scan_config(&options).unwrap(),
+            control,
+            emit,
+            None,
+        )
+        .unwrap();
+
+        let summary = cancelled.lock().unwrap().take().unwrap();
+        assert!(summary.file_count < 150);
+        assert_eq!(summary.filter_hits.len(), 1);
+        assert_eq!(summary.filter_hits[0].field, "excludeExtensions");
+        assert!(summary.filter_hits[0].hits > 0);
+        assert!(!summary.truncated_by_budget);
+    }
+
+    fn complete_scan(root: &Path, options: &ScanOptions) -> ScanSummary {
+        let complete = Arc::new(Mutex::new(None));
+        let emit: ScanEmitter = {
+            let complete = Arc::clone(&complete);
+            Arc::new(move |event| {
+                if let ScanEvent::Complete(summary) = event {
+                    *complete.lock().unwrap() = Some(summary);
+                }
+            })
+        };
+        run_scan(
+            vec![root.to_path_buf()],
+            build_scan_config(options).unwrap(),
+            ScanControl::default(),
+            emit,
+            None,
+        )
+        .unwrap();
+        let summary = complete.lock().unwrap().take();
+        summary.unwrap()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[test]
+    fn hidden_trash_folders_are_measured_but_other_hidden_entries_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".Trash-1000")).unwrap();
+        fs::write(dir.path().join(".Trash-1000").join("file"), "deleted").unwrap();
+        fs::create_dir(dir.path().join(".cache")).unwrap();
+        fs::write(dir.path().join(".cache").join("blob"), "cached").unwrap();
+        fs::write(dir.path().join("kept.txt"), "kept").unwrap();
+
+        let summary = complete_scan(dir.path(), &ScanOptions::default());
+        assert!(summary.trash_bytes > 0);
+        assert_eq!(summary.file_count, 1);
+
+        let with_trash = complete_scan(
+            dir.path(),
+            &ScanOptions {
+                include_trash: true,
+                ..ScanOptions::default()
+            },
+        );
+        assert_eq!(with_trash.file_count, 2);
+    }
+
+    fn count_entries(root: &Path, options: &ScanOptions) -> EntryCount {
+        let count = Arc::new(Mutex::new(None));
+        let emit: ScanEmitter = {
+            let count = Arc::clone(&count);
+            Arc::new(move |event| {
+                if let ScanEvent::CountComplete(value) = event {
+                    *count.lock().unwrap() = Some(value);
+                }
+            })
+        };
+        run_entry_count(
+            root.to_path_buf(),
+            build_scan_config(options).unwrap(),
+            ScanControl::default(),
+            emit,
+            None,
+        )
+        .unwrap();
+        let value = count.lock().unwrap().take();
+        value.unwrap()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    #[test]
+    fn entry_counts_skip_trash_and_walk_depth_like_a_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join(".Trash-1000")).unwrap();
+        fs::write(dir.path().join(".Trash-1000").join("file"), "deleted").unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub").join("deep.txt"), "deep").unwrap();
+        fs::write(dir.path().join("top.txt"), "top").unwrap();
+
+        let count = count_entries(dir.path(), &ScanOptions::default());
+        assert_eq!((count.file_count, count.dir_count), (2, 1));
+        assert!(!count.truncated_by_budget);
+
+        let shallow = count_entries(
+            dir.path(),
+            &ScanOptions {
+                walk_depth: Some(1),
+                ..ScanOptions::default()
+            },
+        );
+        assert_eq!((shallow.file_count, shallow.dir_count), (1, 1));
+
+        let budgeted = count_entries(
+            dir.path(),
+            &ScanOptions {
+                max_entries: Some(2),
+                ..ScanOptions::default()
+            },
+        );
+        assert!(budgeted.truncated_by_budget);
+        assert!(budgeted.file_count + budgeted.dir_count < 2);
+    }
+
+    #[test]
+    fn empty_files_do_not_dilute_the_compression_sample() {
+        let mut sampler = CompressionSampler::new(10);
+        for index in 0..10 {
+            sampler.offer(PathBuf::from(format!("early-{index}")), 1);
+        }
+        for index in 0..100 {
+            for _ in 0..99 {
+                sampler.offer(PathBuf::from("empty"), 0);
+            }
+            sampler.offer(PathBuf::from(format!("late-{index}")), 1);
+        }
+        assert_eq!(sampler.total_files, 10_010);
+        assert_eq!(sampler.total_bytes, 110);
+        let late = sampler
+            .reservoir
+            .iter()
+            .filter(|(path, _)| path.to_string_lossy().starts_with("late-"))
+            .count();
+        // 100 of the 110 non-empty files came late, so most of the sample should too.
+        assert!(late >= 5, "only {late} of 10 samples came from late files");
+    }
+
+    #[test]
+    fn skipped_network_mounts_are_listed_but_never_opened() {
+        let dir = tempfile::tempdir().unwrap();
+        let mount = dir.path().join("share");
+        fs::create_dir_all(mount.join("deep")).unwrap();
+        fs::write(mount.join("deep").join("file"), "remote").unwrap();
+        fs::write(dir.path().join("local.txt"), "local").unwrap();
+        let config = build_scan_config(&ScanOptions::default()).unwrap();
+        let mounts = HashMap::from([(mount.clone(), "nfs".to_string())]);
+
+        let seen: Vec<PathBuf> = scan_walk(dir.path(), &config, &mounts)
+            .into_iter()
+            .flatten()
+            .map(|entry| entry.path())
+            .collect();
+        assert!(seen.contains(&mount));
+        assert!(seen.contains(&dir.path().join("local.txt")));
+        assert!(!seen
+            .iter()
+            .any(|path| path.starts_with(&mount) && *path != mount));
+    }
+
+    #[test]
+    fn default_options_build_the_balanced_config() {
+        let config = build_scan_config(&ScanOptions::default()).unwrap();
+        assert_eq!(config.emit_every, 10000);
+        assert_eq!(config.emit_interval, Duration::from_millis(1000));
+        assert_eq!(config.batch_size, DEFAULT_PROGRESS_BATCH_SIZE);
+        assert_eq!(config.largest_files_limit, DEFAULT_LARGEST_FILES_LIMIT);
+        assert!(config.throttle.is_none());
+        assert!(!config.filters.flags.has_includes);
+        assert!(!config.filters.flags.has_file_excludes);
+        assert!(!config.filters.flags.needs_metadata);
+    }
+
+    #[test]
+    fn scan_config_applies_overrides_and_clamps_limits() {
+        let options = ScanOptions {
+            priority_mode: ScanPriorityMode::Low,
+            throttle_level: ScanThrottleLevel::High,
+            emit_every: Some(500),
+            largest_files_limit: Some(5000),
+            ..ScanOptions::default()
+        };
+        let config = build_scan_config(&options).unwrap();
+        assert_eq!(config.emit_every, 500);
+        assert_eq!(config.emit_interval, Duration::from_millis(2000));
+        assert_eq!(config.largest_files_limit, MAX_LARGEST_FILES_LIMIT);
+        let throttle = config.throttle.unwrap();
+        assert_eq!((throttle.every_entries, throttle.sleep_ms), (250, 6));
+        assert!(matches!(config.parallelism, Parallelism::Serial));
+    }
+
+    #[test]
+    fn scan_config_rejects_invalid_options() {
+        let invalid = [
+            ScanOptions {
+                emit_interval_ms: Some(MIN_EMIT_INTERVAL_MS - 1),
+                ..ScanOptions::default()
+            },
+            ScanOptions {
+                progress_batch_size: Some(0),
+                ..ScanOptions::default()
+            },
+            ScanOptions {
+                max_entries: Some(0),
+                ..ScanOptions::default()
+            },
+            ScanOptions {
+                filters: ScanFilters {
+                    include_regex: Some("(".to_string()),
+                    ..ScanFilters::default()
+                },
+                ..ScanOptions::default()
+            },
+            ScanOptions {
+                filters: ScanFilters {
+                    min_size: Some("ten".to_string()),
+                    ..ScanFilters::default()
+                },
+                ..ScanOptions::default()
+            },
+        ];
+        for options in &invalid {
+            assert!(build_scan_config(options).is_err());
+            assert_eq!(scan_option_errors(options).len(), 1);
+        }
+    }
+
+    #[test]
+    fn every_invalid_option_is_reported_with_its_field() {
+        let options = ScanOptions {
+            max_entries: Some(0),
+            filters: ScanFilters {
+                include_regex: Some("(".to_string()),
+                exclude_globs: strings(&["a[b"]),
+                min_size: Some("ten".to_string()),
+                ..ScanFilters::default()
+            },
+            ..ScanOptions::default()
+        };
+        let fields: Vec<String> = scan_option_errors(&options)
+            .into_iter()
+            .map(|error| error.field)
+            .collect();
+        assert_eq!(
+            fields,
+            strings(&[
+                "filters.minSize",
+                "filters.includeRegex",
+                "filters.excludeGlobs",
+                "maxEntries",
+            ])
+        );
+        let message = build_scan_config(&options).err().unwrap();
+        assert!(message.starts_with("invalid size"));
+    }
+
+    #[test]
+    fn explanations_name_the_matching_term() {
+        let config = filters(ScanFilters {
+            exclude_names: strings(&["node_modules"]),
+            exclude_globs: strings(&["*.tmp", "*.log"]),
+            ..ScanFilters::default()
+        });
+        let root = Path::new("/data");
+
+        let file = explain_file_filters(root, Path::new("/data/app/run.log"), 10, None, &config);
+        assert!(!file.included);
+        assert_eq!(file.rule, "excludeGlobs");
+        assert_eq!(file.term.as_deref(), Some("*.log"));
+        assert!(file.folder.is_none());
+
+        let nested = Path::new("/data/app/node_modules/index.js");
+        let folder = explain_file_filters(root, nested, 10, None, &config);
+        assert_eq!(folder.rule, "excludeNames");
+        assert_eq!(folder.term.as_deref(), Some("node_modules"));
+        assert!(folder.folder.is_some());
+
+        let kept = explain_file_filters(root, Path::new("/data/app/main.rs"), 10, None, &config);
+        assert!(kept.included);
+        assert!(kept.term.is_none());
+    }
+
+    #[test]
+    fn folders_cut_off_by_max_depth_still_have_children() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("top").join("inner").join("deep")).unwrap();
+        fs::write(
+            dir.path()
+                .join("top")
+                .join("inner")
+                .join("deep")
+                .join("file"),
+            "x",
+        )
+        .unwrap();
+
+        let summary = complete_scan(
+            dir.path(),
+            &ScanOptions {
+                max_depth: Some(1),
+                ..ScanOptions::default()
+            },
+        );
+        let top = &summary.root.children[0];
+        assert!(top.truncated);
+        assert!(top.children.is_empty());
+        assert_eq!(top.child_count, 1);
+        assert!(top.has_children);
+    }
+
+    #[test]
+    fn extensions_and_names_fold_case_unless_asked_not_to() {
+        let folded = filters(ScanFilters {
+            include_extensions: strings(&[".JPG"]),
+            ..ScanFilters::default()
+        });
+        assert!(includes(&folded, "/data/a.jpg", 10));
+        assert!(includes(&folded, "/data/b.Jpg", 10));
+        assert!(!includes(&folded, "/data/c.png", 10));
+
+        let exact = filters(ScanFilters {
+            include_extensions: strings(&["JPG"]),
+            case_sensitive: true,
+            ..ScanFilters::default()
+        });
+        assert!(includes(&exact, "/data/a.JPG", 10));
+        assert!(!includes(&exact, "/data/a.jpg", 10));
+
+        let names = filters(ScanFilters {
+            exclude_names: strings(&["Thumbs"]),
+            ..ScanFilters::default()
+        });
+        assert!(!includes(&names, "/data/thumbs.db", 10));
+        assert!(includes(&names, "/data/photo.db", 10));
+    }
+
+    #[test]
+    fn png_filter_matches_image_png_only_when_case_insensitive() {
+        for case_sensitive in [false, true] {
+            let include = filters(ScanFilters {
+                include_extensions: strings(&["PNG"]),
+                case_sensitive,
+                ..ScanFilters::default()
+            });
+            assert_eq!(includes(&include, "/data/image.png", 10), !case_sensitive);
+            assert!(includes(&include, "/data/image.PNG", 10));
+
+            let exclude = filters(ScanFilters {
+                exclude_extensions: strings(&["PNG"]),
+                case_sensitive,
+                ..ScanFilters::default()
+            });
+            assert_eq!(includes(&exclude, "/data/image.png", 10), case_sensitive);
+            assert!(!includes(&exclude, "/data/image.PNG", 10));
+        }
+    }
+
+    #[test]
+    fn excludes_win_over_includes_and_report_their_rule() {
+        let config = filters(ScanFilters {
+            include_extensions: strings(&["log"]),
+            exclude_paths: strings(&["/data/old"]),
+            min_size_bytes: Some(5),
+            ..ScanFilters::default()
+        });
+        let decide = |path: &str, size_bytes| {
+            file_decision(
+                Path::new("/data"),
+                Path::new(path),
+                size_bytes,
+                None,
+                &config,
+            )
+            .rule
+        };
+        assert_eq!(decide("/data/new/a.log", 10), "includeExtensions");
+        assert_eq!(decide("/data/old/a.log", 10), "excludePaths");
+        assert_eq!(decide("/data/new/a.log", 1), "minSizeBytes");
+        assert_eq!(decide("/data/new/a.txt", 10), "noMatchingInclude");
+    }
+
+    #[test]
+    fn folders_are_skipped_by_path_prefix_name_and_glob() {
+        let config = filters(ScanFilters {
+            exclude_path_prefixes: strings(&["/data/cache"]),
+            exclude_names: strings(&["node_modules"]),
+            exclude_globs: strings(&["**/target"]),
+            ..ScanFilters::default()
+        });
+        let root = Path::new("/data");
+        let skips = |path: &str| should_skip_dir(root, Path::new(path), &config);
+        assert!(!skips("/data"));
+        assert!(skips("/data/cache"));
+        assert!(skips("/data/cache/nested"));
+        assert!(!skips("/data/cached"));
+        assert!(skips("/data/web/node_modules"));
+        assert!(skips("/data/crate/target"));
+        assert!(!skips("/data/crate/src"));
+    }
+
+    fn file_path(dirs: &[String], stem: &str, ext: &str) -> PathBuf {
+        let mut path = PathBuf::from("/data");
+        path.extend(dirs);
+        path.push(format!("{stem}.{ext}"));
+        path
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn an_excluded_extension_excludes_regardless_of_includes(
+            dirs in proptest::collection::vec("[a-z]{1,6}", 0..4),
+            stem in "[a-z]{1,8}",
+            ext in "[a-zA-Z]{1,4}",
+            include_extensions in proptest::collection::vec("[a-z]{1,4}", 0..4),
+            include_names in proptest::collection::vec("[a-z]{1,3}", 0..3),
+            include_paths in proptest::collection::vec("[a-z/]{1,6}", 0..3),
+            include_globs in proptest::collection::vec("\\*\\*/\\*\\.[a-z]{1,3}", 0..2),
+        ) {
+            let mut include_extensions = include_extensions;
+            include_extensions.push(ext.clone());
+            let config = filters(ScanFilters {
+                include_extensions,
+                include_names,
+                include_paths,
+                include_globs,
+                exclude_extensions: vec![ext.to_lowercase()],
+                ..ScanFilters::default()
+            });
+            let path = file_path(&dirs, &stem, &ext);
+            proptest::prop_assert!(!includes(&config, path.to_str().unwrap(), 1));
+        }
+
+        #[test]
+        fn includes_never_bring_back_an_excluded_file(
+            dirs in proptest::collection::vec("[a-z]{1,6}", 0..4),
+            stem in "[a-z]{1,8}",
+            ext in "[a-z]{1,4}",
+            exclude_names in proptest::collection::vec("[a-z]{1,3}", 0..3),
+            exclude_paths in proptest::collection::vec("[a-z/]{1,6}", 0..3),
+            include_names in proptest::collection::vec("[a-z]{1,3}", 0..3),
+            size_bytes in 0u64..1000,
+        ) {
+            let excludes = ScanFilters {
+                exclude_names,
+                exclude_paths,
+                min_size_bytes: Some(100),
+                ..ScanFilters::default()
+            };
+            let with_includes = ScanFilters {
+                include_names,
+                include_extensions: vec![ext.clone()],
+                ..excludes.clone()
+            };
+            let path = file_path(&dirs, &stem, &ext);
+            let path = path.to_str().unwrap();
+            if !includes(&filters(excludes), path, size_bytes) {
+                proptest::prop_assert!(!includes(&filters(with_includes), path, size_bytes));
+            }
+        }
+
+        #[test]
+        fn size_bounds_keep_exactly_the_sizes_between_them(
+            bounds in (0u64..10_000, 0u64..10_000),
+            size_bytes in 0u64..12_000,
+        ) {
+            let (min, max) = (bounds.0.min(bounds.1), bounds.0.max(bounds.1));
+            let config = filters(ScanFilters {
+                min_size_bytes: Some(min),
+                max_size_bytes: Some(max),
+                ..ScanFilters::default()
+            });
+            proptest::prop_assert_eq!(
+                includes(&config, "/data/a.bin", size_bytes),
+                (min..=max).contains(&size_bytes)
+            );
+        }
+
+        #[test]
+        fn date_bounds_keep_exactly_the_times_between_them(
+            bounds in (0u64..10_000, 0u64..10_000),
+            modified in proptest::option::of(0u64..12_000),
+        ) {
+            let (min, max) = (bounds.0.min(bounds.1), bounds.0.max(bounds.1));
+            let config = filters(ScanFilters {
+                min_modified_timestamp: Some(min),
+                max_modified_timestamp: Some(max),
+                ..ScanFilters::default()
+            });
+            let kept = should_include_file(
+                Path::new("/data"),
+                Path::new("/data/a.bin"),
+                1,
+                modified,
+                &config,
+            );
+            let expected = modified.is_none_or(|time| (min..=max).contains(&time));
+            proptest::prop_assert_eq!(kept, expected);
+        }
+
+        #[test]
+        fn min_above_max_is_rejected_at_build_time(
+            max in 0u64..u64::MAX,
+            gap in 1u64..1_000,
+            use_text in proptest::bool::ANY,
+        ) {
+            let min = max.saturating_add(gap);
+            proptest::prop_assume!(min > max);
+            let sizes = if use_text {
+                ScanFilters {
+                    min_size: Some(min.to_string()),
+                    max_size: Some(max.to_string()),
+                    ..ScanFilters::default()
+                }
+            } else {
+                ScanFilters {
+                    min_size_bytes: Some(min),
+                    max_size_bytes: Some(max),
+                    ..ScanFilters::default()
+                }
+            };
+            let times = ScanFilters {
+                min_modified_timestamp: Some(min),
+                max_modified_timestamp: Some(max),
+                ..ScanFilters::default()
+            };
+            for invalid in [sizes, times] {
+                proptest::prop_assert!(build_filter_config(&invalid)
+                    .err()
+                    .is_some_and(|errors| !errors.is_empty()));
+            }
+        }
+
+        #[test]
+        fn excluded_folder_prefixes_skip_everything_below_them(
+            prefix in proptest::collection::vec("[a-z]{1,6}", 1..3),
+            rest in proptest::collection::vec("[a-z]{1,6}", 0..3),
+            suffix in "[a-z]{1,3}",
+        ) {
+            let mut folder = PathBuf::from("/data");
+            folder.extend(&prefix);
+            let config = filters(ScanFilters {
+                exclude_path_prefixes: vec![get_path_string(&folder)],
+                ..ScanFilters::default()
+            });
+            let root = Path::new("/data");
+            let mut below = folder.clone();
+            below.extend(&rest);
+            proptest::prop_assert!(should_skip_dir(root, &below, &config));
+            let sibling = PathBuf::from(format!("{}{suffix}", folder.display()));
+            proptest::prop_assert!(!should_skip_dir(root, &sibling, &config));
+        }
+    }
+
+    #[test]
+    fn modified_time_filters_keep_files_without_a_timestamp() {
+        let config = filters(ScanFilters {
+            min_modified_timestamp: Some(100),
+            max_modified_timestamp: Some(200),
+            ..ScanFilters::default()
+        });
+        let keeps = |modified| {
+            should_include_file(
+                Path::new("/data"),
+                Path::new("/data/a"),
+                1,
+                modified,
+                &config,
+            )
+        };
+        assert!(keeps(Some(150)));
+        assert!(!keeps(Some(99)));
+        assert!(!keeps(Some(201)));
+        assert!(keeps(None));
+    }
+}
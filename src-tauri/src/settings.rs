@@ -0,0 +1,1225 @@
+use super::*;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tauri::Emitter;
+
+const DEFAULT_TCP_BIND: &str = "127.0.0.1:4799";
+const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 15;
+const DEFAULT_HASH_MAX_BYTES: u64 = 16 * 1024 * 1024 * 1024; // 16GB
+const DEFAULT_RETAINED_SUMMARIES: usize = 4;
+const APP_IDENTIFIER: &str = "com.dragabyte.app";
+const SETTINGS_POINTER_FILE: &str = "settings-path";
+
+pub(crate) struct SettingsState {
+    pub(crate) path: Mutex<PathBuf>,
+    pub(crate) value: Mutex<AppSettings>,
+    pub(crate) overlay: AppSettings,
+    pub(crate) writable: AtomicBool,
+}
+
+impl SettingsState {
+    pub(crate) fn effective(&self, stored: &AppSettings) -> AppSettings {
+        effective_settings(stored, &self.overlay)
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(not(feature = "remote"), allow(dead_code))]
+pub(crate) struct TcpConfig {
+    pub(crate) bind_addr: SocketAddr,
+    pub(crate) token: Option<String>,
+    pub(crate) scoped_tokens: Vec<RemoteTokenConfig>,
+    pub(crate) allowed_roots: Vec<String>,
+    pub(crate) tls_cert: Option<String>,
+    pub(crate) tls_key: Option<String>,
+    pub(crate) heartbeat_interval: Option<Duration>,
+    pub(crate) hash_max_bytes: Option<u64>,
+    pub(crate) allow_shutdown: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RemoteScope {
+    Read,
+    List,
+    Scan,
+    Delete,
+    Shutdown,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteTokenConfig {
+    pub(crate) token: String,
+    pub(crate) scopes: Vec<RemoteScope>,
+}
+
+pub(crate) struct RuntimeOptions {
+    pub(crate) headless: bool,
+    pub(crate) tcp: Option<TcpConfig>,
+    pub(crate) startup_path: Option<String>,
+    pub(crate) updater_enabled: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppSettings {
+    local_token: Option<String>,
+    tcp_bind: Option<String>,
+    headless: Option<bool>,
+    auto_update: Option<bool>,
+    pub(crate) remember_window_layouts: Option<bool>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) window_layouts: HashMap<String, WindowGeometry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    allowed_roots: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    remote_tokens: Vec<RemoteTokenConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tls_cert: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tls_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    heartbeat_interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash_max_bytes: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    allow_remote_shutdown: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retained_summaries: Option<usize>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) scan_presets: HashMap<String, ScanOptions>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, JsonValue>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppSettingsUpdate {
+    local_token: Option<String>,
+    tcp_bind: Option<String>,
+    headless: Option<bool>,
+    auto_update: Option<bool>,
+    remember_window_layouts: Option<bool>,
+}
+
+pub(crate) fn parse_runtime_options(
+    args: &[String],
+    startup_path: Option<String>,
+    settings: &AppSettings,
+) -> Result<RuntimeOptions, String> {
+    let headless = has_flag(args, "--headless") || settings.headless.unwrap_or(false);
+    let tcp = parse_tcp_config(args, settings)?;
+    let updater_enabled = resolve_updater_enabled(args, settings);
+    Ok(RuntimeOptions {
+        headless,
+        tcp,
+        startup_path,
+        updater_enabled,
+    })
+}
+
+fn parse_tcp_config(args: &[String], settings: &AppSettings) -> Result<Option<TcpConfig>, String> {
+    let bind_arg = get_arg_value(args, "--tcp-bind");
+    let token = tcp_token(args, settings, env_string);
+    let enabled = has_flag(args, "--tcp")
+        || bind_arg.is_some()
+        || settings.tcp_bind.is_some()
+        || settings.local_token.is_some();
+    if !enabled {
+        return Ok(None);
+    }
+    let bind_raw = bind_arg
+        .or_else(|| settings.tcp_bind.clone())
+        .unwrap_or_else(|| DEFAULT_TCP_BIND.to_string());
+    let tls_cert = get_arg_value(args, "--tls-cert").or_else(|| settings.tls_cert.clone());
+    let tls_key = get_arg_value(args, "--tls-key").or_else(|| settings.tls_key.clone());
+    let bind_addr = check_tcp_bind(
+        &bind_raw,
+        token.is_some() || !settings.remote_tokens.is_empty(),
+        tls_cert.is_some(),
+        tls_key.is_some(),
+    )
+    .map_err(|error| error.message)?;
+    let heartbeat_secs = match get_arg_value(args, "--tcp-heartbeat") {
+        Some(value) => value
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| "Invalid heartbeat interval".to_string())?,
+        None => settings
+            .heartbeat_interval_secs
+            .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL_SECS),
+    };
+    Ok(Some(TcpConfig {
+        bind_addr,
+        token,
+        scoped_tokens: settings.remote_tokens.clone(),
+        allowed_roots: settings.allowed_roots.clone(),
+        tls_cert,
+        tls_key,
+        heartbeat_interval: (heartbeat_secs > 0).then(|| Duration::from_secs(heartbeat_secs)),
+        hash_max_bytes: Some(settings.hash_max_bytes.unwrap_or(DEFAULT_HASH_MAX_BYTES))
+            .filter(|limit| *limit > 0),
+        allow_shutdown: settings.allow_remote_shutdown.unwrap_or(false),
+    }))
+}
+
+/// The legacy `DRAGABYTE_TCP_TOKEN` only fills in when neither the CLI nor
+/// `VOXARA_LOCAL_TOKEN` gives a token, and unlike those it never enables TCP.
+fn tcp_token(
+    args: &[String],
+    settings: &AppSettings,
+    var: impl Fn(&str) -> Option<String>,
+) -> Option<String> {
+    get_arg_value(args, "--tcp-token")
+        .or_else(|| var("VOXARA_LOCAL_TOKEN"))
+        .or_else(|| var("DRAGABYTE_TCP_TOKEN"))
+        .or_else(|| settings.local_token.clone())
+}
+
+fn check_tcp_bind(
+    raw: &str,
+    has_token: bool,
+    has_cert: bool,
+    has_key: bool,
+) -> Result<SocketAddr, SettingsError> {
+    let error = |field, message: &str| SettingsError {
+        field: Some(field),
+        message: message.to_string(),
+    };
+    let bind_addr = raw
+        .parse::<SocketAddr>()
+        .map_err(|_| error("tcpBind", "Invalid TCP bind address"))?;
+    if has_cert != has_key {
+        return Err(error(
+            if has_cert { "tlsKey" } else { "tlsCert" },
+            "TLS needs both a certificate and a private key",
+        ));
+    }
+    // Plaintext is only allowed where nothing crosses the network.
+    if !bind_addr.ip().is_loopback() && !has_cert {
+        return Err(error(
+            "tlsCert",
+            "TLS (--tls-cert and --tls-key) is required when binding to non-loopback",
+        ));
+    }
+    if !bind_addr.ip().is_loopback() && !has_token {
+        return Err(error(
+            "localToken",
+            "A token (--tcp-token or VOXARA_LOCAL_TOKEN) is required when binding to non-loopback",
+        ));
+    }
+    Ok(bind_addr)
+}
+
+#[derive(Serialize)]
+pub(crate) struct SettingsError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<&'static str>,
+    message: String,
+}
+
+impl From<String> for SettingsError {
+    fn from(message: String) -> Self {
+        Self {
+            field: None,
+            message,
+        }
+    }
+}
+
+fn validate_tcp_settings(settings: &AppSettings) -> Result<(), SettingsError> {
+    if settings.tcp_bind.is_none() && settings.local_token.is_none() {
+        return Ok(());
+    }
+    let has_token =
+        tcp_token(&[], settings, env_string).is_some() || !settings.remote_tokens.is_empty();
+    check_tcp_bind(
+        settings.tcp_bind.as_deref().unwrap_or(DEFAULT_TCP_BIND),
+        has_token,
+        settings.tls_cert.is_some(),
+        settings.tls_key.is_some(),
+    )
+    .map(|_| ())
+}
+
+impl AppSettings {
+    pub(crate) fn retained_summaries(&self) -> usize {
+        self.retained_summaries
+            .unwrap_or(DEFAULT_RETAINED_SUMMARIES)
+    }
+}
+
+fn resolve_updater_enabled(args: &[String], settings: &AppSettings) -> bool {
+    if has_flag(args, "--disable-updater") || env_flag("DRAGABYTE_DISABLE_UPDATER") {
+        return false;
+    }
+    settings.auto_update.unwrap_or(true)
+}
+
+struct EnvReader<F: Fn(&str) -> Option<String>>(F);
+
+impl<F: Fn(&str) -> Option<String>> EnvReader<F> {
+    fn string(&self, name: &str) -> Option<String> {
+        (self.0)(name)
+    }
+
+    fn parse<T>(&self, name: &str, parse: impl FnOnce(&str) -> Option<T>) -> Option<T> {
+        let raw = self.string(name)?;
+        let value = parse(&raw);
+        if value.is_none() {
+            eprintln!("[settings] ignoring invalid {name}");
+        }
+        value
+    }
+
+    fn flag(&self, name: &str) -> Option<bool> {
+        self.parse(name, parse_bool)
+    }
+
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Option<T> {
+        self.parse(name, |raw| raw.parse().ok())
+    }
+
+    fn json<T: serde::de::DeserializeOwned + Default>(&self, name: &str) -> T {
+        self.parse(name, |raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default()
+    }
+}
+
+fn env_settings(var: impl Fn(&str) -> Option<String>) -> AppSettings {
+    let env = EnvReader(var);
+    AppSettings {
+        local_token: env.string("VOXARA_LOCAL_TOKEN"),
+        tcp_bind: env
+            .string("VOXARA_TCP_BIND")
+            .or_else(|| env.string("DRAGABYTE_TCP_BIND")),
+        headless: env
+            .flag("VOXARA_HEADLESS")
+            .or_else(|| env.flag("DRAGABYTE_HEADLESS")),
+        auto_update: env.flag("VOXARA_AUTO_UPDATE"),
+        remember_window_layouts: env.flag("VOXARA_REMEMBER_WINDOW_LAYOUTS"),
+        window_layouts: env.json("VOXARA_WINDOW_LAYOUTS"),
+        allowed_roots: env.json("VOXARA_ALLOWED_ROOTS"),
+        remote_tokens: env.json("VOXARA_REMOTE_TOKENS"),
+        tls_cert: env.string("VOXARA_TLS_CERT"),
+        tls_key: env.string("VOXARA_TLS_KEY"),
+        heartbeat_interval_secs: env.number("VOXARA_HEARTBEAT_INTERVAL_SECS"),
+        hash_max_bytes: env.number("VOXARA_HASH_MAX_BYTES"),
+        allow_remote_shutdown: env.flag("VOXARA_ALLOW_REMOTE_SHUTDOWN"),
+        retained_summaries: env.number("VOXARA_RETAINED_SUMMARIES"),
+        scan_presets: env.json("VOXARA_SCAN_PRESETS"),
+        extra: serde_json::Map::new(),
+    }
+}
+
+pub(crate) fn print_runtime_config(options: &RuntimeOptions, settings_path: &Path) {
+    let tcp = options.tcp.as_ref().map(|config| {
+        serde_json::json!({
+          "bind": config.bind_addr.to_string(),
+          "token": config.token.as_ref().map(|_| "<redacted>"),
+          "scopedTokens": config.scoped_tokens.len(),
+          "tls": config.tls_cert.is_some(),
+          "heartbeatSecs": config.heartbeat_interval.map(|interval| interval.as_secs()),
+          "hashMaxBytes": config.hash_max_bytes,
+          "allowShutdown": config.allow_shutdown,
+          "allowedRoots": config.allowed_roots
+        })
+    });
+    let payload = serde_json::json!({
+      "headless": options.headless,
+      "tcp": tcp,
+      "startupPath": options.startup_path,
+      "updaterEnabled": options.updater_enabled,
+      "settingsPath": get_path_string(settings_path)
+    });
+    match serde_json::to_string_pretty(&payload) {
+        Ok(text) => println!("{text}"),
+        Err(error) => eprintln!("Failed to print config: {error}"),
+    }
+}
+
+pub(crate) fn env_string(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "1" | "true" | "yes" => Some(true),
+        "0" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn env_flag(name: &str) -> bool {
+    match std::env::var(name) {
+        Ok(value) => matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"),
+        Err(_) => false,
+    }
+}
+
+pub(crate) fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|value| value == flag)
+}
+
+pub(crate) fn get_arg_value(args: &[String], prefix: &str) -> Option<String> {
+    for value in args {
+        if let Some(stripped) = value.strip_prefix(&format!("{}=", prefix)) {
+            return Some(stripped.to_string());
+        }
+    }
+    None
+}
+
+pub(crate) fn resolve_settings_path(args: &[String]) -> PathBuf {
+    if let Some(path) = get_arg_value(args, "--settings") {
+        return PathBuf::from(path);
+    }
+    if let Some(path) =
+        env_string("VOXARA_SETTINGS_PATH").or_else(|| env_string("DRAGABYTE_SETTINGS_PATH"))
+    {
+        return PathBuf::from(path);
+    }
+    if let Some(path) = settings_pointer_file().and_then(|pointer| read_settings_pointer(&pointer))
+    {
+        return path;
+    }
+    std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("dragabyte.settings.json")
+}
+
+/// Where `set_settings_path` records a relocated settings file for later launches.
+fn settings_pointer_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_IDENTIFIER).join(SETTINGS_POINTER_FILE))
+}
+
+fn read_settings_pointer(pointer: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(pointer).ok()?;
+    let path = contents.trim();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+pub(crate) fn load_settings(path: &Path) -> AppSettings {
+    let contents = fs::read_to_string(path).unwrap_or_default();
+    if contents.trim().is_empty() {
+        return AppSettings::default();
+    }
+    serde_json::from_str(&contents).unwrap_or_default()
+}
+
+pub(crate) fn load_settings_overlay(path: &Path) -> AppSettings {
+    settings_overlay(path, env_string)
+}
+
+fn settings_overlay(path: &Path, var: impl Fn(&str) -> Option<String>) -> AppSettings {
+    let mut overlay = load_toml_settings(&path.with_extension("toml")).unwrap_or_default();
+    overlay_settings(&mut overlay, env_settings(var));
+    overlay
+}
+
+fn load_toml_settings(path: &Path) -> Option<AppSettings> {
+    let contents = fs::read_to_string(path).ok()?;
+    match toml::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            eprintln!("[settings] ignoring invalid {}: {error}", path.display());
+            None
+        }
+    }
+}
+
+pub(crate) fn effective_settings(stored: &AppSettings, overlay: &AppSettings) -> AppSettings {
+    let mut settings = stored.clone();
+    overlay_settings(&mut settings, overlay.clone());
+    settings
+}
+
+#[cfg_attr(not(feature = "remote"), allow(dead_code))]
+pub(crate) fn load_effective_settings(path: &Path) -> AppSettings {
+    effective_settings(&load_settings(path), &load_settings_overlay(path))
+}
+
+fn overlay_settings(settings: &mut AppSettings, overrides: AppSettings) {
+    if overrides.local_token.is_some() {
+        settings.local_token = overrides.local_token;
+    }
+    if overrides.tcp_bind.is_some() {
+        settings.tcp_bind = overrides.tcp_bind;
+    }
+    if overrides.headless.is_some() {
+        settings.headless = overrides.headless;
+    }
+    if overrides.auto_update.is_some() {
+        settings.auto_update = overrides.auto_update;
+    }
+    if overrides.remember_window_layouts.is_some() {
+        settings.remember_window_layouts = overrides.remember_window_layouts;
+    }
+    settings.window_layouts.extend(overrides.window_layouts);
+    if !overrides.allowed_roots.is_empty() {
+        settings.allowed_roots = overrides.allowed_roots;
+    }
+    if !overrides.remote_tokens.is_empty() {
+        settings.remote_tokens = overrides.remote_tokens;
+    }
+    if overrides.tls_cert.is_some() {
+        settings.tls_cert = overrides.tls_cert;
+    }
+    if overrides.tls_key.is_some() {
+        settings.tls_key = overrides.tls_key;
+    }
+    if overrides.heartbeat_interval_secs.is_some() {
+        settings.heartbeat_interval_secs = overrides.heartbeat_interval_secs;
+    }
+    if overrides.hash_max_bytes.is_some() {
+        settings.hash_max_bytes = overrides.hash_max_bytes;
+    }
+    if overrides.allow_remote_shutdown.is_some() {
+        settings.allow_remote_shutdown = overrides.allow_remote_shutdown;
+    }
+    if overrides.retained_summaries.is_some() {
+        settings.retained_summaries = overrides.retained_summaries;
+    }
+    // Deployment presets are added to the GUI's, replacing any of the same name.
+    settings.scan_presets.extend(overrides.scan_presets);
+}
+
+pub(crate) fn save_settings(path: &Path, settings: &AppSettings) -> Result<(), String> {
+    let payload = serde_json::to_string_pretty(settings)
+        .map_err(|error| format!("Failed to serialize settings: {error}"))?;
+    write_file_atomic(path, payload.as_bytes())
+        .map_err(|error| format!("Failed to save settings: {error}"))
+}
+
+pub(crate) fn write_file_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .map(|value| value.to_string_lossy().to_string())
+        .unwrap_or_else(|| "settings".to_string());
+    let temp_path = path.with_file_name(format!(".{file_name}.tmp"));
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600); // Read/write for owner only
+    }
+    let result = options.open(&temp_path).and_then(|mut file| {
+        #[cfg(unix)]
+        {
+            // `mode` is ignored when a stale temp file already exists.
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    let result = result.and_then(|_| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn apply_settings_update(settings: &mut AppSettings, update: AppSettingsUpdate) {
+    if update.local_token.is_some() {
+        settings.local_token = update.local_token;
+    }
+    if update.tcp_bind.is_some() {
+        settings.tcp_bind = update.tcp_bind;
+    }
+    if update.headless.is_some() {
+        settings.headless = update.headless;
+    }
+    if update.auto_update.is_some() {
+        settings.auto_update = update.auto_update;
+    }
+    if update.remember_window_layouts.is_some() {
+        settings.remember_window_layouts = update.remember_window_layouts;
+    }
+}
+
+#[tauri::command]
+pub(crate) fn get_settings(state: tauri::State<SettingsState>) -> Result<AppSettings, String> {
+    let guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    Ok(state.effective(&guard))
+}
+
+fn emit_settings_changed(app: &tauri::AppHandle, settings: &AppSettings) {
+    let mut redacted = settings.clone();
+    redacted.local_token = None;
+    redacted.remote_tokens.clear();
+    let _ = app.emit("settings-changed", redacted);
+}
+
+#[tauri::command]
+pub(crate) fn update_settings(
+    app: tauri::AppHandle,
+    state: tauri::State<SettingsState>,
+    update: AppSettingsUpdate,
+) -> Result<AppSettings, SettingsError> {
+    let mut guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    let mut next = guard.clone();
+    apply_settings_update(&mut next, update);
+    let effective = state.effective(&next);
+    validate_tcp_settings(&effective)?;
+    let path = state
+        .path
+        .lock()
+        .map_err(|_| "Failed to lock settings path".to_string())?;
+    if let Err(error) = save_settings(&path, &next) {
+        state.writable.store(false, Ordering::SeqCst);
+        return Err(error.into());
+    }
+    *guard = next;
+    emit_settings_changed(&app, &effective);
+    Ok(effective)
+}
+
+#[tauri::command]
+pub(crate) fn reset_settings(
+    app: tauri::AppHandle,
+    state: tauri::State<SettingsState>,
+) -> Result<String, String> {
+    let mut guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    let path = state
+        .path
+        .lock()
+        .map_err(|_| "Failed to lock settings path".to_string())?;
+    let backup_path = path.with_extension("bak");
+    let contents = match fs::read(&*path) {
+        Ok(bytes) => bytes,
+        Err(_) => serde_json::to_vec_pretty(&*guard)
+            .map_err(|error| format!("Failed to serialize settings: {error}"))?,
+    };
+    write_file_atomic(&backup_path, &contents)
+        .map_err(|error| format!("Failed to back up settings: {error}"))?;
+    let defaults = AppSettings::default();
+    save_settings(&path, &defaults)?;
+    *guard = defaults;
+    emit_settings_changed(&app, &state.effective(&guard));
+    Ok(get_path_string(&backup_path))
+}
+
+/// The token is left out unless `include_token` is set.
+#[tauri::command]
+pub(crate) fn export_settings(
+    state: tauri::State<SettingsState>,
+    path: String,
+    include_token: Option<bool>,
+) -> Result<(), String> {
+    let mut settings = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?
+        .clone();
+    if !include_token.unwrap_or(false) {
+        settings.local_token = None;
+        settings.remote_tokens.clear();
+    }
+    let payload = serde_json::to_string_pretty(&settings)
+        .map_err(|error| format!("Failed to serialize settings: {error}"))?;
+    write_file_atomic(Path::new(&path), payload.as_bytes())
+        .map_err(|error| format!("Failed to export settings: {error}"))
+}
+
+/// With `merge`, only the fields present in the file are applied.
+#[tauri::command]
+pub(crate) fn import_settings(
+    app: tauri::AppHandle,
+    state: tauri::State<SettingsState>,
+    path: String,
+    merge: Option<bool>,
+) -> Result<AppSettings, String> {
+    let contents = fs::read_to_string(&path)
+        .map_err(|error| format!("Failed to read settings file: {error}"))?;
+    let imported: AppSettings = serde_json::from_str(&contents)
+        .map_err(|error| format!("Invalid settings file: {error}"))?;
+    let mut guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    let next = if merge.unwrap_or(false) {
+        let mut merged = guard.clone();
+        merged.extra.extend(imported.extra.clone());
+        overlay_settings(&mut merged, imported);
+        merged
+    } else {
+        imported
+    };
+    let settings_path = state
+        .path
+        .lock()
+        .map_err(|_| "Failed to lock settings path".to_string())?;
+    if let Err(error) = save_settings(&settings_path, &next) {
+        state.writable.store(false, Ordering::SeqCst);
+        return Err(error);
+    }
+    *guard = next;
+    let effective = state.effective(&guard);
+    emit_settings_changed(&app, &effective);
+    Ok(effective)
+}
+
+#[derive(Serialize)]
+pub(crate) struct ScanPreset {
+    name: String,
+    options: ScanOptions,
+}
+
+pub(crate) fn sorted_scan_presets(settings: &AppSettings) -> Vec<ScanPreset> {
+    let mut presets: Vec<ScanPreset> = settings
+        .scan_presets
+        .iter()
+        .map(|(name, options)| ScanPreset {
+            name: name.clone(),
+            options: options.clone(),
+        })
+        .collect();
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+    presets
+}
+
+pub(crate) fn resolve_scan_options(
+    settings: &AppSettings,
+    preset: Option<&str>,
+    overrides: Option<JsonValue>,
+) -> Result<ScanOptions, String> {
+    let Some(name) = preset else {
+        let overrides = overrides.ok_or_else(|| "Scan options are required".to_string())?;
+        return serde_json::from_value(overrides)
+            .map_err(|error| format!("Invalid scan options: {error}"));
+    };
+    let options = settings
+        .scan_presets
+        .get(name)
+        .ok_or_else(|| format!("Scan preset not found: {name}"))?;
+    let mut merged = serde_json::to_value(options)
+        .map_err(|error| format!("Failed to serialize scan preset: {error}"))?;
+    if let Some(overrides) = overrides {
+        merge_json(&mut merged, overrides);
+    }
+    serde_json::from_value(merged).map_err(|error| format!("Invalid scan options: {error}"))
+}
+
+fn merge_json(base: &mut JsonValue, overlay: JsonValue) {
+    match (base, overlay) {
+        (JsonValue::Object(base), JsonValue::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) if existing.is_object() && value.is_object() => {
+                        merge_json(existing, value);
+                    }
+                    _ => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn change_scan_presets(
+    app: &tauri::AppHandle,
+    state: &SettingsState,
+    change: impl FnOnce(&mut HashMap<String, ScanOptions>) -> Result<(), String>,
+) -> Result<Vec<ScanPreset>, String> {
+    let mut guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    let mut next = guard.clone();
+    change(&mut next.scan_presets)?;
+    let path = state
+        .path
+        .lock()
+        .map_err(|_| "Failed to lock settings path".to_string())?;
+    if let Err(error) = save_settings(&path, &next) {
+        state.writable.store(false, Ordering::SeqCst);
+        return Err(error);
+    }
+    *guard = next;
+    let effective = state.effective(&guard);
+    emit_settings_changed(app, &effective);
+    Ok(sorted_scan_presets(&effective))
+}
+
+fn check_preset_editable(state: &SettingsState, name: &str) -> Result<(), String> {
+    if state.overlay.scan_presets.contains_key(name) {
+        return Err(format!(
+            "Scan preset {name} is set in the TOML settings file or the environment and can't be changed here"
+        ));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub(crate) fn list_scan_presets(
+    state: tauri::State<SettingsState>,
+) -> Result<Vec<ScanPreset>, String> {
+    let guard = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    Ok(sorted_scan_presets(&state.effective(&guard)))
+}
+
+#[tauri::command]
+pub(crate) fn save_scan_preset(
+    app: tauri::AppHandle,
+    state: tauri::State<SettingsState>,
+    name: String,
+    options: ScanOptions,
+) -> Result<Vec<ScanPreset>, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    build_scan_config(&options)?;
+    check_preset_editable(&state, &name)?;
+    change_scan_presets(&app, &state, |presets| {
+        presets.insert(name, options);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub(crate) fn delete_scan_preset(
+    app: tauri::AppHandle,
+    state: tauri::State<SettingsState>,
+    name: String,
+) -> Result<Vec<ScanPreset>, String> {
+    check_preset_editable(&state, &name)?;
+    change_scan_presets(&app, &state, |presets| {
+        presets
+            .remove(&name)
+            .map(|_| ())
+            .ok_or_else(|| format!("Scan preset not found: {name}"))
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RuntimeInfo {
+    settings_path: String,
+    settings_writable: bool,
+}
+
+#[tauri::command]
+pub(crate) fn get_runtime_info(state: tauri::State<SettingsState>) -> Result<RuntimeInfo, String> {
+    let path = state
+        .path
+        .lock()
+        .map_err(|_| "Failed to lock settings path".to_string())?;
+    Ok(RuntimeInfo {
+        settings_path: get_path_string(&path),
+        settings_writable: state.writable.load(Ordering::SeqCst),
+    })
+}
+
+/// Later launches pick the new file up through a pointer in the app config
+/// folder, unless `--settings` or the environment names another one.
+#[tauri::command]
+pub(crate) fn set_settings_path(
+    state: tauri::State<SettingsState>,
+    path: String,
+) -> Result<RuntimeInfo, String> {
+    let target = PathBuf::from(path.trim());
+    if target.as_os_str().is_empty() {
+        return Err("Settings path cannot be empty".to_string());
+    }
+    if target.is_dir() {
+        return Err("Settings path must be a file".to_string());
+    }
+    let settings = state
+        .value
+        .lock()
+        .map_err(|_| "Failed to lock settings".to_string())?;
+    let mut current = state
+        .path
+        .lock()
+        .map_err(|_| "Failed to lock settings path".to_string())?;
+    let pointer = settings_pointer_file()
+        .ok_or_else(|| "Could not find the app config folder".to_string())?;
+    relocate_settings(&current, &target, &settings, &pointer)?;
+    *current = target;
+    state.writable.store(true, Ordering::SeqCst);
+    Ok(RuntimeInfo {
+        settings_path: get_path_string(&current),
+        settings_writable: true,
+    })
+}
+
+fn relocate_settings(
+    from: &Path,
+    to: &Path,
+    settings: &AppSettings,
+    pointer: &Path,
+) -> Result<(), String> {
+    save_settings(to, settings)?;
+    // The old folder may be read-only, so the overlay is copied rather than moved.
+    let overlay = from.with_extension("toml");
+    let moved_overlay = to.with_extension("toml");
+    if overlay.is_file() && overlay != moved_overlay && !moved_overlay.exists() {
+        fs::copy(&overlay, &moved_overlay)
+            .map_err(|error| format!("Failed to copy {}: {error}", overlay.display()))?;
+    }
+    if let Some(dir) = pointer.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|error| format!("Failed to remember the settings path: {error}"))?;
+    }
+    write_file_atomic(pointer, get_path_string(to).as_bytes())
+        .map_err(|error| format!("Failed to remember the settings path: {error}"))
+}
+
+pub(crate) fn probe_settings_writable(path: &Path) -> bool {
+    let parent = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let probe = parent.join(".dragabyte-write-test");
+    match fs::File::create(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saving_keeps_toml_values_out_of_the_json_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dragabyte.settings.json");
+        fs::write(&path, r#"{"autoUpdate": false}"#).unwrap();
+        fs::write(
+            path.with_extension("toml"),
+            "tcpBind = \"127.0.0.1:5000\"\nlocalToken = \"deploy\"\n",
+        )
+        .unwrap();
+
+        let mut stored = load_settings(&path);
+        let overlay = settings_overlay(&path, |_| None);
+        let effective = effective_settings(&stored, &overlay);
+        assert_eq!(effective.tcp_bind.as_deref(), Some("127.0.0.1:5000"));
+        assert_eq!(effective.local_token.as_deref(), Some("deploy"));
+
+        let update: AppSettingsUpdate =
+            serde_json::from_str(r#"{"rememberWindowLayouts": true}"#).unwrap();
+        apply_settings_update(&mut stored, update);
+        save_settings(&path, &stored).unwrap();
+
+        let saved: JsonValue = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["rememberWindowLayouts"], true);
+        assert_eq!(saved["tcpBind"], JsonValue::Null);
+        assert_eq!(saved["localToken"], JsonValue::Null);
+        assert_eq!(
+            effective_settings(&load_settings(&path), &overlay)
+                .tcp_bind
+                .as_deref(),
+            Some("127.0.0.1:5000")
+        );
+    }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn later_sources_override_earlier_ones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dragabyte.settings.json");
+        fs::write(
+            &path,
+            r#"{"heartbeatIntervalSecs": 20, "hashMaxBytes": 100, "retainedSummaries": 1}"#,
+        )
+        .unwrap();
+        fs::write(
+            path.with_extension("toml"),
+            "heartbeatIntervalSecs = 30\nhashMaxBytes = 200\n",
+        )
+        .unwrap();
+        let overlay = settings_overlay(&path, env(&[("VOXARA_HEARTBEAT_INTERVAL_SECS", "40")]));
+        let settings = effective_settings(&load_settings(&path), &overlay);
+
+        assert_eq!(settings.heartbeat_interval_secs, Some(40)); // env
+        assert_eq!(settings.hash_max_bytes, Some(200)); // TOML
+        assert_eq!(settings.retained_summaries(), 1); // JSON
+        assert_eq!(settings.allow_remote_shutdown, None); // default
+        let tcp = parse_tcp_config(&["--tcp".to_string()], &settings)
+            .unwrap()
+            .unwrap();
+        assert_eq!(tcp.heartbeat_interval, Some(Duration::from_secs(40)));
+        assert!(!tcp.allow_shutdown);
+    }
+
+    #[test]
+    fn cli_flags_override_the_environment() {
+        let settings = effective_settings(
+            &AppSettings::default(),
+            &env_settings(env(&[
+                ("VOXARA_TCP_BIND", "127.0.0.1:5000"),
+                ("VOXARA_HEARTBEAT_INTERVAL_SECS", "40"),
+            ])),
+        );
+        let args = [
+            "--tcp-bind=127.0.0.1:6000".to_string(),
+            "--tcp-heartbeat=5".to_string(),
+        ];
+        let tcp = parse_tcp_config(&args, &settings).unwrap().unwrap();
+        assert_eq!(tcp.bind_addr.port(), 6000);
+        assert_eq!(tcp.heartbeat_interval, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn every_field_has_an_environment_variable() {
+        let presets = serde_json::json!({ "quick": ScanOptions::default() }).to_string();
+        let settings = env_settings(env(&[
+            ("VOXARA_LOCAL_TOKEN", "secret"),
+            ("VOXARA_TCP_BIND", "127.0.0.1:5000"),
+            ("VOXARA_HEADLESS", "yes"),
+            ("VOXARA_AUTO_UPDATE", "0"),
+            ("VOXARA_REMEMBER_WINDOW_LAYOUTS", "true"),
+            (
+                "VOXARA_WINDOW_LAYOUTS",
+                r#"{"a": {"x": 1, "y": 2, "width": 3, "height": 4}}"#,
+            ),
+            ("VOXARA_ALLOWED_ROOTS", r#"["/srv"]"#),
+            (
+                "VOXARA_REMOTE_TOKENS",
+                r#"[{"token": "r", "scopes": ["read"]}]"#,
+            ),
+            ("VOXARA_TLS_CERT", "/etc/cert.pem"),
+            ("VOXARA_TLS_KEY", "/etc/key.pem"),
+            ("VOXARA_HEARTBEAT_INTERVAL_SECS", "5"),
+            ("VOXARA_HASH_MAX_BYTES", "0"),
+            ("VOXARA_ALLOW_REMOTE_SHUTDOWN", "1"),
+            ("VOXARA_RETAINED_SUMMARIES", "2"),
+            ("VOXARA_SCAN_PRESETS", presets.as_str()),
+        ]));
+        assert_eq!(settings.local_token.as_deref(), Some("secret"));
+        assert_eq!(settings.tcp_bind.as_deref(), Some("127.0.0.1:5000"));
+        assert_eq!(settings.headless, Some(true));
+        assert_eq!(settings.auto_update, Some(false));
+        assert_eq!(settings.remember_window_layouts, Some(true));
+        assert!(settings.window_layouts.contains_key("a"));
+        assert_eq!(settings.allowed_roots, vec!["/srv".to_string()]);
+        assert_eq!(settings.remote_tokens[0].token, "r");
+        assert_eq!(settings.tls_cert.as_deref(), Some("/etc/cert.pem"));
+        assert_eq!(settings.tls_key.as_deref(), Some("/etc/key.pem"));
+        assert_eq!(settings.heartbeat_interval_secs, Some(5));
+        assert_eq!(settings.hash_max_bytes, Some(0));
+        assert_eq!(settings.allow_remote_shutdown, Some(true));
+        assert_eq!(settings.retained_summaries, Some(2));
+        assert!(settings.scan_presets.contains_key("quick"));
+    }
+
+    #[test]
+    fn legacy_names_apply_only_when_the_new_ones_are_unset() {
+        let legacy = env_settings(env(&[("DRAGABYTE_TCP_BIND", "127.0.0.1:5000")]));
+        assert_eq!(legacy.tcp_bind.as_deref(), Some("127.0.0.1:5000"));
+        let both = env_settings(env(&[
+            ("DRAGABYTE_TCP_BIND", "127.0.0.1:5000"),
+            ("VOXARA_TCP_BIND", "127.0.0.1:6000"),
+            ("VOXARA_HEADLESS", "maybe"),
+        ]));
+        assert_eq!(both.tcp_bind.as_deref(), Some("127.0.0.1:6000"));
+        assert_eq!(both.headless, None);
+    }
+
+    #[test]
+    fn new_token_variable_beats_the_legacy_one() {
+        let settings = AppSettings::default();
+        let legacy = env(&[("DRAGABYTE_TCP_TOKEN", "old")]);
+        assert_eq!(tcp_token(&[], &settings, legacy).as_deref(), Some("old"));
+        let both = env(&[
+            ("DRAGABYTE_TCP_TOKEN", "old"),
+            ("VOXARA_LOCAL_TOKEN", "new"),
+        ]);
+        assert_eq!(tcp_token(&[], &settings, both).as_deref(), Some("new"));
+        let args = ["--tcp-token=cli".to_string()];
+        let both = env(&[
+            ("DRAGABYTE_TCP_TOKEN", "old"),
+            ("VOXARA_LOCAL_TOKEN", "new"),
+        ]);
+        assert_eq!(tcp_token(&args, &settings, both).as_deref(), Some("cli"));
+    }
+
+    #[test]
+    fn unknown_fields_survive_load_and_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dragabyte.settings.json");
+        let future = serde_json::json!({
+            "nested": { "list": [1, "two", null, { "deep": 2.5 }], "flag": true },
+            "label": "from a newer build"
+        });
+        let original = serde_json::json!({ "autoUpdate": false, "futureFeature": future });
+        fs::write(&path, original.to_string()).unwrap();
+
+        let settings = load_settings(&path);
+        save_settings(&path, &settings).unwrap();
+
+        let saved: JsonValue = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["futureFeature"], future);
+        assert_eq!(saved["autoUpdate"], false);
+    }
+
+    #[test]
+    fn saving_refuses_exactly_what_launch_would() {
+        let cases = [
+            (
+                serde_json::json!({ "tcpBind": "nonsense" }),
+                Some("tcpBind"),
+            ),
+            (
+                serde_json::json!({ "tcpBind": "0.0.0.0:4799", "localToken": "t" }),
+                Some("tlsCert"),
+            ),
+            (
+                serde_json::json!({ "tcpBind": "0.0.0.0:4799", "localToken": "t", "tlsCert": "c" }),
+                Some("tlsKey"),
+            ),
+            (
+                serde_json::json!({ "tcpBind": "0.0.0.0:4799", "tlsCert": "c", "tlsKey": "k" }),
+                Some("localToken"),
+            ),
+            (
+                serde_json::json!({
+                    "tcpBind": "0.0.0.0:4799",
+                    "localToken": "t",
+                    "tlsCert": "c",
+                    "tlsKey": "k"
+                }),
+                None,
+            ),
+            (serde_json::json!({ "localToken": "t" }), None),
+            (serde_json::json!({ "tcpBind": "127.0.0.1:5000" }), None),
+        ];
+        for (settings, field) in cases {
+            let settings: AppSettings = serde_json::from_value(settings).unwrap();
+            let saved = validate_tcp_settings(&settings);
+            let launched = parse_tcp_config(&[], &settings);
+            assert_eq!(saved.as_ref().err().and_then(|error| error.field), field);
+            assert_eq!(saved.err().map(|error| error.message), launched.err());
+        }
+    }
+
+    #[test]
+    fn missing_empty_or_broken_files_load_as_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dragabyte.settings.json");
+        let defaults = serde_json::to_value(AppSettings::default()).unwrap();
+        assert_eq!(
+            serde_json::to_value(load_settings(&path)).unwrap(),
+            defaults
+        );
+        for contents in ["", "  \n", "{ not json", "[1, 2]"] {
+            fs::write(&path, contents).unwrap();
+            assert_eq!(
+                serde_json::to_value(load_settings(&path)).unwrap(),
+                defaults
+            );
+        }
+    }
+
+    #[test]
+    fn saved_settings_load_back_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dragabyte.settings.json");
+        let settings: AppSettings = serde_json::from_value(serde_json::json!({
+            "localToken": "secret",
+            "tcpBind": "127.0.0.1:7878",
+            "headless": true,
+            "autoUpdate": false,
+            "allowedRoots": ["/data"],
+            "heartbeatIntervalSecs": 30,
+            "scanPresets": { "quick": ScanOptions::default() }
+        }))
+        .unwrap();
+        save_settings(&path, &settings).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("\n  \"localToken\": \"secret\""));
+        assert_eq!(
+            serde_json::to_value(load_settings(&path)).unwrap(),
+            serde_json::to_value(&settings).unwrap()
+        );
+        let names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["dragabyte.settings.json"]);
+    }
+
+    #[test]
+    fn relocated_settings_and_overlay_are_found_on_the_next_launch() {
+        let old_dir = tempfile::tempdir().unwrap();
+        let new_dir = tempfile::tempdir().unwrap();
+        let config_dir = tempfile::tempdir().unwrap();
+        let old_path = old_dir.path().join("dragabyte.settings.json");
+        fs::write(
+            old_path.with_extension("toml"),
+            "tcpBind = \"127.0.0.1:5000\"\n",
+        )
+        .unwrap();
+        let new_path = new_dir.path().join("moved.json");
+        let pointer = config_dir.path().join("app").join(SETTINGS_POINTER_FILE);
+        let settings: AppSettings =
+            serde_json::from_value(serde_json::json!({ "autoUpdate": false })).unwrap();
+
+        relocate_settings(&old_path, &new_path, &settings, &pointer).unwrap();
+
+        let found = read_settings_pointer(&pointer).unwrap();
+        assert_eq!(found, new_path);
+        assert_eq!(load_settings(&found).auto_update, Some(false));
+        assert_eq!(
+            settings_overlay(&found, |_| None).tcp_bind.as_deref(),
+            Some("127.0.0.1:5000")
+        );
+    }
+
+    #[test]
+    fn probing_needs_a_writable_folder_and_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dragabyte.settings.json");
+        assert!(probe_settings_writable(&path));
+        fs::write(&path, "{}").unwrap();
+        assert!(probe_settings_writable(&path));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert!(!probe_settings_writable(
+            &dir.path().join("missing").join("dragabyte.settings.json")
+        ));
+    }
+}
@@ -45,6 +45,7 @@ const getStatusColor = (status: RemoteStatus): string => {
   if (status === "connected") return "text-emerald-400";
   if (status === "connecting") return "text-blue-400";
   if (status === "error") return "text-red-400";
+  if (status === "timed-out") return "text-amber-400";
   return "text-slate-500";
 };
 
@@ -54,6 +55,8 @@ const getStatusBadgeClasses = (status: RemoteStatus): string => {
   if (status === "connecting")
     return "bg-blue-500/10 text-blue-400 border-blue-500/20";
   if (status === "error") return "bg-red-500/10 text-red-400 border-red-500/20";
+  if (status === "timed-out")
+    return "bg-amber-500/10 text-amber-400 border-amber-500/20";
   return "bg-slate-800/50 text-slate-400 border-slate-700/50";
 };
 
@@ -61,6 +64,7 @@ const getStatusLabel = (status: RemoteStatus): string => {
   if (status === "connected") return "Online";
   if (status === "connecting") return "Connecting...";
   if (status === "error") return "Error";
+  if (status === "timed-out") return "Timed out";
   return "Offline";
 };
 
@@ -43,6 +43,9 @@ export const connectRemote = async (server: RemoteServer): Promise<void> => {
       host: server.host,
       port: server.port,
       token: server.token || null,
+      tls: server.tls ?? false,
+      caCert: server.caCert ?? null,
+      pinSha256: server.pinSha256 ?? null,
     },
   });
 };
@@ -2,6 +2,7 @@ export type RemoteStatus =
   | "disconnected"
   | "connecting"
   | "connected"
+  | "timed-out"
   | "error";
 
 export interface RemoteServer {
@@ -10,6 +11,9 @@ export interface RemoteServer {
   host: string;
   port: number;
   token: string;
+  tls?: boolean;
+  caCert?: string | null;
+  pinSha256?: string | null;
   status: RemoteStatus;
   lastMessage?: string | null;
 }
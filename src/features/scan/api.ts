@@ -1,14 +1,25 @@
 import { listen } from "@tauri-apps/api/event";
 import { invokeCommand } from "../../lib/tauriInvoke";
-import type { DiskUsage, ScanOptions, ScanSummary } from "./types";
+import type {
+  DiskUsage,
+  ScanFile,
+  ScanFilters,
+  ScanOptions,
+  ScanSummary,
+} from "./types";
 
 interface ScanHandlers {
   onProgress: (summary: ScanSummary) => void;
   onComplete: (summary: ScanSummary) => void;
   onError: (message: string) => void;
-  onCancel: (message: string) => void;
+  onCancel: (message: string, partial?: ScanSummary) => void;
 }
 
+type ScanCancelled = {
+  message: string;
+  summary?: ScanSummary;
+};
+
 const listenToScanEvent = async <T>(
   eventName: string,
   handler: (payload: T) => void,
@@ -21,21 +32,33 @@ const listenToScanEvent = async <T>(
   };
 };
 
+/**
+ * With `preset`, the saved preset of that name is used and `options` only
+ * needs the fields that should differ from it.
+ */
 export const startScan = async (
   path: string,
-  options: ScanOptions,
+  options: Partial<ScanOptions>,
   handlers: ScanHandlers,
   scanId: string,
+  preset?: string,
 ): Promise<() => void> => {
   const [unlistenProgress, unlistenComplete, unlistenError, unlistenCancelled] =
     await Promise.all([
       listenToScanEvent<ScanSummary>("scan-progress", handlers.onProgress),
       listenToScanEvent<ScanSummary>("scan-complete", handlers.onComplete),
       listenToScanEvent<string>("scan-error", handlers.onError),
-      listenToScanEvent<string>("scan-cancelled", handlers.onCancel),
+      listenToScanEvent<ScanCancelled>("scan-cancelled", (payload) =>
+        handlers.onCancel(payload.message, payload.summary),
+      ),
     ]);
 
-  await invokeCommand<void>("scan_path", { path, options, id: scanId });
+  await invokeCommand<void>("scan_path", {
+    path,
+    options,
+    preset,
+    id: scanId,
+  });
 
   return (): void => {
     unlistenProgress();
@@ -45,6 +68,230 @@ export const startScan = async (
   };
 };
 
+export const restartScan = async (
+  path: string,
+  options: ScanOptions,
+  scanId: string,
+): Promise<void> => {
+  return invokeCommand<void>("restart_scan", { path, options, id: scanId });
+};
+
+export type ScanStatus = "running" | "completed" | "not-found";
+
+export const isScanActive = async (id: string): Promise<ScanStatus> => {
+  return invokeCommand<ScanStatus>("is_scan_active", { id });
+};
+
+export const getSummary = async (id: string): Promise<ScanSummary> => {
+  return invokeCommand<ScanSummary>("get_summary", { id });
+};
+
+export type FileSortKey =
+  | "size-desc"
+  | "size-asc"
+  | "name-asc"
+  | "name-desc"
+  | "path-asc"
+  | "modified-desc";
+
+export type FilePage = {
+  id: string;
+  total: number;
+  offset: number;
+  files: ScanFile[];
+};
+
+export const listScanFiles = async (
+  id: string,
+  limit: number,
+  offset = 0,
+  sort: FileSortKey = "size-desc",
+): Promise<FilePage> => {
+  return invokeCommand<FilePage>("list_scan_files", {
+    id,
+    sort,
+    offset,
+    limit,
+  });
+};
+
+export type TransferFit = {
+  destination: string;
+  freeBytes: number;
+  requiredBytes: number;
+  fits: boolean;
+  oversizedFiles: ScanFile[];
+  oversizedCount: number;
+};
+
+export const checkTransferFit = async (
+  id: string,
+  destination: string,
+): Promise<TransferFit> => {
+  return invokeCommand<TransferFit>("check_transfer_fit", { id, destination });
+};
+
+export type FolderChange = {
+  path: string;
+  oldBytes: number;
+  newBytes: number;
+  byteDelta: number;
+  fileDelta: number;
+};
+
+export type DiffEntry = {
+  path: string;
+  sizeBytes: number;
+  isDir: boolean;
+};
+
+export type ScanDiff = {
+  byteDelta: number;
+  fileDelta: number;
+  changes: FolderChange[];
+  added: DiffEntry[];
+  removed: DiffEntry[];
+};
+
+export const diffScans = async (
+  old: ScanSummary,
+  next: ScanSummary,
+  threshold?: number,
+): Promise<ScanDiff> => {
+  return invokeCommand<ScanDiff>("diff_scans", {
+    old,
+    new: next,
+    threshold,
+  });
+};
+
+export type ScanOptionError = {
+  field: string;
+  message: string;
+};
+
+/** Resolves with every problem in `options`; empty when they are valid. */
+export const validateScanOptions = async (
+  options: ScanOptions,
+): Promise<ScanOptionError[]> => {
+  try {
+    await invokeCommand<void>("validate_scan_options", { options });
+    return [];
+  } catch (errors) {
+    if (Array.isArray(errors)) {
+      return errors as ScanOptionError[];
+    }
+    throw errors;
+  }
+};
+
+export type FileExplanation = {
+  path: string;
+  sizeBytes: number;
+  modified?: number;
+  included: boolean;
+  rule: string;
+  term?: string;
+  folder?: string;
+};
+
+export const explainFile = async (
+  path: string,
+  filters: ScanFilters,
+  size?: number,
+  root?: string,
+): Promise<FileExplanation> => {
+  return invokeCommand<FileExplanation>("explain_file", {
+    path,
+    size,
+    filters,
+    root,
+  });
+};
+
+export type ExportProgress = {
+  id?: string;
+  lines: number;
+  totalBytes: number;
+  truncatedByBudget: boolean;
+  durationMs: number;
+};
+
+export const scanToNdjson = async (
+  path: string,
+  outPath: string,
+  options: ScanOptions,
+  id?: string,
+): Promise<void> => {
+  return invokeCommand<void>("scan_to_ndjson", { path, outPath, options, id });
+};
+
+export type ArchiveCandidate = {
+  path: string;
+  name: string;
+  sizeBytes: number;
+  modified: number;
+  ageDays: number;
+};
+
+export type ArchiveCandidatesReport = {
+  id?: string;
+  files: ArchiveCandidate[];
+  matchedFiles: number;
+  matchedBytes: number;
+  scannedFiles: number;
+  minSizeBytes: number;
+  minAgeDays: number;
+  truncatedByBudget: boolean;
+  durationMs: number;
+};
+
+export const findArchiveCandidates = async (
+  path: string,
+  options: ScanOptions,
+  minSizeBytes?: number,
+  minAgeDays?: number,
+  limit?: number,
+  id?: string,
+): Promise<void> => {
+  return invokeCommand<void>("find_archive_candidates", {
+    path,
+    options,
+    minSizeBytes,
+    minAgeDays,
+    limit,
+    id,
+  });
+};
+
+export type CompressionEstimate = {
+  id?: string;
+  totalFiles: number;
+  totalBytes: number;
+  sampledFiles: number;
+  sampledBytes: number;
+  compressedSampleBytes: number;
+  coverage: number;
+  estimatedRatio: number;
+  estimatedCompressedBytes: number;
+  truncatedByBudget: boolean;
+  durationMs: number;
+};
+
+export const estimateCompression = async (
+  path: string,
+  options: ScanOptions,
+  samples?: number,
+  id?: string,
+): Promise<void> => {
+  return invokeCommand<void>("estimate_compression", {
+    path,
+    options,
+    samples,
+    id,
+  });
+};
+
 export const cancelScan = async (): Promise<void> => {
   return invokeCommand<void>("cancel_scan");
 };
@@ -94,6 +341,22 @@ export const renameItem = async (
   return invokeCommand<void>("rename_item", { path, newPath });
 };
 
+export const movePath = async (from: string, to: string): Promise<string> => {
+  return invokeCommand<string>("move_path", { from, to });
+};
+
+export const copyPath = async (
+  from: string,
+  to: string,
+  overwrite = false,
+): Promise<void> => {
+  return invokeCommand<void>("copy_path", { from, to, overwrite });
+};
+
+export const cancelCopy = async (): Promise<void> => {
+  return invokeCommand<void>("cancel_copy");
+};
+
 export const createFolder = async (path: string): Promise<void> => {
   return invokeCommand<void>("create_folder", { path });
 };
@@ -6,6 +6,8 @@ export interface ScanNode {
   dirCount: number;
   files: ScanFile[];
   children: ScanNode[];
+  childCount?: number;
+  hasChildren?: boolean;
 }
 
 export interface ScanFile {
@@ -13,6 +15,7 @@ export interface ScanFile {
   name: string;
   sizeBytes: number;
   modified?: number;
+  kind?: string;
 }
 
 export interface ScanSummary {
@@ -22,7 +25,22 @@ export interface ScanSummary {
   fileCount: number;
   dirCount: number;
   largestFiles: ScanFile[];
+  largestDirs?: DirStat[];
   durationMs: number;
+  truncatedByBudget?: boolean;
+  filterHits?: FilterHit[];
+}
+
+export interface FilterHit {
+  field: string;
+  rule: string;
+  hits: number;
+}
+
+export interface DirStat {
+  path: string;
+  sizeBytes: number;
+  fileCount: number;
 }
 
 export interface DiskUsage {
@@ -54,16 +72,35 @@ export interface ScanFilters {
   excludeNames: string[];
   minSizeBytes: number | null;
   maxSizeBytes: number | null;
+  minSize?: string | null;
+  maxSize?: string | null;
   minModifiedTimestamp: number | null;
   maxModifiedTimestamp: number | null;
   includeRegex: string | null;
   excludeRegex: string | null;
   includePaths: string[];
   excludePaths: string[];
+  excludePathPrefixes?: string[];
+  normalizeUnicode?: boolean;
 }
 
 export interface ScanOptions {
   priorityMode: ScanPriorityMode;
   throttleLevel: ScanThrottleLevel;
   filters: ScanFilters;
+  largestFilesLimit?: number;
+  extensionSizeStats?: boolean;
+  emitEvery?: number;
+  emitIntervalMs?: number;
+  maxDepth?: number;
+  walkDepth?: number;
+  maxEntries?: number;
+  sortOrder?: ScanSortOrder;
 }
+
+export type ScanSortOrder =
+  | "sizeDesc"
+  | "sizeAsc"
+  | "nameAsc"
+  | "nameDesc"
+  | "fileCountDesc";
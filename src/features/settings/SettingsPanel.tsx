@@ -60,6 +60,7 @@ const SettingsPanel = (): JSX.Element => {
   const [tcpBind, setTcpBind] = useState("");
   const [headless, setHeadless] = useState(false);
   const [autoUpdate, setAutoUpdate] = useState(true);
+  const [rememberWindowLayouts, setRememberWindowLayouts] = useState(false);
   const [status, setStatus] = useState<string | null>(null);
   const [isSaving, setIsSaving] = useState(false);
   const [isResettingMenu, setIsResettingMenu] = useState(false);
@@ -82,6 +83,7 @@ const SettingsPanel = (): JSX.Element => {
         setTcpBind(settings.tcpBind ?? "");
         setHeadless(Boolean(settings.headless));
         setAutoUpdate(settings.autoUpdate ?? true);
+        setRememberWindowLayouts(Boolean(settings.rememberWindowLayouts));
         if (!settings.localToken && !autoTokenRef.current) {
           const generated = generateLocalToken();
           autoTokenRef.current = true;
@@ -123,6 +125,7 @@ const SettingsPanel = (): JSX.Element => {
         tcpBind: tcpBind.trim() || null,
         headless,
         autoUpdate,
+        rememberWindowLayouts,
       });
       setStatus("Saved. Restart required to apply TCP/headless changes.");
       void refreshTcpStatus(setTcpStatus);
@@ -181,7 +184,12 @@ const SettingsPanel = (): JSX.Element => {
                 : "bg-slate-800/60 text-slate-400"
             }`}
           >
-            Server: {tcpStatus?.enabled ? "Running" : "Stopped"}
+            Server:{" "}
+            {tcpStatus?.available === false
+              ? "Unavailable"
+              : tcpStatus?.enabled
+                ? "Running"
+                : "Stopped"}
           </span>
           {tcpStatus?.enabled && tcpStatus.bind ? (
             <span className="text-slate-500">{tcpStatus.bind}</span>
@@ -205,6 +213,17 @@ const SettingsPanel = (): JSX.Element => {
           />
           Enable automatic updates
         </label>
+        <label className="flex items-center gap-2 text-xs text-slate-400">
+          <input
+            type="checkbox"
+            checked={rememberWindowLayouts}
+            onChange={(event): void =>
+              setRememberWindowLayouts(event.target.checked)
+            }
+            className="h-3.5 w-3.5 accent-blue-500"
+          />
+          Remember window position for each monitor setup
+        </label>
         <button
           type="button"
           onClick={(): void => {
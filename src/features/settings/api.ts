@@ -1,5 +1,11 @@
 import { invokeCommand } from "../../lib/tauriInvoke";
-import type { AppSettings, AppSettingsUpdate, TcpStatus } from "./types";
+import type { ScanOptions } from "../scan/types";
+import type {
+  AppSettings,
+  AppSettingsUpdate,
+  ScanPreset,
+  TcpStatus,
+} from "./types";
 
 export const fetchSettings = async (): Promise<AppSettings> => {
   return invokeCommand<AppSettings>("get_settings");
@@ -11,6 +17,23 @@ export const saveSettings = async (
   return invokeCommand<AppSettings>("update_settings", { update });
 };
 
+export const listScanPresets = async (): Promise<ScanPreset[]> => {
+  return invokeCommand<ScanPreset[]>("list_scan_presets");
+};
+
+export const saveScanPreset = async (
+  name: string,
+  options: ScanOptions,
+): Promise<ScanPreset[]> => {
+  return invokeCommand<ScanPreset[]>("save_scan_preset", { name, options });
+};
+
+export const deleteScanPreset = async (
+  name: string,
+): Promise<ScanPreset[]> => {
+  return invokeCommand<ScanPreset[]>("delete_scan_preset", { name });
+};
+
 export const fetchTcpStatus = async (): Promise<TcpStatus> => {
   return invokeCommand<TcpStatus>("get_tcp_status");
 };
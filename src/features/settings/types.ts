@@ -1,8 +1,16 @@
+import type { ScanOptions } from "../scan/types";
+
 export interface AppSettings {
   localToken: string | null;
   tcpBind: string | null;
   headless: boolean | null;
   autoUpdate: boolean | null;
+  rememberWindowLayouts: boolean | null;
+}
+
+export interface ScanPreset {
+  name: string;
+  options: ScanOptions;
 }
 
 export interface AppSettingsUpdate {
@@ -10,9 +18,17 @@ export interface AppSettingsUpdate {
   tcpBind?: string | null;
   headless?: boolean | null;
   autoUpdate?: boolean | null;
+  rememberWindowLayouts?: boolean | null;
+}
+
+/** Rejection from `update_settings`; `field` names the input at fault. */
+export interface SettingsError {
+  field?: "tcpBind" | "localToken";
+  message: string;
 }
 
 export interface TcpStatus {
+  available: boolean;
   enabled: boolean;
   bind: string | null;
 }
@@ -1,5 +1,5 @@
 import { check } from "@tauri-apps/plugin-updater";
-import { relaunch } from "@tauri-apps/plugin-process";
+import { invokeCommand } from "./tauriInvoke";
 import { toErrorMessage } from "./utils";
 
 const logInfo = (message: string): void => {
@@ -16,7 +16,7 @@ export const installUpdateIfAvailable = async (): Promise<void> => {
     if (!update) return;
     logInfo(`Update ${update.currentVersion} -> ${update.version}`);
     await update.downloadAndInstall();
-    await relaunch();
+    await invokeCommand<void>("restart_app");
   } catch (error) {
     logError(error);
   }
@@ -16,6 +16,15 @@ export const toErrorMessage = (error: unknown): string => {
   if (typeof error === "string") {
     return error || "Unknown error";
   }
+  // Commands such as `update_settings` fail with `{ field?, message }`.
+  if (
+    typeof error === "object" &&
+    error !== null &&
+    "message" in error &&
+    typeof error.message === "string"
+  ) {
+    return error.message || "Unknown error";
+  }
   return "Unknown error";
 };
 